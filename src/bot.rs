@@ -1,43 +1,181 @@
+use std::collections::HashMap;
 use std::env;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use serenity::all::{ChannelId, GuildId, Interaction};
+use anyhow::Context as _;
+use reqwest::Url;
+use serde::Serialize;
+use serenity::all::{
+    ChannelId, Command, CommandInteraction, GuildId, Interaction, RoleId, WebhookId,
+};
 use serenity::async_trait;
 use serenity::builder::{
-    CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
-    CreateMessage,
+    Builder, CreateActionRow, CreateAllowedMentions, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+    CreateMessage, ExecuteWebhook,
 };
+use serenity::http::{CacheHttp, Http};
 use serenity::model::gateway::Ready;
 use serenity::prelude::*;
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-use crate::commands;
-use crate::esi::EsiID;
+use crate::commands::{self, set_channel::ChannelKind};
+use crate::esi::{Corporation, EsiError, EsiID, SecurityClass};
+use crate::health::unix_now;
+use crate::quiet_hours::QuietHours;
+use crate::rate_limit::{spawn_channel_queue, MessageRate};
+use crate::services::adm_acks::AdmAcks;
 use crate::services::adm_configuration::AdmConfiguration;
+use crate::services::adm_notification_service::AdmRefreshSender;
 use crate::services::adm_service::{AdmService, Status, SystemAdm};
+use crate::services::channel_config::{ChannelConfig, NotificationChannels};
+use crate::services::corp_watch::CorpWatchList;
+use crate::services::event_log::EventLog;
 use crate::services::information_service::InformationService;
+use crate::services::standings::{Standing, Standings};
+use crate::services::vulnerability_service::VulnerabilityWindow;
+use crate::shutdown::{self, ShutdownReceiver};
+use crate::sink::NotificationSink;
+use crate::theme::{Branding, Color, Theme};
 
-#[allow(dead_code)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Serialize)]
 pub enum BotNotification {
     NotifyCorpJoinAlliance(EsiID, EsiID),
     NotifyCorpLeftAlliance(EsiID, EsiID),
+    NotifyCorpMovedAlliance(EsiID, EsiID, EsiID),
+    NotifyAllianceCreated(EsiID),
+    NotifyAllianceClosed(EsiID),
+    NotifyMemberChange(EsiID, u64, u64),
     NotifyAdm(SystemAdm),
+    NotifyAdmRecovered(SystemAdm),
+    NotifyAdmStillCritical(SystemAdm, u64),
+    NotifySovLost(EsiID),
+    NotifyVulnerable(EsiID, VulnerabilityWindow),
+    AdmDigest(Vec<SystemAdm>),
+    AdmStartupReport(Vec<SystemAdm>),
 }
 
 pub type BotResult = anyhow::Result<()>;
 
+const CORP_BATCH_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct BotConfig {
+    pub channels: ChannelConfig,
+    pub corp_min_members: u64,
+    pub adm_critical_ping_role_id: Option<u64>,
+    pub dry_run: bool,
+    pub theme: Theme,
+    pub branding: Branding,
+    pub corp_filter: CorpFilter,
+    pub standings: Standings,
+    pub message_rate: MessageRate,
+    pub quiet_hours: Option<QuietHours>,
+    pub webhook: Option<DiscordWebhook>,
+    pub sinks: Vec<Arc<dyn NotificationSink>>,
+    pub departure_alert: DepartureAlert,
+}
+
+impl BotConfig {
+    fn corp_embed_options(
+        &self,
+        alliance_id: EsiID,
+        watched: bool,
+        color: Color,
+    ) -> CorpEmbedOptions<'_> {
+        let standing = self.standings.get(alliance_id);
+
+        CorpEmbedOptions {
+            min_members: corp_min_members(self.corp_min_members, watched),
+            filter: self.corp_filter,
+            color: self.theme.standing_color(standing, color),
+            standing,
+            branding: &self.branding,
+        }
+    }
+}
+
+// Corporations leaving our own alliance with more than `min_members` members get a
+// separate high priority notification.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DepartureAlert {
+    pub alliance_id: EsiID,
+    pub min_members: Option<u64>,
+    pub ping_role_id: Option<u64>,
+}
+
+impl DepartureAlert {
+    fn watches(&self, alliance_id: EsiID) -> bool {
+        self.min_members.is_some() && alliance_id == self.alliance_id
+    }
+
+    fn is_major(&self, alliance_id: EsiID, member_count: u64) -> bool {
+        self.watches(alliance_id)
+            && self
+                .min_members
+                .is_some_and(|min_members| member_count > min_members)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CorpFilter {
+    pub skip_war_ineligible: bool,
+    pub skip_faction: bool,
+    pub sov_security: Option<SecurityClass>,
+}
+
+pub struct BotServices {
+    pub information: InformationService,
+    pub adm_configuration: AdmConfiguration,
+    pub adm_service: AdmService,
+    pub corp_watch: CorpWatchList,
+    pub adm_refresh: AdmRefreshSender,
+    pub event_log: EventLog,
+    pub adm_acks: AdmAcks,
+}
+
 struct Bot {
-    notify_corp_channel_id: u64,
-    notify_adm_channel_id: u64,
+    config: BotConfig,
     information: InformationService,
     adm_service: AdmService,
     adm_configuration: AdmConfiguration,
+    corp_watch: CorpWatchList,
+    adm_refresh: AdmRefreshSender,
+    event_log: EventLog,
+    adm_acks: AdmAcks,
     command_receiver: RwLock<Option<UnboundedReceiver<BotNotification>>>,
 }
 
 #[async_trait]
 impl EventHandler for Bot {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::Component(component) = &interaction {
+            // Page and import buttons are answered by the collector waiting on the message.
+            if commands::pagination::is_page_button(&component.data.custom_id)
+                || commands::adm_import::is_import_button(&component.data.custom_id)
+            {
+                tracing::debug!(
+                    custom_id = component.data.custom_id,
+                    user = component.user.name,
+                    "collector button interaction"
+                );
+            } else if commands::adm_ack::is_ack_button(&component.data.custom_id) {
+                if let Err(err) = commands::adm_ack::run(&ctx, component, &self.adm_acks).await {
+                    tracing::error!(?err, "couldn't acknowledge adm alert");
+                }
+            } else {
+                tracing::warn!(
+                    custom_id = component.data.custom_id,
+                    "unhandled component interaction"
+                );
+            }
+
+            return;
+        }
+
         if let Interaction::Command(command) = interaction {
             tracing::info!(
                 name = command.data.name,
@@ -45,30 +183,126 @@ impl EventHandler for Bot {
                 "command interaction"
             );
 
-            let content = match command.data.name.as_str() {
+            let result = match command.data.name.as_str() {
                 commands::adm::COMMAND_NAME => {
-                    commands::adm::run(&ctx, &command, &self.information, &self.adm_service)
-                        .await
-                        .unwrap();
-
-                    None
+                    commands::adm::run(
+                        &ctx,
+                        &command,
+                        &self.information,
+                        &self.adm_service,
+                        &self.config.branding,
+                    )
+                    .await
                 }
                 commands::adm_configure::COMMAND_NAME => {
-                    commands::adm_configure::run(&ctx, &command, &self.adm_configuration)
+                    commands::adm_configure::run(
+                        &ctx,
+                        &command,
+                        &self.adm_configuration,
+                        &self.information,
+                    )
+                    .await
+                }
+                commands::adm_configure_thresholds::COMMAND_NAME => {
+                    commands::adm_configure_thresholds::run(
+                        &ctx,
+                        &command,
+                        &self.adm_configuration,
+                        &self.information,
+                    )
+                    .await
+                }
+                #[cfg(feature = "adm-history")]
+                commands::adm_history::COMMAND_NAME => {
+                    commands::adm_history::run(&ctx, &command, &self.information, &self.adm_service)
                         .await
-                        .unwrap();
-
-                    None
                 }
-                _ => Some("Command not implemented!".to_string()),
+                commands::adm_thresholds::COMMAND_NAME => {
+                    commands::adm_thresholds::run(&ctx, &command).await
+                }
+                commands::adm_list::COMMAND_NAME => {
+                    commands::adm_list::run(&ctx, &command, &self.adm_configuration).await
+                }
+                commands::adm_refresh::COMMAND_NAME => {
+                    commands::adm_refresh::run(&ctx, &command, &self.adm_refresh).await
+                }
+                commands::adm_reload::COMMAND_NAME => {
+                    commands::adm_reload::run(&ctx, &command, &self.adm_configuration).await
+                }
+                commands::adm_include_tcus::COMMAND_NAME => {
+                    commands::adm_include_tcus::run(&ctx, &command, &self.adm_service).await
+                }
+                commands::sov::COMMAND_NAME => {
+                    commands::sov::run(
+                        &ctx,
+                        &command,
+                        &self.information,
+                        &self.adm_service,
+                        &self.config.branding,
+                    )
+                    .await
+                }
+                commands::system::COMMAND_NAME => {
+                    commands::system::run(
+                        &ctx,
+                        &command,
+                        &self.information,
+                        &self.adm_service,
+                        &self.config.branding,
+                    )
+                    .await
+                }
+                commands::adm_export::COMMAND_NAME => {
+                    commands::adm_export::run(&ctx, &command, &self.adm_configuration).await
+                }
+                commands::adm_import::COMMAND_NAME => {
+                    commands::adm_import::run(&ctx, &command, &self.adm_configuration).await
+                }
+                commands::set_channel::ADM_COMMAND_NAME => {
+                    commands::set_channel::run(
+                        &ctx,
+                        &command,
+                        &self.config.channels,
+                        ChannelKind::Adm,
+                    )
+                    .await
+                }
+                commands::set_channel::CORP_COMMAND_NAME => {
+                    commands::set_channel::run(
+                        &ctx,
+                        &command,
+                        &self.config.channels,
+                        ChannelKind::Corp,
+                    )
+                    .await
+                }
+                commands::corp_watch::COMMAND_NAME => {
+                    commands::corp_watch::run(&ctx, &command, &self.information, &self.corp_watch)
+                        .await
+                }
+                commands::adm_unconfigure::COMMAND_NAME => {
+                    commands::adm_unconfigure::run(&ctx, &command, &self.adm_configuration).await
+                }
+                commands::recent::COMMAND_NAME => {
+                    commands::recent::run(&ctx, &command, &self.information, &self.event_log).await
+                }
+                commands::whois::COMMAND_NAME => {
+                    commands::whois::run(&ctx, &command, &self.information).await
+                }
+                _ => {
+                    let data =
+                        CreateInteractionResponseMessage::new().content("Command not implemented!");
+                    command
+                        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+                        .await
+                        .map_err(anyhow::Error::from)
+                }
             };
 
-            if let Some(content) = content {
-                let data = CreateInteractionResponseMessage::new().content(content);
-                let builder = CreateInteractionResponse::Message(data);
-                if let Err(why) = command.create_response(&ctx.http, builder).await {
-                    tracing::error!(?why, "couldn't create command response");
-                }
+            if let Err(err) = result {
+                tracing::error!(?err, name = command.data.name, "command failed");
+
+                respond_error(&ctx, &command, command_error_message(&err)).await;
             }
         }
     }
@@ -76,242 +310,1841 @@ impl EventHandler for Bot {
     async fn ready(&self, ctx: Context, ready: Ready) {
         tracing::info!(bot_name = ready.user.name, "connected");
 
-        let guild_id = GuildId::new(
-            env::var("DISCORD_GUILD_ID")
-                .expect("`DISCORD_GUILD_ID` configuration variable")
-                .parse()
-                .expect("`DISCORD_GUILD_ID` is an integer"),
+        let guild_ids = parse_guild_ids(
+            &env::var("DISCORD_GUILD_IDS")
+                .or_else(|_| env::var("DISCORD_GUILD_ID"))
+                .unwrap_or_default(),
         );
 
-        let commands = guild_id
-            .set_commands(
-                &ctx.http,
-                vec![
-                    commands::adm::register(),
-                    commands::adm_configure::register(),
-                ],
-            )
-            .await;
+        #[allow(unused_mut)]
+        let mut command_list = vec![
+            commands::adm::register(),
+            commands::adm_configure::register(),
+            commands::adm_configure_thresholds::register(),
+            commands::adm_export::register(),
+            commands::adm_import::register(),
+            commands::adm_include_tcus::register(),
+            commands::adm_list::register(),
+            commands::adm_refresh::register(),
+            commands::adm_reload::register(),
+            commands::adm_thresholds::register(),
+            commands::adm_unconfigure::register(),
+            commands::corp_watch::register(),
+            commands::recent::register(),
+            commands::set_channel::register(ChannelKind::Adm),
+            commands::set_channel::register(ChannelKind::Corp),
+            commands::sov::register(),
+            commands::system::register(),
+            commands::whois::register(),
+        ];
+
+        #[cfg(feature = "adm-history")]
+        command_list.push(commands::adm_history::register());
 
-        tracing::info!(?guild_id, ?commands, "registered commands");
-
-        if let Some(mut receiver) = self.command_receiver.write().await.take() {
-            let information = self.information.clone();
-
-            let ctx = Arc::new(ctx);
-            let corp_channel_id = self.notify_corp_channel_id;
-            let adm_channel_id = self.notify_adm_channel_id;
-
-            tokio::spawn(async move {
-                loop {
-                    let command = receiver.recv().await;
-
-                    match command {
-                        Some(command) => {
-                            send_notification(
-                                &ctx,
-                                corp_channel_id,
-                                adm_channel_id,
-                                &information,
-                                command,
-                            )
-                            .await
-                        }
-                        None => {
-                            tracing::warn!("channel closed, stopping command loop");
-                            break;
-                        }
-                    };
+        if guild_ids.is_empty() {
+            match Command::set_global_commands(&ctx.http, command_list).await {
+                Ok(commands) => {
+                    tracing::info!(commands = commands.len(), "registered global commands")
                 }
-            });
+                Err(err) => tracing::error!(?err, "couldn't register global commands"),
+            }
+        } else {
+            for guild_id in guild_ids {
+                match guild_id.set_commands(&ctx.http, command_list.clone()).await {
+                    Ok(commands) => {
+                        tracing::info!(?guild_id, commands = commands.len(), "registered commands")
+                    }
+                    Err(err) => tracing::error!(?guild_id, ?err, "couldn't register commands"),
+                }
+            }
+        }
+
+        if let Some(receiver) = self.command_receiver.write().await.take() {
+            let discord = DiscordSink {
+                http: ctx.http.clone(),
+                config: self.config.clone(),
+                information: self.information.clone(),
+                corp_watch: self.corp_watch.clone(),
+                adm_service: self.adm_service.clone(),
+                adm_acks: self.adm_acks.clone(),
+            };
+
+            tokio::spawn(notification_loop(discord, self.event_log.clone(), receiver));
         }
     }
 }
 
-async fn send_corp_notification(
-    ctx: &Context,
-    channel_id: u64,
-    info: &InformationService,
-    alliance_id: EsiID,
-    corporation_id: EsiID,
-    msg: &str,
+// Errors from ESI are worth telling apart since they usually clear up on their own,
+// anything else stays generic so internal details aren't shown in the channel.
+fn command_error_message(err: &anyhow::Error) -> &'static str {
+    if err.chain().any(|cause| cause.is::<EsiError>()) {
+        "EVE's ESI API is unavailable right now, please try again later."
+    } else {
+        "Something went wrong, please try again later."
+    }
+}
+
+// The command may already have deferred or responded before failing, in which case the
+// error is sent as a followup instead.
+async fn respond_error(ctx: &Context, command: &CommandInteraction, content: &str) {
+    let data = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+
+    if command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+        .is_ok()
+    {
+        return;
+    }
+
+    let followup = CreateInteractionResponseFollowup::new()
+        .content(content)
+        .ephemeral(true);
+
+    if let Err(why) = command.create_followup(&ctx.http, followup).await {
+        tracing::error!(?why, "couldn't send command error response");
+    }
+}
+
+type QueuedNotification = (BotNotification, Arc<HashMap<EsiID, String>>);
+
+// Notifications are always posted to discord and mirrored to the configured sinks.
+async fn notification_loop(
+    discord: DiscordSink,
+    event_log: EventLog,
+    mut receiver: UnboundedReceiver<BotNotification>,
 ) {
-    tracing::info!(alliance_id, corporation_id, msg, "send corp notification");
+    let discord = Arc::new(discord);
+    let mut channel_queues: HashMap<u64, UnboundedSender<QueuedNotification>> = HashMap::new();
 
-    let res = tokio::try_join!(
-        info.get_alliance(alliance_id),
-        info.get_corporation(corporation_id)
-    );
+    loop {
+        let Some(batch) = receive_batch(&mut receiver, CORP_BATCH_WINDOW).await else {
+            tracing::warn!("channel closed, stopping command loop");
+            break;
+        };
 
-    match res {
-        Ok((alliance, corporation)) => {
-            tracing::debug!(alliance_id, corporation_id, "esi data");
+        let alliance_names = Arc::new(batch_alliance_names(&discord.information, &batch).await);
 
-            if corporation.member_count < 10 {
-                return;
-            }
+        for command in batch {
+            let Some(channel_id) = accept_notification(&discord.config, &event_log, &command).await
+            else {
+                continue;
+            };
 
-            let alliance_link = format!(
-                "https://evemaps.dotlan.net/alliance/{}",
-                alliance.name.replace(' ', "_")
-            );
-            let corporation_link = format!(
-                "https://evemaps.dotlan.net/corp/{}",
-                corporation.name.replace(' ', "_")
-            );
+            let queue = channel_queues.entry(channel_id).or_insert_with(|| {
+                let discord = discord.clone();
 
-            let embed = CreateEmbed::new()
-                .title(msg)
-                .field(
-                    "Corporation",
-                    format!(
-                        "{} ([{}]({}))",
-                        corporation.name, corporation.ticker, corporation_link
-                    ),
-                    false,
-                )
-                .field(
-                    "Member Count",
-                    format!("{}", corporation.member_count),
-                    false,
-                )
-                .field(
-                    "Alliance",
-                    format!(
-                        "{} ([{}]({}))",
-                        alliance.name, alliance.ticker, alliance_link
-                    ),
-                    false,
+                spawn_channel_queue(
+                    discord.config.message_rate,
+                    channel_id,
+                    move |(command, alliance_names): QueuedNotification| {
+                        let discord = discord.clone();
+
+                        async move { send_notification(&discord, &alliance_names, command).await }
+                    },
                 )
-                .color((188, 69, 255));
+            });
+
+            if queue.send((command, alliance_names.clone())).is_err() {
+                tracing::error!(channel_id, "channel queue stopped, notification dropped");
+            }
+        }
+    }
+}
+
+fn is_corp_notification(notification: &BotNotification) -> bool {
+    matches!(
+        notification,
+        BotNotification::NotifyCorpJoinAlliance(..)
+            | BotNotification::NotifyCorpLeftAlliance(..)
+            | BotNotification::NotifyCorpMovedAlliance(..)
+    )
+}
+
+// Corp notifications tend to arrive in bursts, so the ones arriving shortly after each
+// other are collected and their ESI data fetched together.
+async fn receive_batch(
+    receiver: &mut UnboundedReceiver<BotNotification>,
+    window: Duration,
+) -> Option<Vec<BotNotification>> {
+    let notification = receiver.recv().await?;
+
+    if !is_corp_notification(&notification) {
+        return Some(vec![notification]);
+    }
+
+    let deadline = tokio::time::Instant::now() + window;
+    let mut batch = vec![notification];
 
-            let builder = CreateMessage::new().embed(embed);
-            let message = ChannelId::new(channel_id).send_message(&ctx, builder).await;
+    while let Ok(Some(notification)) = tokio::time::timeout_at(deadline, receiver.recv()).await {
+        batch.push(notification);
+    }
+
+    Some(batch)
+}
 
-            tracing::debug!(?message, "composed message");
+fn corp_notification_ids(notifications: &[BotNotification]) -> (Vec<EsiID>, Vec<EsiID>) {
+    let mut alliance_ids = vec![];
+    let mut corporation_ids = vec![];
 
-            if let Err(err) = message {
-                tracing::error!(?err, "error sending notification");
+    for notification in notifications {
+        match notification {
+            BotNotification::NotifyCorpJoinAlliance(alliance_id, corporation_id)
+            | BotNotification::NotifyCorpLeftAlliance(alliance_id, corporation_id) => {
+                alliance_ids.push(*alliance_id);
+                corporation_ids.push(*corporation_id);
+            }
+            BotNotification::NotifyCorpMovedAlliance(
+                corporation_id,
+                from_alliance_id,
+                to_alliance_id,
+            ) => {
+                alliance_ids.extend([*from_alliance_id, *to_alliance_id]);
+                corporation_ids.push(*corporation_id);
             }
+            _ => {}
         }
-        Err(err) => tracing::error!(?err, "error fetching esi data"),
     }
-}
 
-async fn send_adm_notification(
-    ctx: &Context,
-    channel_id: u64,
-    info: &InformationService,
-    system_adm: SystemAdm,
-) {
-    tracing::info!(?system_adm, "send adm notification");
+    alliance_ids.sort_unstable();
+    alliance_ids.dedup();
+    corporation_ids.sort_unstable();
+    corporation_ids.dedup();
 
-    match info.get_system(system_adm.system_id).await {
-        Ok(system) => {
-            if let Some((msg, footer, adm, color)) = match system_adm.status {
-                Status::Warning(adm) => Some((
-                    format!("{} ADM is deteriorated!", system.name),
-                    "Please do some ratting or mining here.",
-                    adm,
-                    (238, 210, 2),
-                )),
-                Status::Critical(adm) => Some((
-                    format!("{} ADM is critically low!", system.name),
-                    "Do ratting or mining here ASAP!!!",
-                    adm,
-                    (255, 103, 0),
-                )),
-                _ => None,
-            } {
-                let system_link = format!("https://evemaps.dotlan.net/system/{}", system.name);
+    (alliance_ids, corporation_ids)
+}
 
-                let embed = CreateEmbed::new()
-                    .title(msg)
-                    .field(
-                        "System",
-                        format!("[{}]({})", system.name, system_link),
-                        true,
-                    )
-                    .field("ADM", format!("{}", adm), true)
-                    .footer(CreateEmbedFooter::new(footer))
-                    .color(color);
+// The alliances of a batch only need their names, which are resolved with a single
+// `universe/names` request while the corporations are fetched concurrently.
+async fn batch_alliance_names(
+    information: &InformationService,
+    batch: &[BotNotification],
+) -> HashMap<EsiID, String> {
+    let (alliance_ids, corporation_ids) = corp_notification_ids(batch);
 
-                let builder = CreateMessage::new().embed(embed);
-                let message = ChannelId::new(channel_id).send_message(&ctx, builder).await;
+    if alliance_ids.is_empty() {
+        return HashMap::new();
+    }
 
-                tracing::debug!(?message, "composed message");
+    let (alliance_names, _) = tokio::join!(
+        information.get_alliance_names(&alliance_ids),
+        information.prefetch(&corporation_ids)
+    );
 
-                if let Err(err) = message {
-                    tracing::error!(?err, "error sending notification");
-                }
+    alliance_names.unwrap_or_else(|err| {
+        tracing::warn!(
+            ?err,
+            "couldn't resolve alliance names, fetching them one by one"
+        );
+        HashMap::new()
+    })
+}
+
+fn parse_guild_ids(guild_ids: &str) -> Vec<GuildId> {
+    guild_ids
+        .split(',')
+        .map(str::trim)
+        .filter(|guild_id| !guild_id.is_empty())
+        .filter_map(|guild_id| match guild_id.parse::<u64>() {
+            Ok(0) => {
+                tracing::warn!(guild_id, "ignoring invalid guild id");
+                None
+            }
+            Ok(id) => Some(GuildId::new(id)),
+            Err(err) => {
+                tracing::warn!(guild_id, ?err, "ignoring invalid guild id");
+                None
             }
+        })
+        .collect()
+}
+
+fn passes_member_filter(member_count: u64, min_members: u64) -> bool {
+    min_members == 0 || member_count >= min_members
+}
+
+fn passes_corp_filter(corporation: &Corporation, filter: CorpFilter) -> bool {
+    if filter.skip_war_ineligible && corporation.war_eligible == Some(false) {
+        return false;
+    }
+
+    !(filter.skip_faction && corporation.faction_id.is_some())
+}
+
+// With a security class configured, corp notifications are only sent for alliances holding
+// sovereignty in a system of that class.
+async fn passes_sov_filter(
+    adm_service: &AdmService,
+    sov_security: Option<SecurityClass>,
+    alliance_ids: &[EsiID],
+) -> anyhow::Result<bool> {
+    let Some(sov_security) = sov_security else {
+        return Ok(true);
+    };
+
+    for alliance_id in alliance_ids {
+        if adm_service
+            .sov_security_classes(*alliance_id)
+            .await?
+            .contains(&sov_security)
+        {
+            return Ok(true);
         }
-        Err(err) => tracing::error!(?err, "error fetching esi data"),
     }
+
+    Ok(false)
 }
 
-async fn send_notification(
-    ctx: &Context,
-    corp_channel_id: u64,
-    adm_channel_id: u64,
-    info: &InformationService,
-    command: BotNotification,
-) {
-    match command {
-        BotNotification::NotifyCorpJoinAlliance(alliance_id, corporation_id) => {
-            send_corp_notification(
-                ctx,
-                corp_channel_id,
-                info,
-                alliance_id,
-                corporation_id,
-                "Joined Alliance",
-            )
-            .await;
+fn corp_min_members(min_members: u64, watched: bool) -> u64 {
+    if watched {
+        0
+    } else {
+        min_members
+    }
+}
+
+fn notification_channel_id(channels: NotificationChannels, notification: &BotNotification) -> u64 {
+    match notification {
+        BotNotification::NotifyCorpJoinAlliance(..)
+        | BotNotification::NotifyCorpLeftAlliance(..)
+        | BotNotification::NotifyCorpMovedAlliance(..)
+        | BotNotification::NotifyAllianceCreated(_)
+        | BotNotification::NotifyAllianceClosed(_)
+        | BotNotification::NotifyMemberChange(..) => channels.corp,
+        BotNotification::NotifyAdm(_)
+        | BotNotification::NotifyAdmRecovered(_)
+        | BotNotification::NotifyAdmStillCritical(..)
+        | BotNotification::NotifySovLost(_)
+        | BotNotification::NotifyVulnerable(..)
+        | BotNotification::AdmDigest(_)
+        | BotNotification::AdmStartupReport(_) => channels.adm,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscordWebhook {
+    id: WebhookId,
+    token: String,
+}
+
+impl FromStr for DiscordWebhook {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<DiscordWebhook> {
+        let url: Url = value.trim().parse()?;
+
+        let (id, token) = serenity::utils::parse_webhook(&url)
+            .ok_or_else(|| anyhow::anyhow!("not a discord webhook url"))?;
+
+        Ok(DiscordWebhook {
+            id,
+            token: token.to_string(),
+        })
+    }
+}
+
+// Embed and role ping of a notification, rendered either as a channel message or as a
+// webhook execution.
+#[derive(Debug, Clone)]
+struct NotificationMessage {
+    embed: CreateEmbed,
+    ping_role_id: Option<u64>,
+    components: Vec<CreateActionRow>,
+}
+
+impl NotificationMessage {
+    fn new(embed: CreateEmbed) -> Self {
+        NotificationMessage {
+            embed,
+            ping_role_id: None,
+            components: vec![],
         }
-        BotNotification::NotifyCorpLeftAlliance(alliance_id, corporation_id) => {
-            send_corp_notification(
-                ctx,
-                corp_channel_id,
-                info,
-                alliance_id,
-                corporation_id,
-                "Left Alliance",
+    }
+
+    fn with_components(mut self, components: Vec<CreateActionRow>) -> Self {
+        self.components = components;
+        self
+    }
+
+    fn with_ping_role_id(mut self, ping_role_id: Option<u64>) -> Self {
+        self.ping_role_id = ping_role_id;
+        self
+    }
+
+    fn role_mention(&self) -> Option<(String, CreateAllowedMentions)> {
+        self.ping_role_id.map(|role_id| {
+            (
+                format!("<@&{}>", role_id),
+                CreateAllowedMentions::new().roles([RoleId::new(role_id)]),
             )
-            .await;
+        })
+    }
+
+    fn message(self) -> CreateMessage {
+        let message = match self.role_mention() {
+            Some((content, allowed_mentions)) => CreateMessage::new()
+                .embed(self.embed)
+                .content(content)
+                .allowed_mentions(allowed_mentions),
+            None => CreateMessage::new().embed(self.embed),
+        };
+
+        if self.components.is_empty() {
+            message
+        } else {
+            message.components(self.components)
         }
-        BotNotification::NotifyAdm(adm_status) => {
-            send_adm_notification(ctx, adm_channel_id, info, adm_status).await;
+    }
+
+    // Only application owned webhooks may post components, and nothing would answer the
+    // interactions anyway, so buttons are left out.
+    fn webhook(self) -> ExecuteWebhook {
+        match self.role_mention() {
+            Some((content, allowed_mentions)) => ExecuteWebhook::new()
+                .embed(self.embed)
+                .content(content)
+                .allowed_mentions(allowed_mentions),
+            None => ExecuteWebhook::new().embed(self.embed),
         }
-    };
+    }
 }
 
-pub async fn run(
-    info: InformationService,
-    adm_configuration: AdmConfiguration,
-    adm: AdmService,
-    receiver: UnboundedReceiver<BotNotification>,
-    token: String,
-    notify_adm_channel_id: u64,
-    notify_corp_channel_id: u64,
-) -> BotResult {
-    let intents = GatewayIntents::GUILD_MESSAGES;
+#[derive(Debug, Clone, PartialEq)]
+enum NotificationTarget {
+    Channel(u64),
+    Webhook(DiscordWebhook),
+    DryRun(u64),
+}
 
-    let bot = Bot {
-        notify_adm_channel_id,
-        notify_corp_channel_id,
-        adm_configuration,
-        information: info,
-        adm_service: adm,
-        command_receiver: RwLock::new(Some(receiver)),
-    };
+impl NotificationTarget {
+    async fn send(
+        self,
+        cache_http: impl CacheHttp,
+        message: NotificationMessage,
+    ) -> anyhow::Result<()> {
+        match self {
+            NotificationTarget::Channel(channel_id) => {
+                let message = ChannelId::new(channel_id)
+                    .send_message(cache_http, message.message())
+                    .await
+                    .context("couldn't send discord message")?;
 
-    let mut client = Client::builder(&token, intents).event_handler(bot).await?;
+                tracing::debug!(?message, "composed message");
+            }
+            NotificationTarget::Webhook(webhook) => {
+                message
+                    .webhook()
+                    .execute(cache_http, (webhook.id, &webhook.token, false))
+                    .await
+                    .context("couldn't execute discord webhook")?;
 
-    client
-        .start()
-        .await
-        .map_err(|err| anyhow::Error::from(err))?;
+                tracing::debug!(webhook_id = ?webhook.id, "executed webhook");
+            }
+            NotificationTarget::DryRun(channel_id) => {
+                tracing::info!(channel_id, ?message, "dry run, notification not sent");
+            }
+        }
 
-    Ok(())
+        Ok(())
+    }
+}
+
+fn notification_target(
+    config: &BotConfig,
+    channels: NotificationChannels,
+    notification: &BotNotification,
+) -> NotificationTarget {
+    let channel_id = notification_channel_id(channels, notification);
+
+    if config.dry_run {
+        NotificationTarget::DryRun(channel_id)
+    } else if let Some(webhook) = &config.webhook {
+        NotificationTarget::Webhook(webhook.clone())
+    } else {
+        NotificationTarget::Channel(channel_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CorpEmbedOptions<'a> {
+    min_members: u64,
+    filter: CorpFilter,
+    color: Color,
+    standing: Option<Standing>,
+    branding: &'a Branding,
+}
+
+// Alliances resolved for the whole batch only need their name, anything missing from it is
+// fetched on its own.
+async fn alliance_name(
+    info: &InformationService,
+    alliance_names: &HashMap<EsiID, String>,
+    alliance_id: EsiID,
+) -> anyhow::Result<String> {
+    match alliance_names.get(&alliance_id) {
+        Some(name) => Ok(name.clone()),
+        None => Ok(info.get_alliance(alliance_id).await?.name),
+    }
+}
+
+fn dotlan_link(kind: &str, name: &str) -> String {
+    format!(
+        "https://evemaps.dotlan.net/{}/{}",
+        kind,
+        name.replace(' ', "_")
+    )
+}
+
+fn alliance_field(name: &str) -> String {
+    format!("[{}]({})", name, dotlan_link("alliance", name))
+}
+
+fn corporation_field(corporation: &Corporation) -> String {
+    format!(
+        "{} ([{}]({}))",
+        corporation.name,
+        corporation.ticker,
+        dotlan_link("corp", &corporation.name)
+    )
+}
+
+// Starts a corp notification embed, or returns `None` when the corporation is filtered out.
+fn corp_embed(
+    options: CorpEmbedOptions<'_>,
+    title: &str,
+    corporation_id: EsiID,
+    corporation: &Corporation,
+) -> Option<CreateEmbed> {
+    if !passes_member_filter(corporation.member_count, options.min_members) {
+        tracing::debug!(
+            corporation_id,
+            member_count = corporation.member_count,
+            min_members = options.min_members,
+            "corp notification suppressed by member count filter"
+        );
+        return None;
+    }
+
+    if !passes_corp_filter(corporation, options.filter) {
+        tracing::debug!(
+            corporation_id,
+            "corp notification suppressed by corp filter"
+        );
+        return None;
+    }
+
+    Some(
+        options
+            .branding
+            .embed(title)
+            .field("Corporation", corporation_field(corporation), false)
+            .field(
+                "Member Count",
+                format!("{}", corporation.member_count),
+                false,
+            )
+            .color(options.color),
+    )
+}
+
+fn corp_notification(options: CorpEmbedOptions<'_>, embed: CreateEmbed) -> NotificationMessage {
+    let embed = match options.standing {
+        Some(standing) => embed.field("Standing", standing.to_string(), false),
+        None => embed,
+    };
+
+    NotificationMessage::new(embed)
+}
+
+async fn corp_message(
+    options: CorpEmbedOptions<'_>,
+    info: &InformationService,
+    alliance_names: &HashMap<EsiID, String>,
+    alliance_id: EsiID,
+    corporation_id: EsiID,
+    msg: &str,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(alliance_id, corporation_id, msg, "send corp notification");
+
+    let (alliance_name, corporation) = tokio::try_join!(
+        alliance_name(info, alliance_names, alliance_id),
+        info.get_corporation(corporation_id)
+    )
+    .context("couldn't fetch esi data")?;
+
+    tracing::debug!(alliance_id, corporation_id, "esi data");
+
+    Ok(
+        corp_embed(options, msg, corporation_id, &corporation).map(|embed| {
+            corp_notification(
+                options,
+                embed.field("Alliance", alliance_field(&alliance_name), false),
+            )
+        }),
+    )
+}
+
+async fn corp_moved_message(
+    options: CorpEmbedOptions<'_>,
+    info: &InformationService,
+    alliance_names: &HashMap<EsiID, String>,
+    corporation_id: EsiID,
+    from_alliance_id: EsiID,
+    to_alliance_id: EsiID,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(
+        corporation_id,
+        from_alliance_id,
+        to_alliance_id,
+        "send corp moved notification"
+    );
+
+    let (corporation, from_alliance_name, to_alliance_name) = tokio::try_join!(
+        info.get_corporation(corporation_id),
+        alliance_name(info, alliance_names, from_alliance_id),
+        alliance_name(info, alliance_names, to_alliance_id)
+    )
+    .context("couldn't fetch esi data")?;
+
+    Ok(
+        corp_embed(options, "Moved Alliance", corporation_id, &corporation).map(|embed| {
+            corp_notification(
+                options,
+                embed
+                    .field("From", alliance_field(&from_alliance_name), false)
+                    .field("To", alliance_field(&to_alliance_name), false),
+            )
+        }),
+    )
+}
+
+async fn alliance_message(
+    info: &InformationService,
+    alliance_id: EsiID,
+    msg: &str,
+    color: Color,
+    branding: &Branding,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(alliance_id, msg, "send alliance notification");
+
+    match info.get_alliance(alliance_id).await {
+        Ok(alliance) => {
+            let alliance_link = dotlan_link("alliance", &alliance.name);
+
+            let embed = branding
+                .embed(msg)
+                .field(
+                    "Alliance",
+                    format!(
+                        "{} ([{}]({}))",
+                        alliance.name, alliance.ticker, alliance_link
+                    ),
+                    false,
+                )
+                .color(color);
+
+            Ok(Some(NotificationMessage::new(embed)))
+        }
+        Err(err) => Err(err.context("couldn't fetch esi data")),
+    }
+}
+
+async fn major_departure_message(
+    config: &BotConfig,
+    info: &InformationService,
+    corporation_id: EsiID,
+    from_alliance_id: EsiID,
+    to_alliance_id: Option<EsiID>,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    let departure_alert = config.departure_alert;
+
+    if !departure_alert.watches(from_alliance_id) {
+        return Ok(None);
+    }
+
+    let corporation = info
+        .get_corporation(corporation_id)
+        .await
+        .context("couldn't fetch esi data")?;
+
+    if !departure_alert.is_major(from_alliance_id, corporation.member_count) {
+        return Ok(None);
+    }
+
+    tracing::info!(
+        corporation_id,
+        from_alliance_id,
+        to_alliance_id,
+        member_count = corporation.member_count,
+        "send major departure notification"
+    );
+
+    let alliance = info
+        .get_alliance(from_alliance_id)
+        .await
+        .context("couldn't fetch esi data")?;
+
+    let embed = config
+        .branding
+        .embed(format!("🚨 Major corporation left {}", alliance.name))
+        .field("Corporation", corporation_field(&corporation), false)
+        .field(
+            "Member Count",
+            format!("{}", corporation.member_count),
+            false,
+        )
+        .color(config.theme.critical);
+
+    let embed = match to_alliance_id {
+        Some(to_alliance_id) => {
+            let to_alliance = info
+                .get_alliance(to_alliance_id)
+                .await
+                .context("couldn't fetch esi data")?;
+
+            embed.field(
+                "Joined",
+                format!("{} [{}]", to_alliance.name, to_alliance.ticker),
+                false,
+            )
+        }
+        None => embed,
+    };
+
+    Ok(Some(
+        NotificationMessage::new(embed).with_ping_role_id(departure_alert.ping_role_id),
+    ))
+}
+
+fn adm_ping_role_id(status: Status, ping_role_id: Option<u64>) -> Option<u64> {
+    match status {
+        Status::Critical(_) => ping_role_id,
+        Status::Warning(_) | Status::Good(_) => None,
+    }
+}
+
+async fn adm_message(
+    config: &BotConfig,
+    info: &InformationService,
+    system_adm: SystemAdm,
+    critical_seconds: Option<u64>,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(?system_adm, critical_seconds, "send adm notification");
+
+    let theme = &config.theme;
+
+    match info.get_system(system_adm.system_id).await {
+        Ok(system) => {
+            if let Some((msg, footer, adm, color)) = match (system_adm.status, critical_seconds) {
+                (Status::Critical(adm), Some(critical_seconds)) => Some((
+                    format!(
+                        "{} ADM is still critical after {}h!",
+                        system.name,
+                        critical_seconds / 3600
+                    ),
+                    "Do ratting or mining here ASAP!!!",
+                    adm,
+                    theme.critical,
+                )),
+                (Status::Warning(adm), _) => Some((
+                    format!("{} ADM is deteriorated!", system.name),
+                    "Please do some ratting or mining here.",
+                    adm,
+                    theme.warning,
+                )),
+                (Status::Critical(adm), None) => Some((
+                    format!("{} ADM is critically low!", system.name),
+                    "Do ratting or mining here ASAP!!!",
+                    adm,
+                    theme.critical,
+                )),
+                (Status::Good(adm), _) => Some((
+                    format!("{} ADM recovered", system.name),
+                    "Thank you for keeping the ADM up!",
+                    adm,
+                    theme.recovered,
+                )),
+            } {
+                let system_link = format!("https://evemaps.dotlan.net/system/{}", system.name);
+
+                let embed = config
+                    .branding
+                    .embed(msg)
+                    .field(
+                        "System",
+                        format!("[{}]({})", system.name, system_link),
+                        true,
+                    )
+                    .field("ADM", format!("{}", adm), true)
+                    .field("Structure", system_adm.structure_label(), true)
+                    .field("Trend", system_adm.trend().to_string(), true)
+                    .field("Importance", system_adm.importance.to_string(), true)
+                    .footer(CreateEmbedFooter::new(footer))
+                    .color(color);
+
+                let components = match system_adm.status {
+                    Status::Critical(_) | Status::Warning(_) => {
+                        vec![commands::adm_ack::ack_button(system_adm.system_id)]
+                    }
+                    Status::Good(_) => vec![],
+                };
+
+                Ok(Some(
+                    NotificationMessage::new(embed)
+                        .with_ping_role_id(adm_ping_role_id(
+                            system_adm.status,
+                            config.adm_critical_ping_role_id,
+                        ))
+                        .with_components(components),
+                ))
+            } else {
+                Ok(None)
+            }
+        }
+        Err(err) => Err(err.context("couldn't fetch esi data")),
+    }
+}
+
+async fn member_change_message(
+    config: &BotConfig,
+    info: &InformationService,
+    corporation_id: EsiID,
+    previous_members: u64,
+    members: u64,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(
+        corporation_id,
+        previous_members,
+        members,
+        "send member change notification"
+    );
+
+    let corporation = info
+        .get_corporation(corporation_id)
+        .await
+        .context("couldn't fetch esi data")?;
+
+    let alliance = match corporation.alliance_id {
+        Some(alliance_id) => Some(info.get_alliance(alliance_id).await?),
+        None => None,
+    };
+
+    let (title, color) = if members > previous_members {
+        ("Corporation Growing", config.theme.corp_join)
+    } else {
+        ("Corporation Shrinking", config.theme.corp_leave)
+    };
+
+    let embed = config
+        .branding
+        .embed(title)
+        .field("Corporation", corporation_field(&corporation), false)
+        .field(
+            "Member Count",
+            format!("{} → {}", previous_members, members),
+            false,
+        )
+        .color(color);
+
+    let embed = match alliance {
+        Some(alliance) => embed.field(
+            "Alliance",
+            format!("{} [{}]", alliance.name, alliance.ticker),
+            false,
+        ),
+        None => embed,
+    };
+
+    Ok(Some(NotificationMessage::new(embed)))
+}
+
+async fn sov_lost_message(
+    branding: &Branding,
+    info: &InformationService,
+    system_id: EsiID,
+    color: Color,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(system_id, "send sov lost notification");
+
+    match info.get_system(system_id).await {
+        Ok(system) => {
+            let system_link = format!("https://evemaps.dotlan.net/system/{}", system.name);
+
+            let embed = branding
+                .embed(format!("{} sovereignty lost!", system.name))
+                .field(
+                    "System",
+                    format!("[{}]({})", system.name, system_link),
+                    true,
+                )
+                .footer(CreateEmbedFooter::new(
+                    "The system no longer appears in our sovereignty structures.",
+                ))
+                .color(color);
+
+            Ok(Some(NotificationMessage::new(embed)))
+        }
+        Err(err) => Err(err.context("couldn't fetch esi data")),
+    }
+}
+
+async fn vulnerable_message(
+    branding: &Branding,
+    info: &InformationService,
+    system_id: EsiID,
+    window: VulnerabilityWindow,
+    color: Color,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(system_id, ?window, "send vulnerable notification");
+
+    match info.get_system(system_id).await {
+        Ok(system) => {
+            let system_link = format!("https://evemaps.dotlan.net/system/{}", system.name);
+
+            let embed = branding
+                .embed(format!("{} is becoming vulnerable!", system.name))
+                .field(
+                    "System",
+                    format!("[{}]({})", system.name, system_link),
+                    true,
+                )
+                .field(
+                    "Opens",
+                    format!("<t:{0}:f> (<t:{0}:R>)", window.start.timestamp()),
+                    true,
+                )
+                .field("Closes", format!("<t:{}:f>", window.end.timestamp()), true)
+                .color(color);
+
+            Ok(Some(NotificationMessage::new(embed)))
+        }
+        Err(err) => Err(err.context("couldn't fetch esi data")),
+    }
+}
+
+async fn adm_digest_message(
+    branding: &Branding,
+    info: &InformationService,
+    title: &str,
+    system_adms: Vec<SystemAdm>,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    tracing::info!(systems = system_adms.len(), title, "send adm digest");
+
+    let critical_systems: Vec<_> = system_adms
+        .iter()
+        .filter(|system_adm| matches!(system_adm.status, Status::Critical(_)))
+        .collect();
+
+    let warning_systems: Vec<_> = system_adms
+        .iter()
+        .filter(|system_adm| matches!(system_adm.status, Status::Warning(_)))
+        .collect();
+
+    match commands::adm::AdmReport::new(
+        info,
+        title.to_string(),
+        commands::adm::AdmHealth::from_systems(&system_adms),
+        &critical_systems,
+        Some(&warning_systems),
+    )
+    .await
+    {
+        Ok(report) => Ok(Some(NotificationMessage::new(report.summary(branding)))),
+        Err(err) => Err(err.context("couldn't compose adm digest")),
+    }
+}
+
+async fn notification_message(
+    config: &BotConfig,
+    info: &InformationService,
+    corp_watch: &CorpWatchList,
+    adm_service: &AdmService,
+    acks: &AdmAcks,
+    alliance_names: &HashMap<EsiID, String>,
+    command: BotNotification,
+) -> anyhow::Result<Option<NotificationMessage>> {
+    // Departures from our own alliance skip the filters meant for universe wide tracking.
+    let departure = match command {
+        BotNotification::NotifyCorpLeftAlliance(alliance_id, corporation_id) => {
+            major_departure_message(config, info, corporation_id, alliance_id, None).await?
+        }
+        BotNotification::NotifyCorpMovedAlliance(
+            corporation_id,
+            from_alliance_id,
+            to_alliance_id,
+        ) => {
+            major_departure_message(
+                config,
+                info,
+                corporation_id,
+                from_alliance_id,
+                Some(to_alliance_id),
+            )
+            .await?
+        }
+        _ => None,
+    };
+
+    if departure.is_some() {
+        return Ok(departure);
+    }
+
+    let sov_alliance_ids = match command {
+        BotNotification::NotifyCorpJoinAlliance(alliance_id, _)
+        | BotNotification::NotifyCorpLeftAlliance(alliance_id, _) => vec![alliance_id],
+        BotNotification::NotifyCorpMovedAlliance(_, from_alliance_id, to_alliance_id) => {
+            vec![from_alliance_id, to_alliance_id]
+        }
+        _ => vec![],
+    };
+
+    if !sov_alliance_ids.is_empty()
+        && !passes_sov_filter(
+            adm_service,
+            config.corp_filter.sov_security,
+            &sov_alliance_ids,
+        )
+        .await?
+    {
+        tracing::debug!(
+            ?sov_alliance_ids,
+            "corp notification suppressed by sov security filter"
+        );
+        return Ok(None);
+    }
+
+    match command {
+        BotNotification::NotifyCorpJoinAlliance(alliance_id, corporation_id) => {
+            let watched = corp_watch.is_watched(alliance_id).await;
+
+            corp_message(
+                config.corp_embed_options(alliance_id, watched, config.theme.corp_join),
+                info,
+                alliance_names,
+                alliance_id,
+                corporation_id,
+                "Joined Alliance",
+            )
+            .await
+        }
+        BotNotification::NotifyCorpLeftAlliance(alliance_id, corporation_id) => {
+            let watched = corp_watch.is_watched(alliance_id).await;
+
+            corp_message(
+                config.corp_embed_options(alliance_id, watched, config.theme.corp_leave),
+                info,
+                alliance_names,
+                alliance_id,
+                corporation_id,
+                "Left Alliance",
+            )
+            .await
+        }
+        BotNotification::NotifyCorpMovedAlliance(
+            corporation_id,
+            from_alliance_id,
+            to_alliance_id,
+        ) => {
+            let watched = corp_watch.is_watched(from_alliance_id).await
+                || corp_watch.is_watched(to_alliance_id).await;
+
+            corp_moved_message(
+                config.corp_embed_options(to_alliance_id, watched, config.theme.corp_join),
+                info,
+                alliance_names,
+                corporation_id,
+                from_alliance_id,
+                to_alliance_id,
+            )
+            .await
+        }
+        BotNotification::NotifyAllianceCreated(alliance_id) => {
+            alliance_message(
+                info,
+                alliance_id,
+                "Alliance Created",
+                config.theme.corp_join,
+                &config.branding,
+            )
+            .await
+        }
+        BotNotification::NotifyAllianceClosed(alliance_id) => {
+            alliance_message(
+                info,
+                alliance_id,
+                "Alliance Closed",
+                config.theme.corp_leave,
+                &config.branding,
+            )
+            .await
+        }
+        BotNotification::NotifyAdm(adm_status)
+        | BotNotification::NotifyAdmRecovered(adm_status) => {
+            adm_message(config, info, adm_status, None).await
+        }
+        BotNotification::NotifyAdmStillCritical(adm_status, critical_seconds) => {
+            if let Some(user) = acks.acknowledged_by(adm_status.system_id, unix_now()).await {
+                tracing::info!(
+                    system_id = adm_status.system_id,
+                    user,
+                    "adm alert acknowledged, skipping repeat"
+                );
+
+                return Ok(None);
+            }
+
+            adm_message(config, info, adm_status, Some(critical_seconds)).await
+        }
+        BotNotification::NotifyMemberChange(corporation_id, previous_members, members) => {
+            member_change_message(config, info, corporation_id, previous_members, members).await
+        }
+        BotNotification::NotifySovLost(system_id) => {
+            sov_lost_message(&config.branding, info, system_id, config.theme.sov_lost).await
+        }
+        BotNotification::NotifyVulnerable(system_id, window) => {
+            vulnerable_message(
+                &config.branding,
+                info,
+                system_id,
+                window,
+                config.theme.vulnerable,
+            )
+            .await
+        }
+        BotNotification::AdmDigest(system_adms) => {
+            adm_digest_message(&config.branding, info, "Daily ADM Digest", system_adms).await
+        }
+        BotNotification::AdmStartupReport(system_adms) => {
+            adm_digest_message(&config.branding, info, "Current ADM Status", system_adms).await
+        }
+    }
+}
+
+struct DiscordSink {
+    http: Arc<Http>,
+    config: BotConfig,
+    information: InformationService,
+    corp_watch: CorpWatchList,
+    adm_service: AdmService,
+    adm_acks: AdmAcks,
+}
+
+impl std::fmt::Debug for DiscordSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiscordSink")
+            .field("dry_run", &self.config.dry_run)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DiscordSink {
+    async fn send(
+        &self,
+        notification: &BotNotification,
+        alliance_names: &HashMap<EsiID, String>,
+    ) -> anyhow::Result<()> {
+        let channels = self.config.channels.channels().await;
+        let target = notification_target(&self.config, channels, notification);
+
+        let message = notification_message(
+            &self.config,
+            &self.information,
+            &self.corp_watch,
+            &self.adm_service,
+            &self.adm_acks,
+            alliance_names,
+            notification.clone(),
+        )
+        .await
+        .context("couldn't compose notification")?;
+
+        match message {
+            Some(message) => target.send(&self.http, message).await,
+            None => Ok(()),
+        }
+    }
+}
+
+// Records the notification and returns the channel it's queued for, unless it's dropped
+// during quiet hours.
+async fn accept_notification(
+    config: &BotConfig,
+    event_log: &EventLog,
+    command: &BotNotification,
+) -> Option<u64> {
+    let now = unix_now();
+
+    event_log.record(now, command.clone()).await;
+
+    if config
+        .quiet_hours
+        .is_some_and(|quiet_hours| !quiet_hours.should_send_now(command, now))
+    {
+        tracing::info!(?command, "notification dropped during quiet hours");
+        return None;
+    }
+
+    let channels = config.channels.channels().await;
+
+    Some(notification_channel_id(channels, command))
+}
+
+async fn send_notification(
+    discord: &DiscordSink,
+    alliance_names: &HashMap<EsiID, String>,
+    command: BotNotification,
+) {
+    let config = &discord.config;
+
+    if let Err(err) = discord.send(&command, alliance_names).await {
+        tracing::error!(?err, ?discord, "couldn't send notification");
+    }
+
+    for sink in &config.sinks {
+        if let Err(err) = sink.send(&command).await {
+            tracing::error!(?err, ?sink, "couldn't send notification");
+        }
+    }
+}
+
+pub async fn run(
+    services: BotServices,
+    receiver: UnboundedReceiver<BotNotification>,
+    token: String,
+    config: BotConfig,
+    mut shutdown: ShutdownReceiver,
+) -> BotResult {
+    let intents = GatewayIntents::GUILD_MESSAGES;
+
+    let bot = Bot {
+        config,
+        adm_configuration: services.adm_configuration,
+        information: services.information,
+        adm_service: services.adm_service,
+        corp_watch: services.corp_watch,
+        adm_refresh: services.adm_refresh,
+        event_log: services.event_log,
+        adm_acks: services.adm_acks,
+        command_receiver: RwLock::new(Some(receiver)),
+    };
+
+    let mut client = Client::builder(&token, intents).event_handler(bot).await?;
+
+    let shard_manager = client.shard_manager.clone();
+
+    tokio::spawn(async move {
+        shutdown::requested(&mut shutdown).await;
+
+        tracing::info!("stopping bot");
+
+        shard_manager.shutdown_all().await;
+    });
+
+    client.start().await.map_err(anyhow::Error::from)?;
+
+    Ok(())
+}
+
+// Posts notifications through a webhook without connecting to the gateway, so slash
+// commands aren't available.
+pub async fn run_webhook(
+    services: BotServices,
+    receiver: UnboundedReceiver<BotNotification>,
+    config: BotConfig,
+    mut shutdown: ShutdownReceiver,
+) -> BotResult {
+    tracing::info!("posting notifications through webhook");
+
+    tokio::select! {
+        _ = shutdown::requested(&mut shutdown) => tracing::info!("stopping webhook output"),
+        _ = notification_loop(
+            DiscordSink {
+                http: Arc::new(Http::new("")),
+                config,
+                information: services.information,
+                corp_watch: services.corp_watch,
+                adm_service: services.adm_service,
+                adm_acks: services.adm_acks,
+            },
+            services.event_log,
+            receiver,
+        ) => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+    use serenity::{all::GuildId, builder::CreateEmbed, http::HttpBuilder};
+    use std::{sync::Arc, time::Duration};
+
+    use tracing_test::traced_test;
+    use wiremock::{
+        matchers::{body_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::{
+        esi::{Corporation, Esi, EsiError, EsiID},
+        rate_limit::MessageRate,
+        services::{
+            adm_service::{Status, SystemAdm},
+            channel_config::{ChannelConfig, NotificationChannels},
+            information_service::InformationService,
+            standings::{Standing, Standings},
+        },
+    };
+
+    use super::{
+        adm_ping_role_id, batch_alliance_names, command_error_message, corp_embed,
+        corp_min_members, corp_notification_ids, notification_channel_id, notification_target,
+        parse_guild_ids, passes_corp_filter, passes_member_filter, receive_batch, BotConfig,
+        BotNotification, Branding, CorpEmbedOptions, CorpFilter, DepartureAlert, DiscordWebhook,
+        NotificationMessage, NotificationTarget, Theme,
+    };
+
+    const WEBHOOK_URL: &str = "https://discord.com/api/webhooks/245037420704169985/ig5AO-wdVWpCBtUUMxmgsWryqgsW3DChbKYOINftJ4DCrUbnkedoYZD0VOH1QLr-S3sV";
+
+    const CORP_CHANNEL_ID: u64 = 1;
+    const ADM_CHANNEL_ID: u64 = 2;
+    const CHANNELS: NotificationChannels = NotificationChannels {
+        corp: CORP_CHANNEL_ID,
+        adm: ADM_CHANNEL_ID,
+    };
+
+    fn config() -> BotConfig {
+        BotConfig {
+            channels: ChannelConfig::new(
+                std::env::temp_dir().join("alliance_squawk_bot_channels.json"),
+                CHANNELS,
+            ),
+            corp_min_members: 10,
+            adm_critical_ping_role_id: None,
+            dry_run: false,
+            theme: Theme::default(),
+            branding: Branding::default(),
+            corp_filter: CorpFilter::default(),
+            standings: Standings::default(),
+            message_rate: MessageRate::default(),
+            quiet_hours: None,
+            webhook: None,
+            sinks: vec![],
+            departure_alert: DepartureAlert::default(),
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn command_error_messages() {
+        let esi_error = anyhow::Error::from(EsiError::Server(502));
+        assert!(command_error_message(&esi_error).contains("ESI"));
+
+        let context_error = anyhow::Error::from(EsiError::Timeout).context("fetching alliance");
+        assert!(command_error_message(&context_error).contains("ESI"));
+
+        let other_error = anyhow::anyhow!("couldn't save the ADM configuration");
+        assert!(
+            command_error_message(&other_error) == "Something went wrong, please try again later."
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn member_filter_threshold() {
+        assert!(!passes_member_filter(9, 10));
+        assert!(passes_member_filter(10, 10));
+        assert!(passes_member_filter(250, 10));
+    }
+
+    #[traced_test]
+    #[test]
+    fn watched_alliance_bypasses_member_filter() {
+        assert!(!passes_member_filter(3, corp_min_members(10, false)));
+        assert!(passes_member_filter(3, corp_min_members(10, true)));
+        assert!(passes_member_filter(25, corp_min_members(10, false)));
+    }
+
+    fn corporation(war_eligible: Option<bool>, faction_id: Option<EsiID>) -> Corporation {
+        Corporation {
+            alliance_id: Some(99000001),
+            ceo_id: 2112000001,
+            creator_id: 2112000001,
+            date_founded: None,
+            description: None,
+            faction_id,
+            home_station_id: None,
+            member_count: 100,
+            name: "Corporation".to_string(),
+            shares: None,
+            tax_rate: 0.1,
+            ticker: "CORP".to_string(),
+            url: None,
+            war_eligible,
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn departure_alert_classification() {
+        let departure_alert = DepartureAlert {
+            alliance_id: 99000001,
+            min_members: Some(100),
+            ping_role_id: None,
+        };
+
+        assert!(departure_alert.is_major(99000001, 101));
+        assert!(!departure_alert.is_major(99000001, 100));
+        assert!(!departure_alert.is_major(99000001, 10));
+        assert!(!departure_alert.is_major(99000002, 500));
+        assert!(departure_alert.watches(99000001));
+        assert!(!departure_alert.watches(99000002));
+
+        let disabled = DepartureAlert {
+            min_members: None,
+            ..departure_alert
+        };
+
+        assert!(!disabled.watches(99000001));
+        assert!(!disabled.is_major(99000001, 500));
+    }
+
+    #[traced_test]
+    #[test]
+    fn corp_filter_skips_war_ineligible() {
+        let filter = CorpFilter {
+            skip_war_ineligible: true,
+            ..Default::default()
+        };
+
+        assert!(!passes_corp_filter(&corporation(Some(false), None), filter));
+        assert!(passes_corp_filter(&corporation(Some(true), None), filter));
+        assert!(passes_corp_filter(&corporation(None, None), filter));
+        assert!(passes_corp_filter(
+            &corporation(Some(false), None),
+            CorpFilter::default()
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn corp_filter_skips_faction_corporations() {
+        let filter = CorpFilter {
+            skip_faction: true,
+            ..Default::default()
+        };
+
+        assert!(!passes_corp_filter(
+            &corporation(Some(true), Some(500001)),
+            filter
+        ));
+        assert!(passes_corp_filter(&corporation(Some(false), None), filter));
+        assert!(passes_corp_filter(
+            &corporation(None, Some(500001)),
+            CorpFilter::default()
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn corp_embed_applies_filters() {
+        let mut config = config();
+        config.corp_filter.skip_faction = true;
+        let options = config.corp_embed_options(99000001, false, config.theme.corp_join);
+
+        let embed = corp_embed(
+            options,
+            "Joined Alliance",
+            98000001,
+            &corporation(None, None),
+        );
+        let fields = serde_json::to_value(embed.unwrap()).unwrap()["fields"].clone();
+
+        assert!(
+            fields[0]["value"]
+                == "Corporation ([CORP](https://evemaps.dotlan.net/corp/Corporation))"
+        );
+        assert!(fields[1]["value"] == "100");
+        assert!(corp_embed(
+            options,
+            "Joined Alliance",
+            98000001,
+            &corporation(None, Some(500001))
+        )
+        .is_none());
+        assert!(corp_embed(
+            CorpEmbedOptions {
+                min_members: 101,
+                ..options
+            },
+            "Joined Alliance",
+            98000001,
+            &corporation(None, None)
+        )
+        .is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn member_filter_disabled() {
+        assert!(passes_member_filter(0, 0));
+        assert!(passes_member_filter(1, 0));
+    }
+
+    #[traced_test]
+    #[test]
+    fn corp_notifications_route_to_corp_channel() {
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::NotifyCorpJoinAlliance(1, 2))
+                == CORP_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::NotifyCorpLeftAlliance(1, 2))
+                == CORP_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::NotifyCorpMovedAlliance(1, 2, 3))
+                == CORP_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::NotifyAllianceCreated(1))
+                == CORP_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::NotifyAllianceClosed(1))
+                == CORP_CHANNEL_ID
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_notifications_route_to_adm_channel() {
+        assert!(
+            notification_channel_id(
+                CHANNELS,
+                &BotNotification::NotifyAdm(SystemAdm::for_test(30000142, Status::Critical(0.5)))
+            ) == ADM_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(
+                CHANNELS,
+                &BotNotification::NotifyAdmRecovered(SystemAdm::for_test(
+                    30000142,
+                    Status::Good(4.0)
+                ))
+            ) == ADM_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::NotifySovLost(30000142))
+                == ADM_CHANNEL_ID
+        );
+        assert!(
+            notification_channel_id(CHANNELS, &BotNotification::AdmDigest(vec![]))
+                == ADM_CHANNEL_ID
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn dry_run_targets_are_not_sent() {
+        let notification = BotNotification::NotifySovLost(30000142);
+
+        assert!(
+            notification_target(&config(), CHANNELS, &notification)
+                == NotificationTarget::Channel(ADM_CHANNEL_ID)
+        );
+        assert!(
+            notification_target(
+                &BotConfig {
+                    dry_run: true,
+                    ..config()
+                },
+                CHANNELS,
+                &notification
+            ) == NotificationTarget::DryRun(ADM_CHANNEL_ID)
+        );
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn notifications_follow_channel_changes() {
+        let config = config();
+        let notification = BotNotification::NotifySovLost(30000142);
+
+        config.channels.set_adm_channel(20).await.unwrap();
+        std::fs::remove_file(std::env::temp_dir().join("alliance_squawk_bot_channels.json"))
+            .unwrap();
+
+        assert!(
+            notification_target(&config, config.channels.channels().await, &notification)
+                == NotificationTarget::Channel(20)
+        );
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_failure_propagates() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/api/v10/channels/{}/messages",
+                ADM_CHANNEL_ID
+            )))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "code": 50013,
+                "message": "Missing Permissions"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = HttpBuilder::new("token")
+            .proxy(server.uri())
+            .ratelimiter_disabled(true)
+            .build();
+
+        let message = NotificationMessage::new(CreateEmbed::new().title("test"));
+
+        let result = NotificationTarget::Channel(ADM_CHANNEL_ID)
+            .send(&http, message.clone())
+            .await;
+
+        assert!(result.is_err());
+        assert!(NotificationTarget::DryRun(ADM_CHANNEL_ID)
+            .send(&http, message)
+            .await
+            .is_ok());
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_webhook_url() {
+        assert!(WEBHOOK_URL.parse::<DiscordWebhook>().is_ok());
+        assert!("https://discord.com/api/channels/1"
+            .parse::<DiscordWebhook>()
+            .is_err());
+        assert!("not a url".parse::<DiscordWebhook>().is_err());
+    }
+
+    #[traced_test]
+    #[test]
+    fn webhook_overrides_channel_target() {
+        let webhook: DiscordWebhook = WEBHOOK_URL.parse().unwrap();
+        let notification = BotNotification::NotifySovLost(30000142);
+
+        let webhook_config = BotConfig {
+            webhook: Some(webhook.clone()),
+            ..config()
+        };
+
+        assert!(
+            notification_target(&webhook_config, CHANNELS, &notification)
+                == NotificationTarget::Webhook(webhook)
+        );
+        assert!(
+            notification_target(
+                &BotConfig {
+                    dry_run: true,
+                    ..webhook_config
+                },
+                CHANNELS,
+                &notification
+            ) == NotificationTarget::DryRun(ADM_CHANNEL_ID)
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn webhook_payload_matches_channel_message() {
+        let embed = CreateEmbed::new().title("Critical ADM");
+
+        let message = NotificationMessage::new(embed).with_ping_role_id(Some(42));
+
+        let webhook = serde_json::to_value(message.clone().webhook()).unwrap();
+        let channel = serde_json::to_value(message.message()).unwrap();
+
+        assert!(webhook["content"] == "<@&42>");
+        assert!(webhook["embeds"][0]["title"] == "Critical ADM");
+        assert!(webhook["allowed_mentions"]["roles"] == serde_json::json!(["42"]));
+        assert!(webhook["embeds"] == channel["embeds"]);
+        assert!(webhook["content"] == channel["content"]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn webhook_target_executes_webhook() {
+        let server = MockServer::start().await;
+        let webhook: DiscordWebhook = WEBHOOK_URL.parse().unwrap();
+
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/api/v10/webhooks/{}/{}",
+                webhook.id, webhook.token
+            )))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = HttpBuilder::new("")
+            .proxy(server.uri())
+            .ratelimiter_disabled(true)
+            .build();
+
+        let result = NotificationTarget::Webhook(webhook)
+            .send(
+                &http,
+                NotificationMessage::new(CreateEmbed::new().title("test")),
+            )
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_ping_only_for_critical() {
+        assert!(adm_ping_role_id(Status::Critical(0.5), Some(42)) == Some(42));
+        assert!(adm_ping_role_id(Status::Warning(1.5), Some(42)).is_none());
+        assert!(adm_ping_role_id(Status::Good(4.0), Some(42)).is_none());
+        assert!(adm_ping_role_id(Status::Critical(0.5), None).is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn corp_embed_options_use_standing_color() {
+        let path = std::env::temp_dir().join("alliance_squawk_bot_standings_test.json");
+        std::fs::write(&path, r#"{"1":"Friendly","2":"Hostile","3":"Neutral"}"#).unwrap();
+
+        let config = BotConfig {
+            standings: Standings::load_from(&path).await.unwrap(),
+            ..config()
+        };
+        std::fs::remove_file(&path).unwrap();
+
+        let theme = config.theme;
+        let options = |alliance_id| config.corp_embed_options(alliance_id, false, theme.corp_leave);
+
+        assert!(options(1).color == theme.friendly);
+        assert!(options(1).standing == Some(Standing::Friendly));
+        assert!(options(2).color == theme.hostile);
+        assert!(options(3).color == theme.corp_leave);
+        assert!(options(3).standing == Some(Standing::Neutral));
+        assert!(options(4).color == theme.corp_leave);
+        assert!(options(4).standing.is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn guild_ids_parsing() {
+        assert!(
+            parse_guild_ids("1, 2,3") == vec![GuildId::new(1), GuildId::new(2), GuildId::new(3)]
+        );
+        assert!(parse_guild_ids("1,guild,0,") == vec![GuildId::new(1)]);
+        assert!(parse_guild_ids("").is_empty());
+        assert!(parse_guild_ids(" , ").is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn corp_notification_ids_are_deduplicated() {
+        let (alliance_ids, corporation_ids) = corp_notification_ids(&[
+            BotNotification::NotifyCorpLeftAlliance(1, 10),
+            BotNotification::NotifyCorpLeftAlliance(1, 11),
+            BotNotification::NotifyCorpJoinAlliance(2, 12),
+            BotNotification::NotifyCorpMovedAlliance(10, 1, 3),
+            BotNotification::NotifySovLost(30000142),
+        ]);
+
+        assert!(alliance_ids == vec![1, 2, 3]);
+        assert!(corporation_ids == vec![10, 11, 12]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn batch_alliance_names_resolve_in_one_request() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/names/"))
+            .and(body_json(vec![1, 2, 3]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "category": "alliance", "id": 1, "name": "Alliance 1" },
+                { "category": "alliance", "id": 2, "name": "Alliance 2" },
+                { "category": "alliance", "id": 3, "name": "Alliance 3" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        for corporation_id in [10, 11] {
+            Mock::given(method("GET"))
+                .and(path(format!("/corporations/{}/", corporation_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "ceo_id": 2112000001_u64,
+                    "creator_id": 2112000001_u64,
+                    "member_count": 10,
+                    "name": format!("Corporation {}", corporation_id),
+                    "tax_rate": 0.1,
+                    "ticker": "CORP"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let information = InformationService::new(Arc::new(
+            Esi::new()
+                .with_base_url(Url::parse(&format!("{}/", server.uri())).unwrap())
+                .with_default_cache_ttl(Duration::ZERO),
+        ));
+
+        let alliance_names = batch_alliance_names(
+            &information,
+            &[
+                BotNotification::NotifyCorpLeftAlliance(1, 10),
+                BotNotification::NotifyCorpJoinAlliance(2, 11),
+                BotNotification::NotifyCorpMovedAlliance(10, 1, 3),
+            ],
+        )
+        .await;
+
+        assert!(alliance_names.len() == 3);
+        assert!(alliance_names[&3] == "Alliance 3");
+        assert!(server.received_requests().await.unwrap().len() == 3);
+        assert!(
+            batch_alliance_names(&information, &[BotNotification::NotifySovLost(30000142)])
+                .await
+                .is_empty()
+        );
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn receive_batch_collects_corp_bursts() {
+        const WINDOW: Duration = Duration::from_millis(10);
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        sender
+            .send(BotNotification::NotifySovLost(30000142))
+            .unwrap();
+        sender
+            .send(BotNotification::NotifyCorpLeftAlliance(1, 10))
+            .unwrap();
+        sender
+            .send(BotNotification::NotifyCorpLeftAlliance(1, 11))
+            .unwrap();
+
+        assert!(receive_batch(&mut receiver, WINDOW).await.unwrap().len() == 1);
+        assert!(receive_batch(&mut receiver, WINDOW).await.unwrap().len() == 2);
+
+        drop(sender);
+
+        assert!(receive_batch(&mut receiver, WINDOW).await.is_none());
+    }
 }
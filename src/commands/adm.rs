@@ -1,107 +1,634 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::Duration,
+};
+
 use anyhow::Context as _;
-use futures::future::try_join_all;
 use serenity::{
-    all::CommandInteraction,
+    all::{CommandInteraction, CommandOptionType},
     builder::{
-        CreateCommand, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
-        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage,
     },
     client::Context,
     model::Permissions,
 };
 
-use crate::services::{
-    adm_service::{AdmService, Status},
-    information_service::InformationService,
+use crate::{
+    commands::pagination::{chunk_values, field_pages, paginate, Field},
+    esi::EsiID,
+    services::{
+        adm_configuration::Importance,
+        adm_service::{AdmService, Status, SystemAdm},
+        information_service::InformationService,
+    },
+    theme::Branding,
 };
 
-pub const COMMAND_NAME: &'static str = "adm";
+pub const COMMAND_NAME: &str = "adm";
+
+const REGION_OPTION: &str = "region";
+const SEVERITY_OPTION: &str = "severity";
+const IMPORTANCE_OPTION: &str = "importance";
+const HEALTH_FIELD: &str = "Alliance ADM Health";
+// Room left on a summary for the count of systems which didn't fit.
+const MORE_FIELD_LENGTH: usize = 100;
+const UNKNOWN_REGION: &str = "Unknown Region";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Severity {
+    Critical,
+    All,
+}
+
+impl Severity {
+    fn from_option(value: Option<&str>) -> Severity {
+        match value {
+            Some(value) if value.eq_ignore_ascii_case("critical") => Severity::Critical,
+            _ => Severity::All,
+        }
+    }
+}
+
+fn systems_by_severity(
+    system_adms: &[SystemAdm],
+    severity: Severity,
+) -> (Vec<&SystemAdm>, Option<Vec<&SystemAdm>>) {
+    let critical_systems = system_adms
+        .iter()
+        .filter(|system_adm| matches!(system_adm.status, Status::Critical(_)))
+        .collect();
+
+    let warning_systems = match severity {
+        Severity::Critical => None,
+        Severity::All => Some(
+            system_adms
+                .iter()
+                .filter(|system_adm| matches!(system_adm.status, Status::Warning(_)))
+                .collect(),
+        ),
+    };
+
+    (critical_systems, warning_systems)
+}
+
+fn filter_by_importance(
+    system_adms: Vec<SystemAdm>,
+    importance: Option<Importance>,
+) -> Vec<SystemAdm> {
+    match importance {
+        Some(importance) => system_adms
+            .into_iter()
+            .filter(|system_adm| system_adm.importance.importance() == Some(importance))
+            .collect(),
+        None => system_adms,
+    }
+}
+
+fn pinned_systems<'a>(
+    system_adms: &'a [SystemAdm],
+    system_names: &HashMap<EsiID, String>,
+    pinned: &BTreeSet<String>,
+) -> Vec<&'a SystemAdm> {
+    system_adms
+        .iter()
+        .filter(|system_adm| {
+            system_names
+                .get(&system_adm.system_id)
+                .is_some_and(|system_name| pinned.contains(system_name))
+        })
+        .collect()
+}
+
+fn monitored_list(system_names: &HashMap<EsiID, String>, systems: &[&SystemAdm]) -> Vec<String> {
+    systems
+        .iter()
+        .filter_map(|system_adm| {
+            system_names.get(&system_adm.system_id).map(|system_name| {
+                format!(
+                    "{} {:.1} {}",
+                    system_name,
+                    system_adm.status.adm(),
+                    system_adm.trend()
+                )
+            })
+        })
+        .collect()
+}
+
+async fn filter_by_region<'a>(
+    information: &InformationService,
+    system_adms: Vec<&'a SystemAdm>,
+    region: &str,
+) -> Vec<&'a SystemAdm> {
+    let mut filtered = vec![];
+
+    for system_adm in system_adms {
+        match information.get_system_region(system_adm.system_id).await {
+            Ok(system_region) if system_region.name.eq_ignore_ascii_case(region) => {
+                filtered.push(system_adm)
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!(
+                system_id = system_adm.system_id,
+                ?err,
+                "couldn't resolve system region"
+            ),
+        }
+    }
+
+    filtered
+}
+
+async fn system_regions(
+    information: &InformationService,
+    system_ids: &[EsiID],
+) -> HashMap<EsiID, String> {
+    let mut system_regions = HashMap::new();
+
+    for system_id in system_ids {
+        match information.get_system_region(*system_id).await {
+            Ok(region) => {
+                system_regions.insert(*system_id, region.name);
+            }
+            Err(err) => tracing::warn!(system_id, ?err, "couldn't resolve system region"),
+        }
+    }
+
+    system_regions
+}
+
+// Regions are sorted by name, systems whose region couldn't be resolved are listed last.
+fn group_by_region<'a>(
+    system_regions: &HashMap<EsiID, String>,
+    systems: &[&'a SystemAdm],
+) -> Vec<(String, Vec<&'a SystemAdm>)> {
+    let mut regions: Vec<(String, Vec<&SystemAdm>)> = vec![];
+
+    for system_adm in systems {
+        let region = system_regions
+            .get(&system_adm.system_id)
+            .map(String::as_str)
+            .unwrap_or(UNKNOWN_REGION);
+
+        match regions.iter_mut().find(|(name, _)| name == region) {
+            Some((_, region_systems)) => region_systems.push(system_adm),
+            None => regions.push((region.to_string(), vec![system_adm])),
+        }
+    }
+
+    regions.sort_by_key(|(name, _)| (name == UNKNOWN_REGION, name.clone()));
+
+    regions
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AdmHealth {
+    good: usize,
+    warning: usize,
+    critical: usize,
+    mean_adm: Option<f32>,
+}
+
+impl AdmHealth {
+    pub fn from_systems(system_adms: &[SystemAdm]) -> AdmHealth {
+        let mut health = AdmHealth::default();
+
+        for system_adm in system_adms {
+            match system_adm.status {
+                Status::Good(_) => health.good += 1,
+                Status::Warning(_) => health.warning += 1,
+                Status::Critical(_) => health.critical += 1,
+            }
+        }
+
+        if !system_adms.is_empty() {
+            let total: f32 = system_adms
+                .iter()
+                .map(|system_adm| system_adm.status.adm())
+                .sum();
+
+            health.mean_adm = Some(total / system_adms.len() as f32);
+        }
+
+        health
+    }
+}
+
+impl std::fmt::Display for AdmHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(mean_adm) = self.mean_adm else {
+            return write!(f, "No systems held");
+        };
+
+        write!(
+            f,
+            "Mean ADM {:.1} across {} systems\n{} good, {} warning, {} critical",
+            mean_adm,
+            self.good + self.warning + self.critical,
+            self.good,
+            self.warning,
+            self.critical
+        )
+    }
+}
+
+pub fn stale_notice(stale_for: Duration) -> String {
+    format!(
+        "⚠️ Stale data, ESI unavailable. Showing sovereignty from {} minutes ago.",
+        stale_for.as_secs() / 60
+    )
+}
+
+fn system_list(system_names: &HashMap<EsiID, String>, systems: &[&SystemAdm]) -> Vec<String> {
+    systems
+        .iter()
+        .filter_map(|system_adm| {
+            system_names.get(&system_adm.system_id).map(|system_name| {
+                format!(
+                    "{} ({}) {}",
+                    system_name,
+                    system_adm.structure_label(),
+                    system_adm.trend()
+                )
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SectionField {
+    name: String,
+    value: String,
+    systems: usize,
+}
+
+fn system_fields(name: &str, systems: &[String], empty: &str) -> Vec<SectionField> {
+    let chunks = chunk_values(systems, ", ");
+    let count = chunks.len();
+
+    if count == 0 {
+        return vec![SectionField {
+            name: name.to_string(),
+            value: empty.to_string(),
+            systems: 0,
+        }];
+    }
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| SectionField {
+            name: if count == 1 {
+                name.to_string()
+            } else {
+                format!("{} ({}/{})", name, index + 1, count)
+            },
+            systems: chunk.split(", ").count(),
+            value: chunk,
+        })
+        .collect()
+}
+
+fn region_fields(
+    name: &str,
+    system_names: &HashMap<EsiID, String>,
+    system_regions: &HashMap<EsiID, String>,
+    systems: &[&SystemAdm],
+    empty: &str,
+) -> Vec<SectionField> {
+    let regions = group_by_region(system_regions, systems);
+
+    if regions.is_empty() {
+        return system_fields(name, &[], empty);
+    }
+
+    regions
+        .into_iter()
+        .flat_map(|(region, systems)| {
+            system_fields(
+                &format!("{} - {}", name, region),
+                &system_list(system_names, &systems),
+                empty,
+            )
+        })
+        .collect()
+}
+
+// The health summary, title, description and footer are repeated on every page, the
+// section fields are split across as many pages as needed.
+#[derive(Debug, Clone)]
+pub struct AdmReport {
+    title: String,
+    description: Option<String>,
+    health: AdmHealth,
+    sections: Vec<SectionField>,
+    footer: Option<&'static str>,
+}
+
+impl AdmReport {
+    pub async fn new(
+        information: &InformationService,
+        title: String,
+        health: AdmHealth,
+        critical_systems: &[&SystemAdm],
+        warning_systems: Option<&[&SystemAdm]>,
+    ) -> anyhow::Result<AdmReport> {
+        let system_ids: Vec<_> = critical_systems
+            .iter()
+            .chain(warning_systems.unwrap_or_default())
+            .map(|system_adm| system_adm.system_id)
+            .collect();
+
+        let system_names = information
+            .get_system_names(&system_ids)
+            .await
+            .context("get system names")?;
+
+        let system_regions = system_regions(information, &system_ids).await;
+
+        Ok(AdmReport::from_parts(
+            title,
+            health,
+            &system_names,
+            &system_regions,
+            critical_systems,
+            warning_systems,
+        ))
+    }
+
+    fn from_parts(
+        title: String,
+        health: AdmHealth,
+        system_names: &HashMap<EsiID, String>,
+        system_regions: &HashMap<EsiID, String>,
+        critical_systems: &[&SystemAdm],
+        warning_systems: Option<&[&SystemAdm]>,
+    ) -> AdmReport {
+        let mut sections = region_fields(
+            "Critical Systems",
+            system_names,
+            system_regions,
+            critical_systems,
+            "None 🏆",
+        );
+
+        let footer = warning_systems.map(|warning_systems| {
+            sections.extend(region_fields(
+                "Warning Systems",
+                system_names,
+                system_regions,
+                warning_systems,
+                "None 🎉",
+            ));
+
+            "🦀 Please focus on the <Critical> systems first and then move on to the <Warning> systems."
+        });
+
+        AdmReport {
+            title,
+            description: None,
+            health,
+            sections,
+            footer,
+        }
+    }
+
+    fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    // Pinned systems are listed with their current ADM whatever their status.
+    async fn with_monitored(
+        mut self,
+        information: &InformationService,
+        system_adms: &[SystemAdm],
+        pinned: &BTreeSet<String>,
+    ) -> anyhow::Result<Self> {
+        if pinned.is_empty() {
+            return Ok(self);
+        }
+
+        let system_ids: Vec<_> = system_adms
+            .iter()
+            .map(|system_adm| system_adm.system_id)
+            .collect();
+
+        let system_names = information
+            .get_system_names(&system_ids)
+            .await
+            .context("get system names")?;
+
+        let monitored = pinned_systems(system_adms, &system_names, pinned);
+
+        self.sections.extend(system_fields(
+            "Monitored",
+            &monitored_list(&system_names, &monitored),
+            "None held",
+        ));
+
+        Ok(self)
+    }
+
+    fn reserved_length(&self, branding: &Branding) -> usize {
+        [
+            branding.title(&self.title),
+            branding.name.clone().unwrap_or_default(),
+            self.description.clone().unwrap_or_default(),
+            HEALTH_FIELD.to_string(),
+            self.health.to_string(),
+            self.footer.unwrap_or_default().to_string(),
+        ]
+        .iter()
+        .map(|text| text.chars().count())
+        .sum()
+    }
+
+    fn section_pages(&self, reserved_length: usize, reserved_fields: usize) -> Vec<Vec<Field>> {
+        field_pages(
+            self.sections
+                .iter()
+                .map(|section| (section.name.clone(), section.value.clone()))
+                .collect(),
+            reserved_length,
+            reserved_fields,
+        )
+    }
+
+    fn page(&self, branding: &Branding, fields: Vec<Field>) -> CreateEmbed {
+        let embed = branding
+            .embed(&self.title)
+            .field(HEALTH_FIELD, self.health.to_string(), false);
+
+        let embed = match &self.description {
+            Some(description) => embed.description(description),
+            None => embed,
+        };
+
+        let embed = fields.into_iter().fold(embed, |embed, (name, value)| {
+            embed.field(name, value, false)
+        });
+
+        match self.footer {
+            Some(footer) => embed.footer(CreateEmbedFooter::new(footer)),
+            None => embed,
+        }
+    }
+
+    pub fn pages(&self, branding: &Branding) -> Vec<CreateEmbed> {
+        let pages = self.section_pages(self.reserved_length(branding), 1);
+
+        if pages.is_empty() {
+            return vec![self.page(branding, vec![])];
+        }
+
+        pages
+            .into_iter()
+            .map(|fields| self.page(branding, fields))
+            .collect()
+    }
+
+    // Notifications can't be paged, so only the first page is sent followed by a count of
+    // the systems left out.
+    pub fn summary(&self, branding: &Branding) -> CreateEmbed {
+        let mut pages = self
+            .section_pages(self.reserved_length(branding) + MORE_FIELD_LENGTH, 2)
+            .into_iter();
+
+        let fields = pages.next().unwrap_or_default();
+        let shown = fields.len();
+        let embed = self.page(branding, fields);
+
+        let omitted: usize = self
+            .sections
+            .iter()
+            .skip(shown)
+            .map(|section| section.systems)
+            .sum();
+
+        if omitted == 0 {
+            return embed;
+        }
+
+        embed.field(
+            "More",
+            format!(
+                "+{} more systems, use `/{}` for the full report.",
+                omitted, COMMAND_NAME
+            ),
+            false,
+        )
+    }
+}
 
 pub async fn run(
     ctx: &Context,
     interaction: &CommandInteraction,
     information: &InformationService,
     adm_service: &AdmService,
+    branding: &Branding,
 ) -> anyhow::Result<()> {
     interaction
         .create_response(
             &ctx.http,
             CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
         )
-        .await
-        .expect("create response");
-
-    let system_adms = adm_service.get_adm_status().await;
+        .await?;
 
-    if let Err(error) = system_adms {
-        tracing::error!("{}", error);
+    let region = interaction
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == REGION_OPTION)
+        .and_then(|option| option.value.as_str());
 
+    let severity = Severity::from_option(
         interaction
-            .create_followup(
-                &ctx.http,
-                CreateInteractionResponseFollowup::new()
-                    .content("Error fetching system ADM from ESI. Please try again later.")
-                    .ephemeral(true),
-            )
-            .await?;
-
-        return Ok(());
-    }
+            .data
+            .options
+            .iter()
+            .find(|option| option.name == SEVERITY_OPTION)
+            .and_then(|option| option.value.as_str()),
+    );
 
-    let critical_systems: Vec<_> = system_adms
+    let importance = interaction
+        .data
+        .options
         .iter()
-        .flatten()
-        .filter_map(|system_adm| match system_adm.status {
-            Status::Critical(_) => Some(system_adm),
-            _ => None,
-        })
-        .collect();
+        .find(|option| option.name == IMPORTANCE_OPTION)
+        .and_then(|option| option.value.as_str())
+        .and_then(|importance| importance.parse::<Importance>().ok());
 
-    let critical_system_names = try_join_all(
-        critical_systems
-            .iter()
-            .map(|system| information.get_system(system.system_id)),
-    )
-    .await
-    .context("get system names")?
-    .iter()
-    .map(|system| system.name.to_owned())
-    .reduce(|acc, system_name| format!("{}, {}", acc, system_name));
+    let snapshot = match adm_service.get_adm_snapshot().await {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            tracing::error!("{}", error);
 
-    let warning_systems: Vec<_> = system_adms
-        .iter()
-        .flatten()
-        .filter_map(|system_adm| match system_adm.status {
-            Status::Warning(_) => Some(system_adm),
-            _ => None,
-        })
+            interaction
+                .create_followup(
+                    &ctx.http,
+                    CreateInteractionResponseFollowup::new()
+                        .content("Error fetching system ADM from ESI. Please try again later.")
+                        .ephemeral(true),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    // Health and pinned systems cover every held system regardless of the filters.
+    let health = AdmHealth::from_systems(&snapshot.systems);
+    let pinned = adm_service.pinned_systems().await;
+    let all_systems = snapshot.systems.clone();
+
+    let system_adms = filter_by_importance(snapshot.systems, importance);
+
+    let (critical_systems, warning_systems) = systems_by_severity(&system_adms, severity);
+
+    let (critical_systems, warning_systems) = match region {
+        Some(region) => (
+            filter_by_region(information, critical_systems, region).await,
+            match warning_systems {
+                Some(warning_systems) => {
+                    Some(filter_by_region(information, warning_systems, region).await)
+                }
+                None => None,
+            },
+        ),
+        None => (critical_systems, warning_systems),
+    };
+
+    let filters: Vec<&str> = region
+        .into_iter()
+        .chain(importance.map(|importance| importance.name()))
         .collect();
 
-    let warning_system_names = try_join_all(
-        warning_systems
-            .iter()
-            .map(|system| information.get_system(system.system_id)),
+    let title = if filters.is_empty() {
+        "ADM Status Report".to_string()
+    } else {
+        format!("ADM Status Report ({})", filters.join(", "))
+    };
+
+    let report = AdmReport::new(
+        information,
+        title,
+        health,
+        &critical_systems,
+        warning_systems.as_deref(),
     )
-    .await
-    .context("get system names")?
-    .iter()
-    .map(|system| system.name.to_owned())
-    .reduce(|acc, system_name| format!("{}, {}", acc, system_name));
-
-    let embed = CreateEmbed::new()
-        .title("ADM Status Report")
-        .field("Critical Systems", critical_system_names.unwrap_or("None 🏆".to_string()), false)
-        .field("Warning Systems", warning_system_names.unwrap_or("None 🎉".to_string()), false)
-        .footer(CreateEmbedFooter::new("🦀 Please focus on the <Critical> systems first and then move on to the <Warning> systems."));
+    .await?
+    .with_monitored(information, &all_systems, &pinned)
+    .await?;
 
-    interaction
-        .create_followup(
-            &ctx.http,
-            CreateInteractionResponseFollowup::new().embed(embed),
-        )
-        .await?;
+    let report = match snapshot.stale_for {
+        Some(stale_for) => report.with_description(stale_notice(stale_for)),
+        None => report,
+    };
 
-    Ok(())
+    paginate(ctx, interaction, report.pages(branding)).await
 }
 
 pub fn register() -> CreateCommand {
@@ -109,4 +636,345 @@ pub fn register() -> CreateCommand {
         .description("Show which systems require attention due to low ADM.")
         .default_member_permissions(Permissions::SEND_MESSAGES)
         .dm_permission(true)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                REGION_OPTION,
+                "Only show systems in this region.",
+            )
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                SEVERITY_OPTION,
+                "Which systems to show (default all).",
+            )
+            .add_string_choice("Critical only", "critical")
+            .add_string_choice("Critical and warning", "all")
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                IMPORTANCE_OPTION,
+                "Only show systems of this importance.",
+            )
+            .add_string_choice("Red", "red")
+            .add_string_choice("Yellow", "yellow")
+            .add_string_choice("Green", "green")
+            .required(false),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeSet, HashMap},
+        time::Duration,
+    };
+
+    use tracing_test::traced_test;
+
+    use crate::services::{
+        adm_configuration::Importance,
+        adm_service::{Status, SystemAdm, SystemImportance},
+    };
+
+    use crate::{commands::pagination::embed_length, theme::Branding};
+
+    use super::{
+        filter_by_importance, group_by_region, monitored_list, pinned_systems, stale_notice,
+        systems_by_severity, AdmHealth, AdmReport, Severity, UNKNOWN_REGION,
+    };
+
+    #[traced_test]
+    #[test]
+    fn systems_by_severity_filters_sections() {
+        let system_adms = vec![
+            SystemAdm::for_test(1, Status::Critical(0.8)),
+            SystemAdm::for_test(2, Status::Warning(1.1)),
+            SystemAdm::for_test(3, Status::Good(4.0)),
+            SystemAdm::for_test(4, Status::Critical(0.9)),
+        ];
+
+        let ids = |systems: &[&SystemAdm]| -> Vec<u64> {
+            systems
+                .iter()
+                .map(|system_adm| system_adm.system_id)
+                .collect()
+        };
+
+        let (critical, warning) = systems_by_severity(&system_adms, Severity::All);
+        assert!(ids(&critical) == vec![1, 4]);
+        assert!(warning.as_deref().map(ids) == Some(vec![2]));
+
+        let (critical, warning) = systems_by_severity(&system_adms, Severity::Critical);
+        assert!(ids(&critical) == vec![1, 4]);
+        assert!(warning.is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn severity_from_option() {
+        assert!(Severity::from_option(Some("critical")) == Severity::Critical);
+        assert!(Severity::from_option(Some("all")) == Severity::All);
+        assert!(Severity::from_option(None) == Severity::All);
+    }
+
+    #[traced_test]
+    #[test]
+    fn stale_notice_shows_age_in_minutes() {
+        assert!(
+            stale_notice(Duration::from_secs(125 * 60))
+                == "⚠️ Stale data, ESI unavailable. Showing sovereignty from 125 minutes ago."
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn group_by_region_sorts_regions() {
+        let system_adms = [
+            SystemAdm::for_test(1, Status::Critical(0.8)),
+            SystemAdm::for_test(2, Status::Critical(0.9)),
+            SystemAdm::for_test(3, Status::Critical(0.7)),
+            SystemAdm::for_test(4, Status::Critical(0.6)),
+        ];
+        let systems: Vec<_> = system_adms.iter().collect();
+
+        let system_regions = HashMap::from([
+            (1, "Delve".to_string()),
+            (2, "Catch".to_string()),
+            (3, "Delve".to_string()),
+        ]);
+
+        let regions: Vec<(String, Vec<u64>)> = group_by_region(&system_regions, &systems)
+            .into_iter()
+            .map(|(region, systems)| {
+                (
+                    region,
+                    systems
+                        .iter()
+                        .map(|system_adm| system_adm.system_id)
+                        .collect(),
+                )
+            })
+            .collect();
+
+        assert!(
+            regions
+                == vec![
+                    ("Catch".to_string(), vec![2]),
+                    ("Delve".to_string(), vec![1, 3]),
+                    (UNKNOWN_REGION.to_string(), vec![4]),
+                ]
+        );
+        assert!(group_by_region(&system_regions, &[]).is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn filter_by_importance_keeps_matching_systems() {
+        let with_importance = |system_id, importance| SystemAdm {
+            importance,
+            ..SystemAdm::for_test(system_id, Status::Critical(0.8))
+        };
+
+        let system_adms = vec![
+            with_importance(1, SystemImportance::Configured(Importance::Red)),
+            with_importance(2, SystemImportance::Configured(Importance::Yellow)),
+            with_importance(3, SystemImportance::Default(Importance::Red)),
+            with_importance(4, SystemImportance::Custom(2.5, 2.0)),
+        ];
+
+        let ids = |system_adms: Vec<SystemAdm>| -> Vec<u64> {
+            system_adms
+                .iter()
+                .map(|system_adm| system_adm.system_id)
+                .collect()
+        };
+
+        assert!(
+            ids(filter_by_importance(
+                system_adms.clone(),
+                Some(Importance::Red)
+            )) == vec![1, 3]
+        );
+        assert!(
+            ids(filter_by_importance(
+                system_adms.clone(),
+                Some(Importance::Yellow)
+            )) == vec![2]
+        );
+        assert!(filter_by_importance(system_adms.clone(), Some(Importance::Green)).is_empty());
+        assert!(ids(filter_by_importance(system_adms, None)) == vec![1, 2, 3, 4]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_health_counts_and_averages() {
+        let system_adms = vec![
+            SystemAdm::for_test(1, Status::Critical(0.8)),
+            SystemAdm::for_test(2, Status::Warning(1.2)),
+            SystemAdm::for_test(3, Status::Good(4.0)),
+            SystemAdm::for_test(4, Status::Good(6.0)),
+        ];
+
+        let health = AdmHealth::from_systems(&system_adms);
+
+        assert!(
+            health
+                == AdmHealth {
+                    good: 2,
+                    warning: 1,
+                    critical: 1,
+                    mean_adm: Some(3.0),
+                }
+        );
+        assert!(
+            health.to_string() == "Mean ADM 3.0 across 4 systems\n2 good, 1 warning, 1 critical"
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_health_without_systems() {
+        let health = AdmHealth::from_systems(&[]);
+
+        assert!(health.mean_adm.is_none());
+        assert!(health.to_string() == "No systems held");
+    }
+
+    #[traced_test]
+    #[test]
+    fn pinned_systems_include_good_systems() {
+        let system_adms = vec![
+            SystemAdm::for_test(1, Status::Good(4.5)),
+            SystemAdm::for_test(2, Status::Good(5.0)),
+            SystemAdm::for_test(3, Status::Critical(0.8)),
+        ];
+
+        let system_names = HashMap::from([
+            (1, "1DQ1-A".to_string()),
+            (2, "T5ZI-S".to_string()),
+            (3, "GE-8JV".to_string()),
+        ]);
+
+        let pinned = BTreeSet::from(["1DQ1-A".to_string(), "GE-8JV".to_string()]);
+
+        let monitored = pinned_systems(&system_adms, &system_names, &pinned);
+
+        assert!(
+            monitored
+                .iter()
+                .map(|system_adm| system_adm.system_id)
+                .collect::<Vec<_>>()
+                == vec![1, 3]
+        );
+        assert!(monitored_list(&system_names, &monitored) == vec!["1DQ1-A 4.5 ▬", "GE-8JV 0.8 ▬"]);
+        assert!(pinned_systems(&system_adms, &system_names, &BTreeSet::new()).is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn large_report_is_split_into_pages() {
+        let system_adms: Vec<_> = (0..600)
+            .map(|system_id| {
+                if system_id % 2 == 0 {
+                    SystemAdm::for_test(system_id, Status::Critical(0.8))
+                } else {
+                    SystemAdm::for_test(system_id, Status::Warning(1.2))
+                }
+            })
+            .collect();
+
+        let system_names: HashMap<_, _> = system_adms
+            .iter()
+            .map(|system_adm| {
+                (
+                    system_adm.system_id,
+                    format!("System-{:03}", system_adm.system_id),
+                )
+            })
+            .collect();
+        let system_regions: HashMap<_, _> = system_adms
+            .iter()
+            .map(|system_adm| {
+                (
+                    system_adm.system_id,
+                    format!("Region {}", system_adm.system_id % 30),
+                )
+            })
+            .collect();
+
+        let (critical, warning) = systems_by_severity(&system_adms, Severity::All);
+        let report = AdmReport::from_parts(
+            "ADM Status Report".to_string(),
+            AdmHealth::from_systems(&system_adms),
+            &system_names,
+            &system_regions,
+            &critical,
+            warning.as_deref(),
+        )
+        .with_description(stale_notice(Duration::from_secs(600)));
+
+        let branding = Branding {
+            ticker: Some("TEST".to_string()),
+            name: Some("Test Alliance Please Ignore".to_string()),
+        };
+        let field_count = |embed: &serenity::builder::CreateEmbed| {
+            serde_json::to_value(embed).unwrap()["fields"]
+                .as_array()
+                .unwrap()
+                .len()
+        };
+
+        let pages = report.pages(&branding);
+        let listed: usize = report.sections.iter().map(|section| section.systems).sum();
+
+        assert!(report.sections.len() > 25);
+        assert!(pages.len() > 1);
+        assert!(pages.iter().map(embed_length).sum::<usize>() > 6000);
+        assert!(pages.iter().all(|page| embed_length(page) <= 6000));
+        assert!(pages.iter().all(|page| field_count(page) <= 25));
+        assert!(listed == 600);
+
+        let summary = report.summary(&branding);
+        let summary_json = serde_json::to_value(&summary).unwrap();
+        let more = summary_json["fields"].as_array().unwrap().last().unwrap();
+
+        assert!(embed_length(&summary) <= 6000);
+        assert!(field_count(&summary) <= 25);
+        assert!(more["name"] == "More");
+        assert!(more["value"]
+            .as_str()
+            .unwrap()
+            .ends_with("more systems, use `/adm` for the full report."));
+    }
+
+    #[traced_test]
+    #[test]
+    fn small_report_fits_one_page() {
+        let system_adms = vec![SystemAdm::for_test(1, Status::Critical(0.8))];
+        let system_names = HashMap::from([(1, "1DQ1-A".to_string())]);
+        let (critical, warning) = systems_by_severity(&system_adms, Severity::All);
+
+        let report = AdmReport::from_parts(
+            "ADM Status Report".to_string(),
+            AdmHealth::from_systems(&system_adms),
+            &system_names,
+            &HashMap::new(),
+            &critical,
+            warning.as_deref(),
+        );
+
+        let summary = serde_json::to_value(report.summary(&Branding::default())).unwrap();
+
+        assert!(report.pages(&Branding::default()).len() == 1);
+        assert!(summary["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|field| field["name"] != "More"));
+    }
 }
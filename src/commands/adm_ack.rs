@@ -0,0 +1,95 @@
+use serenity::{
+    all::{ButtonStyle, ComponentInteraction, Mentionable},
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+};
+
+use crate::{esi::EsiID, health::unix_now, services::adm_acks::AdmAcks};
+
+const ACK_BUTTON_PREFIX: &str = "adm_ack_";
+
+pub fn ack_button(system_id: EsiID) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(format!(
+        "{}{}",
+        ACK_BUTTON_PREFIX, system_id
+    ))
+    .label("Acknowledge")
+    .style(ButtonStyle::Primary)])
+}
+
+fn parse_ack_button(custom_id: &str) -> Option<EsiID> {
+    custom_id.strip_prefix(ACK_BUTTON_PREFIX)?.parse().ok()
+}
+
+pub fn is_ack_button(custom_id: &str) -> bool {
+    custom_id.starts_with(ACK_BUTTON_PREFIX)
+}
+
+// Records the acknowledgement and replaces the button with a field naming who acked the
+// alert.
+pub async fn run(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    acks: &AdmAcks,
+) -> anyhow::Result<()> {
+    let Some(system_id) = parse_ack_button(&interaction.data.custom_id) else {
+        anyhow::bail!("invalid ack button `{}`", interaction.data.custom_id);
+    };
+
+    let now = unix_now();
+
+    acks.acknowledge(system_id, &interaction.user.name, now)
+        .await;
+
+    tracing::info!(
+        system_id,
+        user = interaction.user.name,
+        "adm alert acknowledged"
+    );
+
+    let embed = interaction
+        .message
+        .embeds
+        .first()
+        .cloned()
+        .map(CreateEmbed::from)
+        .unwrap_or_default()
+        .field(
+            "Acknowledged",
+            format!("{} <t:{}:R>", interaction.user.mention(), now),
+            false,
+        );
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{is_ack_button, parse_ack_button};
+
+    #[traced_test]
+    #[test]
+    fn ack_buttons_are_recognized() {
+        assert!(parse_ack_button("adm_ack_30000142") == Some(30000142));
+        assert!(parse_ack_button("adm_ack_jita").is_none());
+        assert!(parse_ack_button("page_next").is_none());
+        assert!(is_ack_button("adm_ack_30000142"));
+        assert!(!is_ack_button("page_next"));
+    }
+}
@@ -10,14 +10,18 @@ use serenity::{
     utils::CreateQuickModal,
 };
 
-use crate::services::adm_configuration::{AdmConfiguration, Importance};
+use crate::services::{
+    adm_configuration::{AdmConfiguration, Importance},
+    information_service::InformationService,
+};
 
-pub const COMMAND_NAME: &'static str = "adm_configure";
+pub const COMMAND_NAME: &str = "adm_configure";
 
 pub async fn run(
     ctx: &Context,
     interaction: &CommandInteraction,
     adm_configuration: &AdmConfiguration,
+    information: &InformationService,
 ) -> anyhow::Result<()> {
     let modal = CreateQuickModal::new("Configure ADM")
         .timeout(Duration::from_secs(600))
@@ -27,7 +31,7 @@ pub async fn run(
     let response = interaction.quick_modal(ctx, modal).await?;
 
     if let Some(response) = response {
-        let system = response.inputs[0].to_uppercase();
+        let system_name = response.inputs[0].trim();
         let importance = match response.inputs[1].to_uppercase().as_str() {
             "RED" => Some(Importance::Red),
             "YELLOW" => Some(Importance::Yellow),
@@ -35,38 +39,70 @@ pub async fn run(
             _ => None,
         };
 
-        if let Some(importance) = importance {
-            adm_configuration
-                .set_importance(&system, importance)
-                .await?;
+        let system = match information.search_system(system_name).await {
+            Ok(system) => system,
+            Err(err) => {
+                tracing::error!(?err, system_name, "couldn't search system");
+                None
+            }
+        };
+
+        let error = match (system, importance) {
+            (Some(system), Some(importance)) => match adm_configuration
+                .set_importance(&system.name, importance)
+                .await
+            {
+                Ok(()) => {
+                    response
+                        .interaction
+                        .create_response(
+                            &ctx.http,
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .embed(
+                                        CreateEmbed::new()
+                                            .title("System Importance Updated")
+                                            .field("System", system.name, true)
+                                            .field("Importance", format!("{}", importance), true),
+                                    )
+                                    .ephemeral(true),
+                            ),
+                        )
+                        .await?;
 
+                    None
+                }
+                Err(err) => {
+                    tracing::error!(
+                        ?err,
+                        system = system.name,
+                        "couldn't save adm configuration"
+                    );
+
+                    Some(format!("Couldn't save the ADM configuration: {}", err))
+                }
+            },
+            (None, _) => Some(format!(
+                "Couldn't find a system named `{}`, please check the spelling.",
+                system_name
+            )),
+            (_, None) => Some(
+                "Unrecognized importance level, please use `Red`, `Yellow`, or `Green`".to_string(),
+            ),
+        };
+
+        if let Some(error) = error {
             response
                 .interaction
                 .create_response(
                     &ctx.http,
                     CreateInteractionResponse::Message(
                         CreateInteractionResponseMessage::new()
-                            .embed(
-                                CreateEmbed::new()
-                                    .title("System Importance Updated")
-                                    .field("System", system, true)
-                                    .field("Importance", format!("{}", importance), true),
-                            )
+                            .content(error)
                             .ephemeral(true),
                     ),
                 )
                 .await?;
-        } else {
-            response.interaction.create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content(
-                            "Unrecognized importance level, please use `Red`, `Yellow`, or `Green`",
-                        )
-                        .ephemeral(true),
-                ),
-            ).await?;
         }
     } else {
         tracing::warn!("modal response is `None`");
@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use serenity::{
+    all::CommandInteraction,
+    builder::{
+        CreateCommand, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+    utils::CreateQuickModal,
+};
+
+use crate::services::{
+    adm_configuration::AdmConfiguration, information_service::InformationService,
+};
+
+pub const COMMAND_NAME: &str = "adm_configure_thresholds";
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_configuration: &AdmConfiguration,
+    information: &InformationService,
+) -> anyhow::Result<()> {
+    let modal = CreateQuickModal::new("Configure ADM Thresholds")
+        .timeout(Duration::from_secs(600))
+        .short_field("System")
+        .short_field("Warning Threshold")
+        .short_field("Critical Threshold");
+
+    let response = interaction.quick_modal(ctx, modal).await?;
+
+    if let Some(response) = response {
+        let system_name = response.inputs[0].trim();
+        let thresholds = parse_thresholds(&response.inputs[1], &response.inputs[2]);
+
+        let system = match information.search_system(system_name).await {
+            Ok(system) => system,
+            Err(err) => {
+                tracing::error!(?err, system_name, "couldn't search system");
+                None
+            }
+        };
+
+        let error = match (system, thresholds) {
+            (Some(system), Ok((warning_threshold, critical_threshold))) => match adm_configuration
+                .set_custom_thresholds(&system.name, warning_threshold, critical_threshold)
+                .await
+            {
+                Ok(()) => {
+                    response
+                        .interaction
+                        .create_response(
+                            &ctx.http,
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .embed(
+                                        CreateEmbed::new()
+                                            .title("System Thresholds Updated")
+                                            .field("System", system.name, false)
+                                            .field(
+                                                "Warning",
+                                                format!("{}", warning_threshold),
+                                                true,
+                                            )
+                                            .field(
+                                                "Critical",
+                                                format!("{}", critical_threshold),
+                                                true,
+                                            ),
+                                    )
+                                    .ephemeral(true),
+                            ),
+                        )
+                        .await?;
+
+                    None
+                }
+                Err(err) => {
+                    tracing::error!(
+                        ?err,
+                        system = system.name,
+                        "couldn't save adm configuration"
+                    );
+
+                    Some(format!("Couldn't save the ADM configuration: {}", err))
+                }
+            },
+            (None, _) => Some(format!(
+                "Couldn't find a system named `{}`, please check the spelling.",
+                system_name
+            )),
+            (_, Err(error)) => Some(error.to_string()),
+        };
+
+        if let Some(error) = error {
+            response
+                .interaction
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content(error)
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+        }
+    } else {
+        tracing::warn!("modal response is `None`");
+    }
+
+    Ok(())
+}
+
+fn parse_thresholds(warning: &str, critical: &str) -> Result<(f32, f32), &'static str> {
+    let warning_threshold = warning
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| "Warning threshold must be a number, e.g. `4.5`")?;
+    let critical_threshold = critical
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| "Critical threshold must be a number, e.g. `4.0`")?;
+
+    if warning_threshold <= critical_threshold {
+        return Err("Warning threshold must be greater than the critical threshold");
+    }
+
+    Ok((warning_threshold, critical_threshold))
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Configure custom ADM warning and critical thresholds of a system.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::parse_thresholds;
+
+    #[traced_test]
+    #[test]
+    fn parse_thresholds_valid() {
+        assert!(parse_thresholds(" 5.0", "4.5 ") == Ok((5.0, 4.5)));
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_thresholds_invalid() {
+        assert!(parse_thresholds("4.5", "5.0").is_err());
+        assert!(parse_thresholds("4.5", "4.5").is_err());
+        assert!(parse_thresholds("high", "4.0").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType},
+    builder::{
+        CreateAttachment, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::adm_configuration::{AdmConfiguration, ConfigurationFormat};
+
+pub const COMMAND_NAME: &str = "adm_export";
+
+const FORMAT_OPTION: &str = "format";
+
+fn format_from_option(value: Option<&str>, default: ConfigurationFormat) -> ConfigurationFormat {
+    match value {
+        Some(value) if value.eq_ignore_ascii_case("json") => ConfigurationFormat::Json,
+        Some(value) if value.eq_ignore_ascii_case("toml") => ConfigurationFormat::Toml,
+        _ => default,
+    }
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_configuration: &AdmConfiguration,
+) -> anyhow::Result<()> {
+    let format = format_from_option(
+        interaction
+            .data
+            .options
+            .iter()
+            .find(|option| option.name == FORMAT_OPTION)
+            .and_then(|option| option.value.as_str()),
+        adm_configuration.format(),
+    );
+
+    let message = match format.serialize(&adm_configuration.configuration().await) {
+        Ok(data) => CreateInteractionResponseMessage::new()
+            .content("Current ADM configuration.")
+            .add_file(CreateAttachment::bytes(
+                data,
+                format!("adm.{}", format.extension()),
+            )),
+        Err(err) => {
+            tracing::error!(?err, "couldn't export adm configuration");
+
+            CreateInteractionResponseMessage::new()
+                .content(format!("Couldn't export the ADM configuration: {}", err))
+        }
+    };
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(message.ephemeral(true)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Download the current ADM configuration.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                FORMAT_OPTION,
+                "File format (default the configuration file's format).",
+            )
+            .add_string_choice("TOML", "toml")
+            .add_string_choice("JSON", "json")
+            .required(false),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::services::adm_configuration::ConfigurationFormat;
+
+    use super::format_from_option;
+
+    #[traced_test]
+    #[test]
+    fn format_option_falls_back_to_default() {
+        assert!(
+            format_from_option(Some("json"), ConfigurationFormat::Toml)
+                == ConfigurationFormat::Json
+        );
+        assert!(
+            format_from_option(Some("toml"), ConfigurationFormat::Json)
+                == ConfigurationFormat::Toml
+        );
+        assert!(format_from_option(None, ConfigurationFormat::Json) == ConfigurationFormat::Json);
+    }
+}
@@ -0,0 +1,199 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+};
+
+use crate::{
+    health::unix_now,
+    services::{adm_service::AdmService, information_service::InformationService},
+};
+
+pub const COMMAND_NAME: &str = "adm_history";
+
+const SYSTEM_OPTION: &str = "system";
+const DAYS_OPTION: &str = "days";
+
+const DEFAULT_DAYS: i64 = 7;
+const MAX_DAYS: i64 = 90;
+const SECONDS_PER_DAY: u64 = 24 * 3600;
+
+const SPARKLINE_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARKLINE_WIDTH: usize = 48;
+
+fn downsample(values: &[f32], width: usize) -> Vec<f32> {
+    if values.len() <= width {
+        return values.to_vec();
+    }
+
+    (0..width)
+        .map(|bucket| {
+            let start = bucket * values.len() / width;
+            let end = (bucket + 1) * values.len() / width;
+            let bucket = &values[start..end];
+
+            bucket.iter().sum::<f32>() / bucket.len() as f32
+        })
+        .collect()
+}
+
+fn sparkline(values: &[f32]) -> String {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+
+    downsample(values, SPARKLINE_WIDTH)
+        .into_iter()
+        .map(|value| {
+            if range <= f32::EPSILON {
+                return SPARKLINE_BLOCKS[SPARKLINE_BLOCKS.len() / 2];
+            }
+
+            let index = ((value - min) / range * (SPARKLINE_BLOCKS.len() - 1) as f32).round();
+
+            SPARKLINE_BLOCKS[index as usize]
+        })
+        .collect()
+}
+
+async fn history_embed(
+    information: &InformationService,
+    adm_service: &AdmService,
+    system_name: &str,
+    days: i64,
+) -> anyhow::Result<Result<CreateEmbed, String>> {
+    let Some(system) = information.search_system(system_name).await? else {
+        return Ok(Err(format!(
+            "Couldn't find a system named `{}`, please check the spelling.",
+            system_name
+        )));
+    };
+
+    let since = unix_now().saturating_sub(days as u64 * SECONDS_PER_DAY);
+    let readings = adm_service.adm_history(system.system_id, since)?;
+
+    if readings.is_empty() {
+        return Ok(Err(format!(
+            "No ADM readings recorded for `{}` in the last {} days.",
+            system.name, days
+        )));
+    }
+
+    let values: Vec<_> = readings
+        .iter()
+        .map(|reading| reading.status.adm())
+        .collect();
+
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let current = values[values.len() - 1];
+
+    Ok(Ok(CreateEmbed::new()
+        .title(format!("{} ADM, last {} days", system.name, days))
+        .description(format!("`{}`", sparkline(&values)))
+        .field("Min", format!("{:.1}", min), true)
+        .field("Max", format!("{:.1}", max), true)
+        .field("Current", format!("{:.1}", current), true)))
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    information: &InformationService,
+    adm_service: &AdmService,
+) -> anyhow::Result<()> {
+    let options = interaction.data.options();
+
+    let system_name = options.iter().find_map(|option| match option {
+        ResolvedOption {
+            name: SYSTEM_OPTION,
+            value: ResolvedValue::String(system_name),
+            ..
+        } => Some(*system_name),
+        _ => None,
+    });
+
+    let days = options
+        .iter()
+        .find_map(|option| match option {
+            ResolvedOption {
+                name: DAYS_OPTION,
+                value: ResolvedValue::Integer(days),
+                ..
+            } => Some(*days),
+            _ => None,
+        })
+        .unwrap_or(DEFAULT_DAYS)
+        .clamp(1, MAX_DAYS);
+
+    let message = match system_name {
+        Some(system_name) => {
+            match history_embed(information, adm_service, system_name, days).await? {
+                Ok(embed) => CreateInteractionResponseMessage::new().embed(embed),
+                Err(content) => CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            }
+        }
+        None => CreateInteractionResponseMessage::new()
+            .content("Please specify a system.")
+            .ephemeral(true),
+    };
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Show how a system's ADM has changed over time.")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, SYSTEM_OPTION, "System name.")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                DAYS_OPTION,
+                "Number of days to show (default 7).",
+            )
+            .min_int_value(1)
+            .max_int_value(MAX_DAYS as u64),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{downsample, sparkline, SPARKLINE_WIDTH};
+
+    #[traced_test]
+    #[test]
+    fn sparkline_scales_between_min_and_max() {
+        assert!(sparkline(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]) == "▁▂▃▄▅▆▇█");
+        assert!(sparkline(&[5.0, 1.0, 5.0]) == "█▁█");
+    }
+
+    #[traced_test]
+    #[test]
+    fn sparkline_flat_and_empty() {
+        assert!(sparkline(&[2.5, 2.5, 2.5]) == "▅▅▅");
+        assert!(sparkline(&[]).is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn sparkline_downsamples_long_histories() {
+        let values: Vec<_> = (0..500).map(|value| value as f32).collect();
+
+        assert!(sparkline(&values).chars().count() == SPARKLINE_WIDTH);
+        assert!(downsample(&[1.0, 3.0, 5.0, 7.0], 2) == vec![2.0, 6.0]);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{path::Path, time::Duration};
+
+use serenity::{
+    all::{ButtonStyle, CommandInteraction, CommandOptionType, ResolvedValue},
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateCommandOption,
+        CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::adm_configuration::{AdmConfiguration, ConfigurationFormat};
+
+pub const COMMAND_NAME: &str = "adm_import";
+
+const FILE_OPTION: &str = "file";
+const IMPORT_BUTTON_PREFIX: &str = "adm_import_";
+const CONFIRM_BUTTON: &str = "adm_import_confirm";
+const CANCEL_BUTTON: &str = "adm_import_cancel";
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_FILE_SIZE: u32 = 1024 * 1024;
+
+pub fn is_import_button(custom_id: &str) -> bool {
+    custom_id.starts_with(IMPORT_BUTTON_PREFIX)
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_configuration: &AdmConfiguration,
+) -> anyhow::Result<()> {
+    let Some(attachment) =
+        interaction
+            .data
+            .options()
+            .into_iter()
+            .find_map(|option| match option.value {
+                ResolvedValue::Attachment(attachment) if option.name == FILE_OPTION => {
+                    Some(attachment)
+                }
+                _ => None,
+            })
+    else {
+        anyhow::bail!("missing `{}` option", FILE_OPTION);
+    };
+
+    let configuration = if attachment.size > MAX_FILE_SIZE {
+        Err(anyhow::anyhow!(
+            "the file is larger than {} bytes",
+            MAX_FILE_SIZE
+        ))
+    } else {
+        match attachment.download().await {
+            Ok(data) => String::from_utf8(data)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| {
+                    ConfigurationFormat::from_path(Path::new(&attachment.filename)).parse(&data)
+                }),
+            Err(err) => Err(err.into()),
+        }
+    };
+
+    let configuration = match configuration {
+        Ok(configuration) => configuration,
+        Err(err) => {
+            tracing::warn!(?err, filename = attachment.filename, "invalid adm import");
+
+            interaction
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content(format!(
+                                "`{}` is not a valid ADM configuration: {}",
+                                attachment.filename, err
+                            ))
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(format!(
+                        "Replace the ADM configuration with the {} systems in `{}`?",
+                        configuration.system_count(),
+                        attachment.filename
+                    ))
+                    .components(vec![CreateActionRow::Buttons(vec![
+                        CreateButton::new(CONFIRM_BUTTON)
+                            .label("Replace")
+                            .style(ButtonStyle::Danger),
+                        CreateButton::new(CANCEL_BUTTON)
+                            .label("Cancel")
+                            .style(ButtonStyle::Secondary),
+                    ])])
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let message = interaction.get_response(&ctx.http).await?;
+
+    let Some(component) = message
+        .await_component_interaction(ctx)
+        .author_id(interaction.user.id)
+        .timeout(CONFIRM_TIMEOUT)
+        .await
+    else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("Import timed out, the ADM configuration was not changed.")
+                    .components(vec![]),
+            )
+            .await?;
+
+        return Ok(());
+    };
+
+    let content = if component.data.custom_id == CONFIRM_BUTTON {
+        let system_count = configuration.system_count();
+
+        match adm_configuration.replace(configuration).await {
+            Ok(()) => format!(
+                "Imported the ADM configuration for {} systems.",
+                system_count
+            ),
+            Err(err) => {
+                tracing::error!(?err, "couldn't import adm configuration");
+
+                format!("Couldn't import the ADM configuration: {}", err)
+            }
+        }
+    } else {
+        "Import cancelled, the ADM configuration was not changed.".to_string()
+    };
+
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Replace the ADM configuration with an uploaded file.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Attachment,
+                FILE_OPTION,
+                "ADM configuration as `.toml` or `.json`.",
+            )
+            .required(true),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{is_import_button, CANCEL_BUTTON, CONFIRM_BUTTON};
+
+    #[traced_test]
+    #[test]
+    fn import_buttons_are_recognized() {
+        assert!(is_import_button(CONFIRM_BUTTON));
+        assert!(is_import_button(CANCEL_BUTTON));
+        assert!(!is_import_button("page_next"));
+        assert!(!is_import_button("adm_ack_30000142"));
+    }
+}
@@ -0,0 +1,85 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::adm_service::AdmService;
+
+pub const COMMAND_NAME: &str = "adm_include_tcus";
+
+const ENABLED_OPTION: &str = "enabled";
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_service: &AdmService,
+) -> anyhow::Result<()> {
+    let options = interaction.data.options();
+
+    let enabled = options.iter().find_map(|option| match option {
+        ResolvedOption {
+            name: ENABLED_OPTION,
+            value: ResolvedValue::Boolean(enabled),
+            ..
+        } => Some(*enabled),
+        _ => None,
+    });
+
+    let content = match enabled {
+        Some(enabled) => {
+            adm_service.set_include_tcus(enabled);
+
+            tracing::info!(
+                enabled,
+                user = interaction.user.name,
+                "adm include tcus changed"
+            );
+
+            if enabled {
+                "TCUs are now included in ADM reports and alerts.".to_string()
+            } else {
+                "TCUs are no longer included in ADM reports and alerts.".to_string()
+            }
+        }
+        None => format!(
+            "TCUs are currently {} in ADM reports and alerts.",
+            if adm_service.include_tcus() {
+                "included"
+            } else {
+                "not included"
+            }
+        ),
+    };
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Include or exclude TCUs from ADM reports and alerts.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                ENABLED_OPTION,
+                "Whether TCUs are included, leave out to show the current setting.",
+            )
+            .required(false),
+        )
+}
@@ -0,0 +1,136 @@
+use serenity::{
+    all::CommandInteraction,
+    builder::{
+        CreateCommand, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::{
+    commands::pagination::{chunk_values, field_pages, paginate, Field},
+    services::adm_configuration::{AdmConfiguration, Importance},
+};
+
+pub const COMMAND_NAME: &str = "adm_list";
+
+const TITLE: &str = "Configured System Importance";
+
+fn importance_fields(importances: &[(String, Importance)]) -> Vec<Field> {
+    let mut fields = vec![];
+
+    for importance in Importance::ALL {
+        let system_names: Vec<_> = importances
+            .iter()
+            .filter(|(_, system_importance)| *system_importance == importance)
+            .map(|(system_name, _)| system_name.as_str())
+            .collect();
+
+        let chunks = chunk_values(&system_names, ", ");
+        let chunk_count = chunks.len();
+
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let title = if chunk_count > 1 {
+                format!("{} ({}/{})", importance, index + 1, chunk_count)
+            } else {
+                format!("{}", importance)
+            };
+
+            fields.push((title, chunk));
+        }
+    }
+
+    fields
+}
+
+fn list_pages(importances: &[(String, Importance)]) -> Vec<CreateEmbed> {
+    let embed = || CreateEmbed::new().title(TITLE);
+    let pages = field_pages(importance_fields(importances), TITLE.len(), 0);
+
+    if pages.is_empty() {
+        return vec![embed().description("No systems have been configured.")];
+    }
+
+    pages
+        .into_iter()
+        .map(|fields| {
+            fields.into_iter().fold(embed(), |embed, (name, value)| {
+                embed.field(name, value, false)
+            })
+        })
+        .collect()
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_configuration: &AdmConfiguration,
+) -> anyhow::Result<()> {
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let importances = adm_configuration.all_importances().await;
+
+    paginate(ctx, interaction, list_pages(&importances)).await
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("List the configured ADM importance of all systems.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::{commands::pagination::embed_length, services::adm_configuration::Importance};
+
+    use super::list_pages;
+
+    #[traced_test]
+    #[test]
+    fn list_pages_stay_within_embed_limits() {
+        let importances: Vec<_> = (0..3000)
+            .map(|index| (format!("SYSTEM-{:04}", index), Importance::ALL[index % 3]))
+            .collect();
+
+        let pages = list_pages(&importances);
+        let fields: Vec<_> = pages
+            .iter()
+            .flat_map(|page| {
+                serde_json::to_value(page).unwrap()["fields"]
+                    .as_array()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        let listed: usize = fields
+            .iter()
+            .map(|field| field["value"].as_str().unwrap().split(", ").count())
+            .sum();
+
+        assert!(pages.len() > 1);
+        assert!(pages.iter().all(|page| embed_length(page) <= 6000));
+        assert!(fields.len() > 25);
+        assert!(listed == 3000);
+    }
+
+    #[traced_test]
+    #[test]
+    fn list_pages_without_systems() {
+        let pages = list_pages(&[]);
+
+        assert!(pages.len() == 1);
+        assert!(
+            serde_json::to_value(&pages[0]).unwrap()["description"]
+                == "No systems have been configured."
+        );
+    }
+}
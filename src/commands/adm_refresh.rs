@@ -0,0 +1,44 @@
+use serenity::{
+    all::CommandInteraction,
+    builder::{CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage},
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::adm_notification_service::AdmRefreshSender;
+
+pub const COMMAND_NAME: &str = "adm_refresh";
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_refresh: &AdmRefreshSender,
+) -> anyhow::Result<()> {
+    let content = match adm_refresh.send(()) {
+        Ok(()) => "Requested an ADM refresh, notifications will follow shortly.".to_string(),
+        Err(err) => {
+            tracing::error!(?err, "couldn't request adm refresh");
+
+            "Couldn't request an ADM refresh, the ADM service isn't running.".to_string()
+        }
+    };
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Poll ADM levels immediately instead of waiting for the next scheduled poll.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
@@ -0,0 +1,44 @@
+use serenity::{
+    all::CommandInteraction,
+    builder::{CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage},
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::adm_configuration::AdmConfiguration;
+
+pub const COMMAND_NAME: &str = "adm_reload";
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_configuration: &AdmConfiguration,
+) -> anyhow::Result<()> {
+    let content = match adm_configuration.reload().await {
+        Ok(()) => "Reloaded the ADM configuration.".to_string(),
+        Err(err) => {
+            tracing::error!(?err, "couldn't reload adm configuration");
+
+            format!("Couldn't reload the ADM configuration: {}", err)
+        }
+    };
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Reload the ADM configuration from disk.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
@@ -0,0 +1,63 @@
+use serenity::{
+    all::CommandInteraction,
+    builder::{
+        CreateCommand, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::adm_configuration::Importance;
+
+pub const COMMAND_NAME: &str = "adm_thresholds";
+
+fn threshold_field(importance: Importance) -> String {
+    format!(
+        "Warning: ADM ≤ {:.1}\nCritical: ADM ≤ {:.1}",
+        importance.warning_threshold(),
+        importance.critical_threshold()
+    )
+}
+
+pub async fn run(ctx: &Context, interaction: &CommandInteraction) -> anyhow::Result<()> {
+    let embed = Importance::ALL.into_iter().fold(
+        CreateEmbed::new()
+            .title("ADM Thresholds")
+            .description("Systems with custom thresholds use those instead."),
+        |embed, importance| embed.field(importance.name(), threshold_field(importance), true),
+    );
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Show the ADM thresholds for each system importance.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::services::adm_configuration::Importance;
+
+    use super::threshold_field;
+
+    #[traced_test]
+    #[test]
+    fn threshold_field_lists_both_thresholds() {
+        assert!(threshold_field(Importance::Yellow) == "Warning: ADM ≤ 3.2\nCritical: ADM ≤ 3.0");
+    }
+}
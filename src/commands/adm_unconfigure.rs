@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use serenity::{
+    all::CommandInteraction,
+    builder::{CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage},
+    client::Context,
+    model::Permissions,
+    utils::CreateQuickModal,
+};
+
+use crate::services::adm_configuration::AdmConfiguration;
+
+pub const COMMAND_NAME: &str = "adm_unconfigure";
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    adm_configuration: &AdmConfiguration,
+) -> anyhow::Result<()> {
+    let modal = CreateQuickModal::new("Remove ADM Configuration")
+        .timeout(Duration::from_secs(600))
+        .short_field("System");
+
+    let response = interaction.quick_modal(ctx, modal).await?;
+
+    if let Some(response) = response {
+        let system = response.inputs[0].trim().to_uppercase();
+
+        let content = match adm_configuration.remove_importance(&system).await {
+            Ok(true) => format!("Removed the configured importance of `{}`.", system),
+            Ok(false) => format!("`{}` has no configured importance.", system),
+            Err(err) => {
+                tracing::error!(?err, system, "couldn't save adm configuration");
+
+                format!("Couldn't save the ADM configuration: {}", err)
+            }
+        };
+
+        response
+            .interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(content)
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+    } else {
+        tracing::warn!("modal response is `None`");
+    }
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Remove the configured ADM importance of a system.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
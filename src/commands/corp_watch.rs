@@ -0,0 +1,229 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseMessage, EditInteractionResponse,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::{
+    commands::whois::{disambiguation, not_found},
+    services::{
+        corp_watch::CorpWatchList,
+        information_service::{InformationService, Lookup},
+    },
+};
+
+pub const COMMAND_NAME: &str = "corp_watch";
+
+const WATCH_SUBCOMMAND: &str = "watch";
+const UNWATCH_SUBCOMMAND: &str = "unwatch";
+const LIST_SUBCOMMAND: &str = "list";
+const ALLIANCE_OPTION: &str = "alliance";
+
+fn alliance_option<'a>(options: &'a [ResolvedOption<'a>]) -> Option<&'a str> {
+    options.iter().find_map(|option| match option.value {
+        ResolvedValue::String(alliance) if option.name == ALLIANCE_OPTION => Some(alliance),
+        _ => None,
+    })
+}
+
+async fn watch(
+    information: &InformationService,
+    watch_list: &CorpWatchList,
+    query: &str,
+    enable: bool,
+) -> anyhow::Result<String> {
+    let (alliance_id, alliance) = match information.find_alliance(query).await? {
+        Lookup::Found(alliance_id, alliance) => (alliance_id, alliance),
+        Lookup::Ambiguous(matches) => return Ok(disambiguation(query, &matches)),
+        Lookup::NotFound => return Ok(not_found(query, "an alliance")),
+    };
+
+    let content = match enable {
+        true if watch_list.watch(alliance_id).await? => {
+            format!("Now watching `{}` [{}].", alliance.name, alliance.ticker)
+        }
+        true => format!("`{}` is already watched.", alliance.name),
+        false if watch_list.unwatch(alliance_id).await? => {
+            format!(
+                "Stopped watching `{}` [{}].",
+                alliance.name, alliance.ticker
+            )
+        }
+        false => format!("`{}` isn't watched.", alliance.name),
+    };
+
+    Ok(content)
+}
+
+async fn list(information: &InformationService, watch_list: &CorpWatchList) -> String {
+    let mut lines = vec![];
+
+    for alliance_id in watch_list.alliances().await {
+        match information.get_alliance(alliance_id).await {
+            Ok(alliance) => lines.push(format!(
+                "{} [{}] ({})",
+                alliance.name, alliance.ticker, alliance_id
+            )),
+            Err(_) => lines.push(format!("{}", alliance_id)),
+        }
+    }
+
+    if lines.is_empty() {
+        "No alliances are watched.".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    information: &InformationService,
+    watch_list: &CorpWatchList,
+) -> anyhow::Result<()> {
+    // A ticker lookup may have to index every alliance first.
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let options = interaction.data.options();
+
+    let content = match options.first() {
+        Some(ResolvedOption {
+            name,
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => match (*name, alliance_option(options)) {
+            (WATCH_SUBCOMMAND, Some(query)) => watch(information, watch_list, query, true).await?,
+            (UNWATCH_SUBCOMMAND, Some(query)) => {
+                watch(information, watch_list, query, false).await?
+            }
+            (LIST_SUBCOMMAND, _) => list(information, watch_list).await,
+            _ => "Unrecognized corp watch command.".to_string(),
+        },
+        _ => "Unrecognized corp watch command.".to_string(),
+    };
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    let alliance = || {
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            ALLIANCE_OPTION,
+            "Alliance id, name or ticker.",
+        )
+        .required(true)
+    };
+
+    CreateCommand::new(COMMAND_NAME)
+        .description("Watch alliances for corporations joining or leaving.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                WATCH_SUBCOMMAND,
+                "Always notify about corporations joining or leaving an alliance.",
+            )
+            .add_sub_option(alliance()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                UNWATCH_SUBCOMMAND,
+                "Stop watching an alliance.",
+            )
+            .add_sub_option(alliance()),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            LIST_SUBCOMMAND,
+            "List the watched alliances.",
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use reqwest::Url;
+    use tracing_test::traced_test;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::{
+        esi::Esi,
+        services::{corp_watch::CorpWatchList, information_service::InformationService},
+    };
+
+    use super::watch;
+
+    #[traced_test]
+    #[tokio::test]
+    async fn watch_by_alliance_ticker() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/ids/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![99000001]))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/99000001/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "creator_corporation_id": 98000001,
+                "creator_id": 2112000001_u64,
+                "date_founded": "2010-11-04T13:11:00Z",
+                "name": "Guns Alliance",
+                "ticker": "GUNS"
+            })))
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let information = InformationService::new(Arc::new(
+            Esi::new()
+                .with_base_url(base_url)
+                .with_default_cache_ttl(Duration::ZERO),
+        ));
+        let path = std::env::temp_dir().join("alliance_squawk_corp_watch_ticker.json");
+        let _ = std::fs::remove_file(&path);
+
+        let watch_list = CorpWatchList::load_from(&path).await.unwrap();
+
+        let missing = watch(&information, &watch_list, "NOPE", true)
+            .await
+            .unwrap();
+        let content = watch(&information, &watch_list, "guns", true)
+            .await
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(missing == "Couldn't find an alliance matching `NOPE`.");
+        assert!(content == "Now watching `Guns Alliance` [GUNS].");
+        assert!(watch_list.alliances().await == vec![99000001]);
+    }
+}
@@ -1,2 +1,21 @@
 pub mod adm;
-pub mod adm_configure;
\ No newline at end of file
+pub mod adm_ack;
+pub mod adm_configure;
+pub mod adm_configure_thresholds;
+pub mod adm_export;
+#[cfg(feature = "adm-history")]
+pub mod adm_history;
+pub mod adm_import;
+pub mod adm_include_tcus;
+pub mod adm_list;
+pub mod adm_refresh;
+pub mod adm_reload;
+pub mod adm_thresholds;
+pub mod adm_unconfigure;
+pub mod corp_watch;
+pub mod pagination;
+pub mod recent;
+pub mod set_channel;
+pub mod sov;
+pub mod system;
+pub mod whois;
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use serenity::{
+    all::{ButtonStyle, CommandInteraction},
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, EditMessage,
+    },
+    client::Context,
+};
+
+const PAGE_BUTTON_PREFIX: &str = "page_";
+const PREVIOUS_BUTTON: &str = "page_previous";
+const NEXT_BUTTON: &str = "page_next";
+const POSITION_BUTTON: &str = "page_position";
+const PAGE_TIMEOUT: Duration = Duration::from_secs(300);
+pub const FIELD_VALUE_LIMIT: usize = 1024;
+const EMBED_FIELD_LIMIT: usize = 25;
+const EMBED_LENGTH_LIMIT: usize = 6000;
+
+pub type Field = (String, String);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PageTurn {
+    Previous,
+    Next,
+}
+
+impl PageTurn {
+    fn from_custom_id(custom_id: &str) -> Option<PageTurn> {
+        match custom_id {
+            PREVIOUS_BUTTON => Some(PageTurn::Previous),
+            NEXT_BUTTON => Some(PageTurn::Next),
+            _ => None,
+        }
+    }
+}
+
+pub fn is_page_button(custom_id: &str) -> bool {
+    custom_id.starts_with(PAGE_BUTTON_PREFIX)
+}
+
+// Out of range indices are clamped to the last page before turning, turning past either
+// end wraps around.
+fn turn_page(index: usize, page_count: usize, turn: PageTurn) -> usize {
+    if page_count == 0 {
+        return 0;
+    }
+
+    let index = index.min(page_count - 1);
+
+    match turn {
+        PageTurn::Previous => (index + page_count - 1) % page_count,
+        PageTurn::Next => (index + 1) % page_count,
+    }
+}
+
+// Joins values with `separator` into as few chunks as fit in an embed field.
+pub fn chunk_values<S: AsRef<str>>(values: &[S], separator: &str) -> Vec<String> {
+    let mut chunks: Vec<String> = vec![];
+
+    for value in values {
+        let value = value.as_ref();
+
+        match chunks.last_mut() {
+            Some(chunk)
+                if chunk.chars().count() + separator.chars().count() + value.chars().count()
+                    <= FIELD_VALUE_LIMIT =>
+            {
+                chunk.push_str(separator);
+                chunk.push_str(value);
+            }
+            _ => chunks.push(value.to_string()),
+        }
+    }
+
+    chunks
+}
+
+// Splits fields across pages which stay within Discord's per embed limits on field count and
+// total length. The reserved length and fields are left for what every page repeats, like
+// the title, description and footer.
+pub fn field_pages(
+    fields: Vec<Field>,
+    reserved_length: usize,
+    reserved_fields: usize,
+) -> Vec<Vec<Field>> {
+    let length_budget = EMBED_LENGTH_LIMIT.saturating_sub(reserved_length);
+    let field_budget = EMBED_FIELD_LIMIT.saturating_sub(reserved_fields).max(1);
+
+    let mut pages: Vec<Vec<Field>> = vec![];
+    let mut page_length = 0;
+
+    for (name, value) in fields {
+        let length = name.chars().count() + value.chars().count();
+
+        match pages.last_mut() {
+            Some(page) if page.len() < field_budget && page_length + length <= length_budget => {
+                page_length += length;
+                page.push((name, value));
+            }
+            _ => {
+                page_length = length;
+                pages.push(vec![(name, value)]);
+            }
+        }
+    }
+
+    pages
+}
+
+// Counts the characters Discord includes in its 6000 character embed limit.
+#[cfg(test)]
+pub fn embed_length(embed: &CreateEmbed) -> usize {
+    let embed = serde_json::to_value(embed).unwrap();
+    let length = |value: &serde_json::Value| value.as_str().map_or(0, |text| text.chars().count());
+
+    length(&embed["title"])
+        + length(&embed["description"])
+        + length(&embed["author"]["name"])
+        + length(&embed["footer"]["text"])
+        + embed["fields"].as_array().map_or(0, |fields| {
+            fields
+                .iter()
+                .map(|field| length(&field["name"]) + length(&field["value"]))
+                .sum()
+        })
+}
+
+fn page_buttons(index: usize, page_count: usize) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(PREVIOUS_BUTTON)
+            .label("◀")
+            .style(ButtonStyle::Secondary),
+        CreateButton::new(POSITION_BUTTON)
+            .label(format!("{}/{}", index + 1, page_count))
+            .style(ButtonStyle::Secondary)
+            .disabled(true),
+        CreateButton::new(NEXT_BUTTON)
+            .label("▶")
+            .style(ButtonStyle::Secondary),
+    ])]
+}
+
+// Posts `pages` as a followup to an already deferred interaction. Only the user who ran
+// the command can turn pages, and the buttons are removed once nobody has used them for
+// a while.
+pub async fn paginate(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pages: Vec<CreateEmbed>,
+) -> anyhow::Result<()> {
+    let Some(first_page) = pages.first() else {
+        anyhow::bail!("nothing to paginate");
+    };
+
+    if pages.len() == 1 {
+        interaction
+            .create_followup(
+                &ctx.http,
+                CreateInteractionResponseFollowup::new().embed(first_page.clone()),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let mut message = interaction
+        .create_followup(
+            &ctx.http,
+            CreateInteractionResponseFollowup::new()
+                .embed(first_page.clone())
+                .components(page_buttons(0, pages.len())),
+        )
+        .await?;
+
+    let mut index = 0;
+
+    while let Some(component) = message
+        .await_component_interaction(ctx)
+        .author_id(interaction.user.id)
+        .timeout(PAGE_TIMEOUT)
+        .await
+    {
+        if let Some(turn) = PageTurn::from_custom_id(&component.data.custom_id) {
+            index = turn_page(index, pages.len(), turn);
+        }
+
+        component
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(pages[index].clone())
+                        .components(page_buttons(index, pages.len())),
+                ),
+            )
+            .await?;
+    }
+
+    message
+        .edit(&ctx.http, EditMessage::new().components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{
+        chunk_values, field_pages, is_page_button, turn_page, PageTurn, EMBED_FIELD_LIMIT,
+        FIELD_VALUE_LIMIT, NEXT_BUTTON, PREVIOUS_BUTTON,
+    };
+
+    #[traced_test]
+    #[test]
+    fn turn_page_wraps() {
+        assert!(turn_page(0, 3, PageTurn::Next) == 1);
+        assert!(turn_page(2, 3, PageTurn::Next) == 0);
+        assert!(turn_page(1, 3, PageTurn::Previous) == 0);
+        assert!(turn_page(0, 3, PageTurn::Previous) == 2);
+        assert!(turn_page(0, 1, PageTurn::Next) == 0);
+        assert!(turn_page(0, 1, PageTurn::Previous) == 0);
+    }
+
+    #[traced_test]
+    #[test]
+    fn turn_page_clamps() {
+        assert!(turn_page(7, 3, PageTurn::Next) == 0);
+        assert!(turn_page(7, 3, PageTurn::Previous) == 1);
+        assert!(turn_page(0, 0, PageTurn::Next) == 0);
+        assert!(turn_page(3, 0, PageTurn::Previous) == 0);
+    }
+
+    #[traced_test]
+    #[test]
+    fn page_buttons_are_recognized() {
+        assert!(PageTurn::from_custom_id(PREVIOUS_BUTTON) == Some(PageTurn::Previous));
+        assert!(PageTurn::from_custom_id(NEXT_BUTTON) == Some(PageTurn::Next));
+        assert!(PageTurn::from_custom_id("adm_import_confirm").is_none());
+        assert!(is_page_button(NEXT_BUTTON));
+        assert!(!is_page_button("adm_import_confirm"));
+    }
+
+    #[traced_test]
+    #[test]
+    fn chunk_values_fits_field_limit() {
+        let values: Vec<_> = (0..200)
+            .map(|index| format!("System-{:03} (IHub) ▼", index))
+            .collect();
+
+        let chunks = chunk_values(&values, ", ");
+
+        assert!(chunks.len() > 1);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.chars().count() <= FIELD_VALUE_LIMIT));
+        assert!(chunks.join(", ") == values.join(", "));
+        assert!(chunk_values(&values, "\n").join("\n") == values.join("\n"));
+    }
+
+    #[traced_test]
+    #[test]
+    fn chunk_values_short_list() {
+        assert!(chunk_values(&["JITA", "AMARR"], ", ") == vec!["JITA, AMARR"]);
+        assert!(chunk_values::<&str>(&[], ", ").is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn field_pages_respect_embed_limits() {
+        let fields: Vec<_> = (0..40)
+            .map(|index| (format!("Field {}", index), "x".repeat(1000)))
+            .collect();
+
+        let pages = field_pages(fields.clone(), 500, 1);
+        let page_length = |page: &Vec<(String, String)>| -> usize {
+            page.iter()
+                .map(|(name, value)| name.chars().count() + value.chars().count())
+                .sum()
+        };
+
+        assert!(pages.len() == 8);
+        assert!(pages.iter().all(|page| page_length(page) + 500 <= 6000));
+        assert!(pages.concat() == fields);
+
+        let short_fields: Vec<_> = (0..30)
+            .map(|index| (format!("Field {}", index), "x".to_string()))
+            .collect();
+        let pages = field_pages(short_fields, 0, 1);
+
+        assert!(pages.iter().map(Vec::len).collect::<Vec<_>>() == vec![EMBED_FIELD_LIMIT - 1, 6]);
+        assert!(field_pages(vec![], 0, 0).is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+};
+
+use crate::{
+    bot::BotNotification,
+    esi::EsiID,
+    services::{
+        adm_service::{Status, SystemAdm},
+        event_log::EventLog,
+        information_service::InformationService,
+    },
+};
+
+pub const COMMAND_NAME: &str = "recent";
+
+const COUNT_OPTION: &str = "count";
+
+const DEFAULT_COUNT: i64 = 10;
+const MAX_COUNT: i64 = 25;
+
+fn notification_ids(notification: &BotNotification) -> Vec<EsiID> {
+    match notification {
+        BotNotification::NotifyCorpJoinAlliance(alliance_id, corporation_id)
+        | BotNotification::NotifyCorpLeftAlliance(alliance_id, corporation_id) => {
+            vec![*alliance_id, *corporation_id]
+        }
+        BotNotification::NotifyCorpMovedAlliance(
+            corporation_id,
+            from_alliance_id,
+            to_alliance_id,
+        ) => {
+            vec![*corporation_id, *from_alliance_id, *to_alliance_id]
+        }
+        BotNotification::NotifyAllianceCreated(alliance_id)
+        | BotNotification::NotifyAllianceClosed(alliance_id) => vec![*alliance_id],
+        BotNotification::NotifyMemberChange(corporation_id, ..) => vec![*corporation_id],
+        BotNotification::NotifyAdm(system_adm)
+        | BotNotification::NotifyAdmRecovered(system_adm)
+        | BotNotification::NotifyAdmStillCritical(system_adm, _) => {
+            vec![system_adm.system_id]
+        }
+        BotNotification::NotifySovLost(system_id)
+        | BotNotification::NotifyVulnerable(system_id, _) => vec![*system_id],
+        BotNotification::AdmDigest(_) | BotNotification::AdmStartupReport(_) => vec![],
+    }
+}
+
+fn describe_adm(system_adm: &SystemAdm, name: &str) -> String {
+    match system_adm.status {
+        Status::Critical(adm) => format!("{} ADM critical ({:.1})", name, adm),
+        Status::Warning(adm) => format!("{} ADM deteriorated ({:.1})", name, adm),
+        Status::Good(adm) => format!("{} ADM recovered ({:.1})", name, adm),
+    }
+}
+
+fn describe(notification: &BotNotification, names: &HashMap<EsiID, String>) -> String {
+    let name = |id: &EsiID| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    match notification {
+        BotNotification::NotifyCorpJoinAlliance(alliance_id, corporation_id) => {
+            format!("{} joined {}", name(corporation_id), name(alliance_id))
+        }
+        BotNotification::NotifyCorpLeftAlliance(alliance_id, corporation_id) => {
+            format!("{} left {}", name(corporation_id), name(alliance_id))
+        }
+        BotNotification::NotifyCorpMovedAlliance(
+            corporation_id,
+            from_alliance_id,
+            to_alliance_id,
+        ) => {
+            format!(
+                "{} moved from {} to {}",
+                name(corporation_id),
+                name(from_alliance_id),
+                name(to_alliance_id)
+            )
+        }
+        BotNotification::NotifyAllianceCreated(alliance_id) => {
+            format!("{} created", name(alliance_id))
+        }
+        BotNotification::NotifyAllianceClosed(alliance_id) => {
+            format!("{} closed", name(alliance_id))
+        }
+        BotNotification::NotifyMemberChange(corporation_id, previous_members, members) => {
+            format!(
+                "{} members changed from {} to {}",
+                name(corporation_id),
+                previous_members,
+                members
+            )
+        }
+        BotNotification::NotifyAdm(system_adm)
+        | BotNotification::NotifyAdmRecovered(system_adm) => {
+            describe_adm(system_adm, &name(&system_adm.system_id))
+        }
+        BotNotification::NotifyAdmStillCritical(system_adm, critical_seconds) => format!(
+            "{} ADM still critical after {}h ({:.1})",
+            name(&system_adm.system_id),
+            critical_seconds / 3600,
+            system_adm.status.adm()
+        ),
+        BotNotification::NotifySovLost(system_id) => {
+            format!("{} sovereignty lost", name(system_id))
+        }
+        BotNotification::NotifyVulnerable(system_id, window) => format!(
+            "{} vulnerable from <t:{}:t>",
+            name(system_id),
+            window.start.timestamp()
+        ),
+        BotNotification::AdmDigest(system_adms) => {
+            format!("ADM digest ({} systems)", system_adms.len())
+        }
+        BotNotification::AdmStartupReport(system_adms) => {
+            format!("ADM startup report ({} systems)", system_adms.len())
+        }
+    }
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    information: &InformationService,
+    event_log: &EventLog,
+) -> anyhow::Result<()> {
+    let count = interaction
+        .data
+        .options()
+        .iter()
+        .find_map(|option| match option {
+            ResolvedOption {
+                name: COUNT_OPTION,
+                value: ResolvedValue::Integer(count),
+                ..
+            } => Some(*count),
+            _ => None,
+        })
+        .unwrap_or(DEFAULT_COUNT)
+        .clamp(1, MAX_COUNT);
+
+    let entries = event_log.recent(count as usize).await;
+
+    let mut ids: Vec<_> = entries
+        .iter()
+        .flat_map(|entry| notification_ids(&entry.notification))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let names = match information.get_names(&ids).await {
+        Ok(names) => names,
+        Err(err) => {
+            tracing::warn!(?err, "couldn't resolve recent event names");
+            HashMap::new()
+        }
+    };
+
+    let description = if entries.is_empty() {
+        "No events recorded yet.".to_string()
+    } else {
+        entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "<t:{}:R> {}",
+                    entry.timestamp,
+                    describe(&entry.notification, &names)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title("Recent Events")
+        .description(description);
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Show the most recent notifications.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                COUNT_OPTION,
+                "Number of events to show (default 10).",
+            )
+            .min_int_value(1)
+            .max_int_value(MAX_COUNT as u64),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tracing_test::traced_test;
+
+    use crate::{
+        bot::BotNotification,
+        services::adm_service::{Status, SystemAdm},
+    };
+
+    use super::{describe, notification_ids};
+
+    #[traced_test]
+    #[test]
+    fn describe_uses_names_with_id_fallback() {
+        let names = HashMap::from([(1, "Dreddit".to_string()), (2, "Goonswarm".to_string())]);
+
+        let moved = BotNotification::NotifyCorpMovedAlliance(1, 2, 3);
+        assert!(notification_ids(&moved) == vec![1, 2, 3]);
+        assert!(describe(&moved, &names) == "Dreddit moved from Goonswarm to 3");
+
+        let adm = BotNotification::NotifyAdm(SystemAdm::for_test(30000142, Status::Critical(0.8)));
+        assert!(describe(&adm, &names) == "30000142 ADM critical (0.8)");
+    }
+}
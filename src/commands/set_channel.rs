@@ -0,0 +1,103 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::services::channel_config::ChannelConfig;
+
+pub const ADM_COMMAND_NAME: &str = "set_adm_channel";
+pub const CORP_COMMAND_NAME: &str = "set_corp_channel";
+
+const CHANNEL_OPTION: &str = "channel";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelKind {
+    Adm,
+    Corp,
+}
+
+impl ChannelKind {
+    fn command_name(&self) -> &'static str {
+        match self {
+            ChannelKind::Adm => ADM_COMMAND_NAME,
+            ChannelKind::Corp => CORP_COMMAND_NAME,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ChannelKind::Adm => "ADM",
+            ChannelKind::Corp => "Corporation",
+        }
+    }
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    channel_config: &ChannelConfig,
+    kind: ChannelKind,
+) -> anyhow::Result<()> {
+    let channel_id = interaction
+        .data
+        .options()
+        .iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::Channel(channel) if option.name == CHANNEL_OPTION => Some(channel.id),
+            _ => None,
+        })
+        .unwrap_or(interaction.channel_id);
+
+    let result = match kind {
+        ChannelKind::Adm => channel_config.set_adm_channel(channel_id.get()).await,
+        ChannelKind::Corp => channel_config.set_corp_channel(channel_id.get()).await,
+    };
+
+    let content = match result {
+        Ok(()) => format!(
+            "{} notifications will be posted in <#{}>.",
+            kind.label(),
+            channel_id
+        ),
+        Err(err) => {
+            tracing::error!(?err, ?kind, "couldn't save notification channel");
+
+            "Couldn't save the notification channel.".to_string()
+        }
+    };
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register(kind: ChannelKind) -> CreateCommand {
+    CreateCommand::new(kind.command_name())
+        .description(match kind {
+            ChannelKind::Adm => "Set the channel ADM notifications are posted in.",
+            ChannelKind::Corp => "Set the channel corporation notifications are posted in.",
+        })
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Channel,
+                CHANNEL_OPTION,
+                "Channel to post in (default this channel).",
+            )
+            .required(false),
+        )
+}
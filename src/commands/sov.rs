@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+use serenity::{
+    all::CommandInteraction,
+    builder::{
+        CreateCommand, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+
+use crate::{
+    commands::{
+        adm::stale_notice,
+        pagination::{chunk_values, paginate},
+    },
+    esi::EsiID,
+    services::{
+        adm_service::{AdmService, Status, SystemAdm},
+        information_service::InformationService,
+    },
+    theme::Branding,
+};
+
+pub const COMMAND_NAME: &str = "sov";
+
+// Keeps each embed below Discord's 6000 character limit.
+const FIELDS_PER_PAGE: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+struct SovSystem {
+    name: String,
+    system_adm: SystemAdm,
+}
+
+pub fn status_label(status: Status) -> &'static str {
+    match status {
+        Status::Good(_) => "good",
+        Status::Warning(_) => "warning",
+        Status::Critical(_) => "critical",
+    }
+}
+
+fn sov_lines(systems: &mut [SovSystem]) -> Vec<String> {
+    systems.sort_by(|a, b| a.name.cmp(&b.name));
+
+    systems
+        .iter()
+        .map(|system| {
+            format!(
+                "**{}** ADM {:.1} {} ({}) - {}",
+                system.name,
+                system.system_adm.status.adm(),
+                system.system_adm.trend(),
+                status_label(system.system_adm.status),
+                system.system_adm.importance
+            )
+        })
+        .collect()
+}
+
+fn sov_pages(lines: &[String]) -> Vec<Vec<String>> {
+    chunk_values(lines, "\n")
+        .chunks(FIELDS_PER_PAGE)
+        .map(|page| page.to_vec())
+        .collect()
+}
+
+fn sov_systems(
+    system_names: &HashMap<EsiID, String>,
+    system_adms: Vec<SystemAdm>,
+) -> Vec<SovSystem> {
+    system_adms
+        .into_iter()
+        .filter_map(|system_adm| match system_names.get(&system_adm.system_id) {
+            Some(name) => Some(SovSystem {
+                name: name.clone(),
+                system_adm,
+            }),
+            None => {
+                tracing::warn!(
+                    system_id = system_adm.system_id,
+                    "couldn't resolve system name"
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    information: &InformationService,
+    adm_service: &AdmService,
+    branding: &Branding,
+) -> anyhow::Result<()> {
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let snapshot = match adm_service.get_adm_snapshot().await {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            tracing::error!("{}", error);
+
+            interaction
+                .create_followup(
+                    &ctx.http,
+                    CreateInteractionResponseFollowup::new()
+                        .content("Error fetching sovereignty from ESI. Please try again later.")
+                        .ephemeral(true),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let system_ids: Vec<_> = snapshot
+        .systems
+        .iter()
+        .map(|system_adm| system_adm.system_id)
+        .collect();
+
+    let system_names = information
+        .get_system_names(&system_ids)
+        .await
+        .context("get system names")?;
+
+    let mut systems = sov_systems(&system_names, snapshot.systems);
+    let pages = sov_pages(&sov_lines(&mut systems));
+
+    let description = match snapshot.stale_for {
+        Some(stale_for) => format!("{} systems\n{}", systems.len(), stale_notice(stale_for)),
+        None => format!("{} systems", systems.len()),
+    };
+
+    if pages.is_empty() {
+        interaction
+            .create_followup(
+                &ctx.http,
+                CreateInteractionResponseFollowup::new().embed(
+                    branding
+                        .embed("Sovereignty Holdings")
+                        .description("The alliance holds no sovereignty."),
+                ),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let embeds = pages
+        .into_iter()
+        .map(|page| {
+            page.into_iter().fold(
+                branding
+                    .embed("Sovereignty Holdings")
+                    .description(&description),
+                |embed, chunk| embed.field("Systems", chunk, false),
+            )
+        })
+        .collect();
+
+    paginate(ctx, interaction, embeds).await
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("List every system the alliance holds sovereignty in.")
+        .default_member_permissions(Permissions::SEND_MESSAGES)
+        .dm_permission(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tracing_test::traced_test;
+
+    use crate::services::{
+        adm_configuration::Importance,
+        adm_service::{Status, SystemAdm, SystemImportance},
+    };
+
+    use super::{sov_lines, sov_pages, sov_systems, SovSystem, FIELDS_PER_PAGE};
+
+    fn sov_system(name: &str, status: Status, importance: SystemImportance) -> SovSystem {
+        SovSystem {
+            name: name.to_string(),
+            system_adm: SystemAdm {
+                previous_adm: Some(status.adm()),
+                importance,
+                ..SystemAdm::for_test(30000142, status)
+            },
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn sov_lines_are_sorted_by_name() {
+        let mut systems = vec![
+            sov_system(
+                "T5ZI-S",
+                Status::Warning(3.1),
+                SystemImportance::Configured(Importance::Yellow),
+            ),
+            sov_system(
+                "1DQ1-A",
+                Status::Good(5.0),
+                SystemImportance::Default(Importance::Green),
+            ),
+            sov_system(
+                "GE-8JV",
+                Status::Critical(1.8),
+                SystemImportance::Custom(2.5, 2.0),
+            ),
+        ];
+
+        assert!(
+            sov_lines(&mut systems)
+                == vec![
+                    "**1DQ1-A** ADM 5.0 ▬ (good) - Green (default)",
+                    "**GE-8JV** ADM 1.8 ▬ (critical) - Custom (2.0 - 2.5)",
+                    "**T5ZI-S** ADM 3.1 ▬ (warning) - Yellow",
+                ]
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn sov_pages_split_many_systems() {
+        let lines: Vec<_> = (0..1000)
+            .map(|index| format!("**System-{:03}** ADM 4.0 ▬ (good) - Red", index))
+            .collect();
+
+        let pages = sov_pages(&lines);
+
+        assert!(pages.len() > 1);
+        assert!(pages.iter().all(|page| page.len() <= FIELDS_PER_PAGE));
+        assert!(pages.concat().join("\n") == lines.join("\n"));
+        assert!(sov_pages(&[]).is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn sov_systems_skip_unresolved_names() {
+        let system_adms = vec![
+            SystemAdm::for_test(30000142, Status::Good(5.0)),
+            SystemAdm::for_test(30000144, Status::Good(4.0)),
+        ];
+        let system_names = HashMap::from([(30000142, "1DQ1-A".to_string())]);
+
+        let systems = sov_systems(&system_names, system_adms);
+
+        assert!(systems.len() == 1);
+        assert!(systems[0].name == "1DQ1-A");
+        assert!(systems[0].system_adm.system_id == 30000142);
+    }
+}
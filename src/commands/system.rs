@@ -0,0 +1,252 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage, EditInteractionResponse,
+    },
+    client::Context,
+};
+
+use crate::{
+    commands::{adm::stale_notice, sov::status_label},
+    esi::{Constellation, Region, System},
+    services::{
+        adm_service::{AdmService, SystemAdm, SystemImportance},
+        information_service::InformationService,
+    },
+    theme::Branding,
+};
+
+pub const COMMAND_NAME: &str = "system";
+
+const NAME_OPTION: &str = "name";
+
+type EmbedField = (&'static str, String, bool);
+
+fn system_fields(
+    system: &System,
+    constellation: &Constellation,
+    region: &Region,
+    system_adm: Option<&SystemAdm>,
+    importance: SystemImportance,
+) -> Vec<EmbedField> {
+    let mut fields = vec![
+        ("Security", format!("{:.1}", system.security_status), true),
+        ("Constellation", constellation.name.clone(), true),
+        ("Region", region.name.clone(), true),
+    ];
+
+    match system_adm {
+        Some(system_adm) => fields.extend([
+            ("Sovereignty", "Held by us".to_string(), true),
+            (
+                "ADM",
+                format!(
+                    "{:.1} {} ({})",
+                    system_adm.status.adm(),
+                    system_adm.trend(),
+                    status_label(system_adm.status)
+                ),
+                true,
+            ),
+        ]),
+        None => fields.push(("Sovereignty", "Not held by us".to_string(), true)),
+    }
+
+    fields.push(("Importance", importance.to_string(), true));
+
+    fields
+}
+
+async fn system_embed(
+    information: &InformationService,
+    adm_service: &AdmService,
+    branding: &Branding,
+    system_name: &str,
+) -> anyhow::Result<Result<CreateEmbed, String>> {
+    let Some(system) = information.search_system(system_name).await? else {
+        return Ok(Err(format!(
+            "Couldn't find a system named `{}`, please check the spelling.",
+            system_name
+        )));
+    };
+
+    let constellation = information
+        .get_constellation(system.constellation_id)
+        .await?;
+    let region = information.get_region(constellation.region_id).await?;
+    let snapshot = adm_service.get_adm_snapshot().await?;
+    let importance = adm_service.system_importance(&system.name).await;
+
+    let system_adm = snapshot
+        .systems
+        .iter()
+        .find(|system_adm| system_adm.system_id == system.system_id);
+
+    let embed = branding
+        .embed(&system.name)
+        .url(format!(
+            "https://evemaps.dotlan.net/system/{}",
+            system.name.replace(' ', "_")
+        ))
+        .fields(system_fields(
+            &system,
+            &constellation,
+            &region,
+            system_adm,
+            importance,
+        ));
+
+    Ok(Ok(match snapshot.stale_for {
+        Some(stale_for) => embed.description(stale_notice(stale_for)),
+        None => embed,
+    }))
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    information: &InformationService,
+    adm_service: &AdmService,
+    branding: &Branding,
+) -> anyhow::Result<()> {
+    let options = interaction.data.options();
+
+    let system_name = options.iter().find_map(|option| match option {
+        ResolvedOption {
+            name: NAME_OPTION,
+            value: ResolvedValue::String(system_name),
+            ..
+        } => Some(*system_name),
+        _ => None,
+    });
+
+    let Some(system_name) = system_name else {
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("Please specify a system.")
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+
+        return Ok(());
+    };
+
+    // Looking the system up may take several ESI requests, longer than discord waits for
+    // the initial response.
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let response = match system_embed(information, adm_service, branding, system_name).await? {
+        Ok(embed) => EditInteractionResponse::new().embed(embed),
+        Err(content) => EditInteractionResponse::new().content(content),
+    };
+
+    interaction.edit_response(&ctx.http, response).await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Show a system's location, sovereignty and ADM.")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, NAME_OPTION, "System name.")
+                .required(true),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::{
+        esi::{Constellation, Region, System},
+        services::{
+            adm_configuration::Importance,
+            adm_service::{Status, SystemAdm, SystemImportance},
+        },
+    };
+
+    use super::system_fields;
+
+    fn stub_system() -> (System, Constellation, Region) {
+        (
+            System {
+                system_id: 30004759,
+                constellation_id: 20000696,
+                name: "1DQ1-A".to_string(),
+                security_status: -0.38,
+            },
+            Constellation {
+                constellation_id: 20000696,
+                name: "O-EIMK".to_string(),
+                region_id: 10000060,
+            },
+            Region {
+                region_id: 10000060,
+                name: "Delve".to_string(),
+            },
+        )
+    }
+
+    fn field<'a>(fields: &'a [(&'static str, String, bool)], name: &str) -> Option<&'a str> {
+        fields
+            .iter()
+            .find(|(field_name, _, _)| *field_name == name)
+            .map(|(_, value, _)| value.as_str())
+    }
+
+    #[traced_test]
+    #[test]
+    fn system_fields_with_sov() {
+        let (system, constellation, region) = stub_system();
+        let system_adm = SystemAdm {
+            previous_adm: Some(2.8),
+            importance: SystemImportance::Configured(Importance::Red),
+            ..SystemAdm::for_test(system.system_id, Status::Warning(2.4))
+        };
+
+        let fields = system_fields(
+            &system,
+            &constellation,
+            &region,
+            Some(&system_adm),
+            system_adm.importance,
+        );
+
+        assert!(field(&fields, "Security") == Some("-0.4"));
+        assert!(field(&fields, "Constellation") == Some("O-EIMK"));
+        assert!(field(&fields, "Region") == Some("Delve"));
+        assert!(field(&fields, "Sovereignty") == Some("Held by us"));
+        assert!(field(&fields, "ADM") == Some("2.4 ▼ (warning)"));
+        assert!(field(&fields, "Importance") == Some("Red"));
+    }
+
+    #[traced_test]
+    #[test]
+    fn system_fields_without_sov() {
+        let (system, constellation, region) = stub_system();
+
+        let fields = system_fields(
+            &system,
+            &constellation,
+            &region,
+            None,
+            SystemImportance::Default(Importance::Green),
+        );
+
+        assert!(field(&fields, "Sovereignty") == Some("Not held by us"));
+        assert!(field(&fields, "ADM").is_none());
+        assert!(field(&fields, "Importance") == Some("Green (default)"));
+        assert!(fields.len() == 5);
+    }
+}
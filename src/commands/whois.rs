@@ -0,0 +1,333 @@
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage, EditInteractionResponse,
+    },
+    client::Context,
+};
+
+use crate::{
+    esi::{Alliance, Corporation, EntityKind, EntityMatch, EsiID},
+    services::information_service::InformationService,
+};
+
+pub const COMMAND_NAME: &str = "whois";
+
+const QUERY_OPTION: &str = "query";
+
+const MAX_SUGGESTIONS: usize = 5;
+
+type EmbedField = (&'static str, String, bool);
+
+fn corporation_fields(
+    corporation: &Corporation,
+    ceo_name: &str,
+    alliance: Option<&Alliance>,
+) -> Vec<EmbedField> {
+    vec![
+        ("Ticker", format!("[{}]", corporation.ticker), true),
+        ("Members", format!("{}", corporation.member_count), true),
+        (
+            "Tax Rate",
+            format!("{:.1}%", corporation.tax_rate * 100.0),
+            true,
+        ),
+        ("CEO", ceo_name.to_string(), true),
+        (
+            "Alliance",
+            alliance
+                .map(|alliance| format!("{} [{}]", alliance.name, alliance.ticker))
+                .unwrap_or_else(|| "None".to_string()),
+            true,
+        ),
+    ]
+}
+
+fn alliance_fields(
+    alliance: &Alliance,
+    executor: Option<&Corporation>,
+    corporation_count: usize,
+) -> Vec<EmbedField> {
+    vec![
+        ("Ticker", format!("[{}]", alliance.ticker), true),
+        (
+            "Executor",
+            executor
+                .map(|executor| format!("{} [{}]", executor.name, executor.ticker))
+                .unwrap_or_else(|| "None".to_string()),
+            true,
+        ),
+        (
+            "Founded",
+            alliance.date_founded.format("%Y-%m-%d").to_string(),
+            true,
+        ),
+        ("Corporations", format!("{}", corporation_count), true),
+    ]
+}
+
+pub fn disambiguation(query: &str, matches: &[EntityMatch]) -> String {
+    let mut lines = vec![format!(
+        "`{}` matches more than one alliance or corporation, please use an id:",
+        query
+    )];
+
+    lines.extend(
+        matches
+            .iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|entity_match| format!("- {}", entity_match)),
+    );
+
+    if matches.len() > MAX_SUGGESTIONS {
+        lines.push(format!("- and {} more", matches.len() - MAX_SUGGESTIONS));
+    }
+
+    lines.join("\n")
+}
+
+pub fn not_found(query: &str, kind: &str) -> String {
+    format!("Couldn't find {} matching `{}`.", kind, query)
+}
+
+async fn alliance_embed(
+    information: &InformationService,
+    alliance_id: EsiID,
+) -> anyhow::Result<CreateEmbed> {
+    let alliance = information.get_alliance(alliance_id).await?;
+    let executor = match alliance.executor_corporation_id {
+        Some(executor_id) => Some(information.get_corporation(executor_id).await?),
+        None => None,
+    };
+    let corporation_count = information
+        .get_alliance_corporation_ids(alliance_id)
+        .await?
+        .len();
+
+    Ok(CreateEmbed::new()
+        .title(&alliance.name)
+        .url(format!(
+            "https://evemaps.dotlan.net/alliance/{}",
+            alliance.name.replace(' ', "_")
+        ))
+        .fields(alliance_fields(
+            &alliance,
+            executor.as_ref(),
+            corporation_count,
+        )))
+}
+
+async fn corporation_embed(
+    information: &InformationService,
+    corporation_id: EsiID,
+) -> anyhow::Result<CreateEmbed> {
+    let corporation = information.get_corporation(corporation_id).await?;
+    let ceo_name = information
+        .get_name(corporation.ceo_id)
+        .await?
+        .unwrap_or_else(|| corporation.ceo_id.to_string());
+    let alliance = match corporation.alliance_id {
+        Some(alliance_id) => Some(information.get_alliance(alliance_id).await?),
+        None => None,
+    };
+
+    Ok(CreateEmbed::new()
+        .title(&corporation.name)
+        .url(format!(
+            "https://evemaps.dotlan.net/corp/{}",
+            corporation.name.replace(' ', "_")
+        ))
+        .fields(corporation_fields(
+            &corporation,
+            &ceo_name,
+            alliance.as_ref(),
+        )))
+}
+
+async fn whois_embed(
+    information: &InformationService,
+    query: &str,
+) -> anyhow::Result<Result<CreateEmbed, String>> {
+    let matches = information.resolve_entities(query).await?;
+
+    match matches.as_slice() {
+        [] => Ok(Err(not_found(query, "an alliance or corporation"))),
+        [entity_match] => match entity_match.kind {
+            EntityKind::Alliance => Ok(Ok(alliance_embed(information, entity_match.id).await?)),
+            EntityKind::Corporation => {
+                Ok(Ok(corporation_embed(information, entity_match.id).await?))
+            }
+        },
+        _ => Ok(Err(disambiguation(query, &matches))),
+    }
+}
+
+pub async fn run(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    information: &InformationService,
+) -> anyhow::Result<()> {
+    let options = interaction.data.options();
+
+    let query = options.iter().find_map(|option| match option {
+        ResolvedOption {
+            name: QUERY_OPTION,
+            value: ResolvedValue::String(query),
+            ..
+        } => Some(*query),
+        _ => None,
+    });
+
+    let Some(query) = query else {
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("Please specify an alliance or corporation.")
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+
+        return Ok(());
+    };
+
+    // Searching and resolving the CEO and alliance may take longer than discord waits for
+    // the initial response.
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let response = match whois_embed(information, query).await? {
+        Ok(embed) => EditInteractionResponse::new().embed(embed),
+        Err(content) => EditInteractionResponse::new().content(content),
+    };
+
+    interaction.edit_response(&ctx.http, response).await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Look up an alliance or corporation.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                QUERY_OPTION,
+                "Alliance or corporation name, ticker or id.",
+            )
+            .required(true),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use tracing_test::traced_test;
+
+    use crate::esi::{Alliance, Corporation, EntityKind, EntityMatch};
+
+    use super::{alliance_fields, corporation_fields, disambiguation};
+
+    fn alliance() -> Alliance {
+        Alliance {
+            creator_corporation_id: 98000001,
+            creator_id: 2112000001,
+            date_founded: Utc.with_ymd_and_hms(2010, 11, 4, 13, 11, 0).unwrap(),
+            executor_corporation_id: Some(98000001),
+            faction_id: None,
+            name: "Test Alliance Please Ignore".to_string(),
+            ticker: "TEST".to_string(),
+        }
+    }
+
+    fn corporation() -> Corporation {
+        Corporation {
+            alliance_id: Some(99000001),
+            ceo_id: 2112000001,
+            creator_id: 2112000001,
+            date_founded: None,
+            description: None,
+            faction_id: None,
+            home_station_id: None,
+            member_count: 1234,
+            name: "Dreddit".to_string(),
+            shares: None,
+            tax_rate: 0.075,
+            ticker: "B0RT".to_string(),
+            url: None,
+            war_eligible: None,
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn corporation_fields_format() {
+        let fields = corporation_fields(&corporation(), "Some Ceo", Some(&alliance()));
+
+        assert!(
+            fields
+                == vec![
+                    ("Ticker", "[B0RT]".to_string(), true),
+                    ("Members", "1234".to_string(), true),
+                    ("Tax Rate", "7.5%".to_string(), true),
+                    ("CEO", "Some Ceo".to_string(), true),
+                    (
+                        "Alliance",
+                        "Test Alliance Please Ignore [TEST]".to_string(),
+                        true
+                    ),
+                ]
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn corporation_fields_without_alliance() {
+        let fields = corporation_fields(&corporation(), "Some Ceo", None);
+
+        assert!(fields[4] == ("Alliance", "None".to_string(), true));
+    }
+
+    #[traced_test]
+    #[test]
+    fn alliance_fields_format() {
+        let fields = alliance_fields(&alliance(), Some(&corporation()), 42);
+
+        assert!(
+            fields
+                == vec![
+                    ("Ticker", "[TEST]".to_string(), true),
+                    ("Executor", "Dreddit [B0RT]".to_string(), true),
+                    ("Founded", "2010-11-04".to_string(), true),
+                    ("Corporations", "42".to_string(), true),
+                ]
+        );
+        assert!(alliance_fields(&alliance(), None, 0)[1] == ("Executor", "None".to_string(), true));
+    }
+
+    #[traced_test]
+    #[test]
+    fn disambiguation_lists_top_matches() {
+        let matches: Vec<_> = (1..=7)
+            .map(|id| EntityMatch {
+                kind: EntityKind::Corporation,
+                id,
+                name: format!("Corp {}", id),
+            })
+            .collect();
+
+        let content = disambiguation("CORP", &matches);
+        let lines: Vec<_> = content.lines().collect();
+
+        assert!(lines.len() == 7);
+        assert!(lines[1] == "- Corporation `Corp 1` (1)");
+        assert!(lines[6] == "- and 2 more");
+    }
+}
@@ -0,0 +1,79 @@
+use std::{env, fmt::Debug, str::FromStr};
+
+// Values that don't parse are logged with the default used instead, so a typo doesn't
+// silently change the configuration.
+pub fn env_value<T>(name: &str, default: T) -> T
+where
+    T: FromStr + Debug,
+    T::Err: Debug,
+{
+    parse_value(name, env::var(name).ok().as_deref(), default)
+}
+
+// `1` and `true` in any case enable a flag, `0` and `false` disable it.
+pub fn env_flag(name: &str, default: bool) -> bool {
+    parse_flag(name, env::var(name).ok().as_deref(), default)
+}
+
+fn parse_value<T>(name: &str, value: Option<&str>, default: T) -> T
+where
+    T: FromStr + Debug,
+    T::Err: Debug,
+{
+    let Some(value) = value else {
+        return default;
+    };
+
+    match value.trim().parse() {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            tracing::warn!(name, value, ?err, ?default, "invalid value, using default");
+            default
+        }
+    }
+}
+
+fn parse_flag(name: &str, value: Option<&str>, default: bool) -> bool {
+    match value.map(str::trim) {
+        None => default,
+        Some(value) if value == "1" || value.eq_ignore_ascii_case("true") => true,
+        Some(value) if value == "0" || value.eq_ignore_ascii_case("false") => false,
+        Some(value) => {
+            tracing::warn!(name, value, default, "invalid flag, using default");
+            default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{parse_flag, parse_value};
+
+    #[traced_test]
+    #[test]
+    fn parse_value_falls_back_with_warning() {
+        assert!(parse_value("CORP_BATCH_SIZE", Some(" 5 "), 20) == 5);
+        assert!(parse_value("CORP_BATCH_SIZE", None, 20) == 20);
+        assert!(!logs_contain("invalid value"));
+
+        assert!(parse_value("CORP_BATCH_SIZE", Some("five"), 20) == 20);
+        assert!(logs_contain("name=\"CORP_BATCH_SIZE\""));
+        assert!(logs_contain("default=20"));
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_flag_values() {
+        assert!(parse_flag("DRY_RUN", Some("1"), false));
+        assert!(parse_flag("DRY_RUN", Some("TRUE"), false));
+        assert!(!parse_flag("DRY_RUN", Some("false"), true));
+        assert!(!parse_flag("DRY_RUN", Some("0"), true));
+        assert!(parse_flag("DRY_RUN", None, true));
+        assert!(!logs_contain("invalid flag"));
+
+        assert!(parse_flag("DRY_RUN", Some("yes please"), true));
+        assert!(logs_contain("invalid flag, using default"));
+    }
+}
@@ -1,33 +1,247 @@
-use anyhow::Context;
-use reqwest::{Client, Url};
-use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Debug,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
 
-pub type ApiResult<T> = Result<T, anyhow::Error>;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, DATE, ETAG, EXPIRES, IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+    Client, Request, Response, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use tokio::{
+    fs,
+    sync::{RwLock, Semaphore},
+};
+
+use crate::{health::unix_now, metrics};
+
+pub type ApiResult<T> = Result<T, EsiError>;
 
 pub type EsiID = u64;
 
 #[derive(Debug, Clone)]
 pub struct Esi {
-    client: Client,
+    client: Arc<Client>,
+    base_url: Url,
+    pub retry_attempts: u32,
+    pub retry_base_delay: Duration,
+    error_limit: Arc<Mutex<ErrorLimit>>,
+    response_cache: Arc<RwLock<HashMap<String, CachedResponse>>>,
+    response_cache_capacity: usize,
+    default_cache_ttl: Duration,
+    concurrency: Arc<Semaphore>,
+    slow_request_threshold: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: Option<String>,
+    value: serde_json::Value,
+    headers: HeaderMap,
+    expires_at: Instant,
+}
+
+// `Instant` can't be stored, so persisted responses record the unix time they expire at.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedResponse {
+    etag: Option<String>,
+    value: serde_json::Value,
+    headers: Vec<(String, String)>,
+    expires_at: u64,
+}
+
+impl PersistedResponse {
+    fn new(cached: &CachedResponse, now: Instant, unix_now: u64) -> Self {
+        PersistedResponse {
+            etag: cached.etag.clone(),
+            value: cached.value.clone(),
+            headers: cached
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            expires_at: unix_now + cached.expires_at.saturating_duration_since(now).as_secs(),
+        }
+    }
+
+    // Expired responses are still worth restoring for their ETag.
+    fn restore(self, now: Instant, unix_now: u64) -> Option<CachedResponse> {
+        if self.etag.is_none() && self.expires_at <= unix_now {
+            return None;
+        }
+
+        Some(CachedResponse {
+            etag: self.etag,
+            value: self.value,
+            headers: self
+                .headers
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    Some((
+                        HeaderName::from_bytes(name.as_bytes()).ok()?,
+                        HeaderValue::from_str(&value).ok()?,
+                    ))
+                })
+                .collect(),
+            expires_at: now + Duration::from_secs(self.expires_at.saturating_sub(unix_now)),
+        })
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct UniverseName {
+    id: EsiID,
+    name: String,
 }
 
 #[derive(Deserialize, Clone, Debug)]
+struct UniverseIds {
+    #[serde(default)]
+    alliances: Vec<UniverseName>,
+    #[serde(default)]
+    corporations: Vec<UniverseName>,
+    #[serde(default)]
+    systems: Vec<UniverseName>,
+}
+
+#[derive(Debug)]
+pub enum EsiError {
+    NotFound,
+    RateLimited,
+    Server(u16),
+    Status(u16),
+    Timeout,
+    Decode(serde_json::Error),
+    Transport(reqwest::Error),
+    Url(url::ParseError),
+}
+
+impl std::fmt::Display for EsiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EsiError::NotFound => write!(f, "esi resource not found"),
+            EsiError::RateLimited => write!(f, "esi rate limit exceeded"),
+            EsiError::Server(status) => write!(f, "esi server error ({})", status),
+            EsiError::Status(status) => write!(f, "unexpected esi response status ({})", status),
+            EsiError::Timeout => write!(f, "esi request timed out"),
+            EsiError::Decode(_) => write!(f, "couldn't decode esi response"),
+            EsiError::Transport(_) => write!(f, "esi request failed"),
+            EsiError::Url(_) => write!(f, "invalid esi url"),
+        }
+    }
+}
+
+impl std::error::Error for EsiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EsiError::Decode(err) => Some(err),
+            EsiError::Transport(err) => Some(err),
+            EsiError::Url(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for EsiError {
+    fn from(err: serde_json::Error) -> Self {
+        EsiError::Decode(err)
+    }
+}
+
+impl From<reqwest::Error> for EsiError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            EsiError::Timeout
+        } else {
+            EsiError::Transport(err)
+        }
+    }
+}
+
+impl From<url::ParseError> for EsiError {
+    fn from(err: url::ParseError) -> Self {
+        EsiError::Url(err)
+    }
+}
+
+impl EsiError {
+    fn from_status(status: StatusCode) -> Option<Self> {
+        match status.as_u16() {
+            _ if status.is_success() || status == StatusCode::NOT_MODIFIED => None,
+            404 => Some(EsiError::NotFound),
+            420 | 429 => Some(EsiError::RateLimited),
+            status @ 500..=599 => Some(EsiError::Server(status)),
+            status => Some(EsiError::Status(status)),
+        }
+    }
+
+    fn is_transient(&self) -> bool {
+        match self {
+            EsiError::Server(_) | EsiError::Timeout => true,
+            EsiError::Transport(err) => err.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ErrorLimit {
+    remain: i32,
+    reset_at: Instant,
+}
+
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(timestamp).map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DateTime<Utc>, D::Error> {
+    let timestamp = String::deserialize(deserializer)?;
+
+    parse_timestamp(&timestamp).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_optional_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|timestamp| parse_timestamp(&timestamp).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Alliance {
     pub creator_corporation_id: EsiID,
     pub creator_id: EsiID,
-    pub date_founded: String,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    pub date_founded: DateTime<Utc>,
     pub executor_corporation_id: Option<EsiID>,
     pub faction_id: Option<EsiID>,
     pub name: String,
     pub ticker: String,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Corporation {
     pub alliance_id: Option<EsiID>,
     pub ceo_id: EsiID,
     pub creator_id: EsiID,
-    pub date_founded: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    pub date_founded: Option<DateTime<Utc>>,
     pub description: Option<String>,
     pub faction_id: Option<EsiID>,
     pub home_station_id: Option<EsiID>,
@@ -40,6 +254,28 @@ pub struct Corporation {
     pub war_eligible: Option<bool>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntityKind {
+    Alliance,
+    Corporation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityMatch {
+    pub kind: EntityKind,
+    pub id: EsiID,
+    pub name: String,
+}
+
+impl std::fmt::Display for EntityMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            EntityKind::Alliance => write!(f, "Alliance `{}` ({})", self.name, self.id),
+            EntityKind::Corporation => write!(f, "Corporation `{}` ({})", self.name, self.id),
+        }
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct SovereigntyStructure {
     pub alliance_id: EsiID,
@@ -47,11 +283,13 @@ pub struct SovereigntyStructure {
     pub structure_id: EsiID,
     pub structure_type_id: EsiID,
     pub vulnerability_occupancy_level: Option<f32>,
-    pub vulnerable_end_time: Option<String>,
-    pub vulnerable_start_time: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    pub vulnerable_end_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    pub vulnerable_start_time: Option<DateTime<Utc>>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct System {
     pub system_id: EsiID,
     pub constellation_id: EsiID,
@@ -59,118 +297,1804 @@ pub struct System {
     pub security_status: f32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecurityClass {
+    High,
+    Low,
+    Null,
+}
+
+impl SecurityClass {
+    pub fn from_security_status(security_status: f32) -> SecurityClass {
+        if security_status < 0.0 {
+            SecurityClass::Null
+        } else if security_status < 0.5 {
+            SecurityClass::Low
+        } else {
+            SecurityClass::High
+        }
+    }
+}
+
+impl FromStr for SecurityClass {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<SecurityClass> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "high" | "highsec" => Ok(SecurityClass::High),
+            "low" | "lowsec" => Ok(SecurityClass::Low),
+            "null" | "nullsec" => Ok(SecurityClass::Null),
+            _ => anyhow::bail!("`{}` is not one of high, low or null", value),
+        }
+    }
+}
+
+impl System {
+    pub fn security_class(&self) -> SecurityClass {
+        SecurityClass::from_security_status(self.security_status)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Clone, Debug)]
+pub struct Constellation {
+    pub constellation_id: EsiID,
+    pub name: String,
+    pub region_id: EsiID,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Clone, Debug)]
+pub struct Region {
+    pub region_id: EsiID,
+    pub name: String,
+}
+
 const BASE_URI: &str = "https://esi.evetech.net/latest/";
 
-fn create_endpoint_url(path: &str) -> ApiResult<Url> {
-    let base_url = Url::parse(BASE_URI)?;
-    let mut url = base_url.join(path)?;
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+const DEFAULT_MAX_CONCURRENCY: usize = 20;
+
+const DEFAULT_SLOW_REQUEST_THRESHOLD: Duration = Duration::from_secs(5);
+
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+const DEFAULT_RESPONSE_CACHE_CAPACITY: usize = 20_000;
+
+const POOL_MAX_IDLE_PER_HOST: usize = 20;
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+static SHARED_ESI: OnceLock<Esi> = OnceLock::new();
 
-    url.query_pairs_mut()
-        .append_pair("datasource", "tranquility");
+const NAMES_CHUNK_SIZE: usize = 1000;
 
-    Ok(url)
+const PAGES_HEADER: &str = "X-Pages";
+const ERROR_LIMIT_REMAIN_HEADER: &str = "X-ESI-Error-Limit-Remain";
+const ERROR_LIMIT_RESET_HEADER: &str = "X-ESI-Error-Limit-Reset";
+const ERROR_LIMIT_THRESHOLD: i32 = 10;
+const ERROR_LIMIT_DEFAULT_REMAIN: i32 = 100;
+
+// Endpoint paths are joined onto the base, which drops its last path segment unless it
+// ends with a slash.
+fn parse_base_url(base_url: &str) -> Result<Url, url::ParseError> {
+    let base_url = base_url.trim();
+
+    if base_url.ends_with('/') {
+        Url::parse(base_url)
+    } else {
+        Url::parse(&format!("{}/", base_url))
+    }
 }
 
 impl Esi {
     pub fn new() -> Self {
+        let timeout = env::var("ESI_TIMEOUT_SECS")
+            .ok()
+            .and_then(|timeout| timeout.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+
+        let max_concurrency = env::var("ESI_MAX_CONCURRENCY")
+            .ok()
+            .and_then(|max_concurrency| max_concurrency.parse().ok())
+            .filter(|max_concurrency| *max_concurrency > 0)
+            .unwrap_or(DEFAULT_MAX_CONCURRENCY);
+
+        let slow_request_threshold = env::var("ESI_SLOW_REQUEST_MS")
+            .ok()
+            .and_then(|slow_request_ms| slow_request_ms.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_SLOW_REQUEST_THRESHOLD);
+
+        let esi = Esi::with_timeout(timeout)
+            .with_max_concurrency(max_concurrency)
+            .with_slow_request_threshold(slow_request_threshold);
+
+        match env::var("ESI_BASE_URL") {
+            Ok(base_url) => match parse_base_url(&base_url) {
+                Ok(base_url) => {
+                    tracing::info!(%base_url, "using custom esi base url");
+                    esi.with_base_url(base_url)
+                }
+                Err(err) => {
+                    tracing::warn!(?err, base_url, "invalid ESI_BASE_URL, using default");
+                    esi
+                }
+            },
+            Err(_) => esi,
+        }
+    }
+
+    /// Clones of the returned client share one connection pool, error budget and response cache.
+    pub fn shared() -> Self {
+        SHARED_ESI.get_or_init(Esi::new).clone()
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let client = Client::builder()
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT.min(timeout))
+            .timeout(timeout)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .build()
+            .expect("build http client");
+
         Esi {
-            client: Client::new(),
+            client: Arc::new(client),
+            base_url: Url::parse(BASE_URI).expect("valid base url"),
+            retry_attempts: DEFAULT_RETRY_ATTEMPTS,
+            retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+            error_limit: Arc::new(Mutex::new(ErrorLimit {
+                remain: ERROR_LIMIT_DEFAULT_REMAIN,
+                reset_at: Instant::now(),
+            })),
+            response_cache: Default::default(),
+            response_cache_capacity: DEFAULT_RESPONSE_CACHE_CAPACITY,
+            default_cache_ttl: DEFAULT_CACHE_TTL,
+            concurrency: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENCY)),
+            slow_request_threshold: DEFAULT_SLOW_REQUEST_THRESHOLD,
         }
     }
 
-    pub async fn get_alliance_ids(&self) -> ApiResult<Vec<EsiID>> {
-        let url = create_endpoint_url("alliances/").context("create url")?;
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.concurrency = Arc::new(Semaphore::new(max_concurrency));
+        self
+    }
 
-        tracing::debug!(?url, "fetch alliances");
+    pub fn with_slow_request_threshold(mut self, slow_request_threshold: Duration) -> Self {
+        self.slow_request_threshold = slow_request_threshold;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_default_cache_ttl(mut self, default_cache_ttl: Duration) -> Self {
+        self.default_cache_ttl = default_cache_ttl;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_response_cache_capacity(mut self, response_cache_capacity: usize) -> Self {
+        self.response_cache_capacity = response_cache_capacity;
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    // ESI redirects resources without a trailing slash, and the query string may be lost
+    // when the redirect is followed.
+    fn create_endpoint_url(&self, path: &str) -> ApiResult<Url> {
+        debug_assert!(path.ends_with('/'), "`{}` has no trailing slash", path);
+
+        let mut url = self.base_url.join(path)?;
+
+        url.query_pairs_mut()
+            .append_pair("datasource", "tranquility");
+
+        Ok(url)
+    }
+
+    pub fn error_budget_remaining(&self) -> i32 {
+        self.error_limit.lock().unwrap().remain
+    }
+
+    fn update_error_limit(&self, headers: &HeaderMap) {
+        let header_value = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<i32>().ok())
+        };
+
+        if let (Some(remain), Some(reset)) = (
+            header_value(ERROR_LIMIT_REMAIN_HEADER),
+            header_value(ERROR_LIMIT_RESET_HEADER),
+        ) {
+            let mut error_limit = self.error_limit.lock().unwrap();
+
+            error_limit.remain = remain;
+            error_limit.reset_at = Instant::now() + Duration::from_secs(reset.max(0) as u64);
+        }
+    }
+
+    async fn wait_for_error_budget(&self) {
+        let wait = {
+            let error_limit = self.error_limit.lock().unwrap();
+
+            if error_limit.remain < ERROR_LIMIT_THRESHOLD {
+                error_limit.reset_at.checked_duration_since(Instant::now())
+            } else {
+                None
+            }
+        };
+
+        if let Some(wait) = wait {
+            tracing::warn!(
+                remain = self.error_budget_remaining(),
+                wait_ms = wait.as_millis() as u64,
+                "esi error budget low, pausing requests"
+            );
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn record_latency(
+        &self,
+        endpoint: &str,
+        attempt: u32,
+        response: &reqwest::Result<Response>,
+        elapsed: Duration,
+    ) {
+        metrics::record_esi_latency(endpoint, elapsed);
+
+        let status = response
+            .as_ref()
+            .map(|response| response.status().as_u16())
+            .ok();
+        let elapsed_ms = elapsed.as_millis() as u64;
+
+        if elapsed >= self.slow_request_threshold {
+            tracing::warn!(endpoint, attempt, status, elapsed_ms, "slow esi request");
+        } else {
+            tracing::debug!(endpoint, attempt, status, elapsed_ms, "esi request");
+        }
+    }
+
+    async fn send_request(&self, request: Request) -> ApiResult<Response> {
+        let url = request.url().clone();
+        let endpoint = metrics::endpoint_label(url.path());
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            self.wait_for_error_budget().await;
 
-        let response = self.client.get(url).send().await.context("/alliances/")?;
+            let attempt_request = request.try_clone().expect("request body is not a stream");
+            metrics::record_esi_request(&endpoint);
+            let response = {
+                let _permit = self
+                    .concurrency
+                    .acquire()
+                    .await
+                    .expect("esi semaphore is never closed");
 
-        let alliance_ids = response
-            .json::<Vec<EsiID>>()
+                let started = Instant::now();
+                let response = self.client.execute(attempt_request).await;
+
+                self.record_latency(&endpoint, attempt, &response, started.elapsed());
+
+                response
+            };
+
+            if let Ok(response) = &response {
+                self.update_error_limit(response.headers());
+            }
+
+            let error = match response {
+                Ok(response) => match EsiError::from_status(response.status()) {
+                    Some(error) => error,
+                    None => return Ok(response),
+                },
+                Err(err) => EsiError::from(err),
+            };
+
+            metrics::record_esi_error();
+
+            if !error.is_transient() {
+                return Err(error);
+            }
+
+            if attempt >= self.retry_attempts {
+                tracing::error!(%url, attempt, ?error, "request failed, giving up");
+
+                return Err(error);
+            }
+
+            let delay = self.retry_base_delay * 2u32.pow(attempt - 1);
+
+            tracing::warn!(
+                %url,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                ?error,
+                "request failed, retrying"
+            );
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    pub async fn save_cache(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let unix_now = unix_now();
+
+        let responses: HashMap<_, _> = self
+            .response_cache
+            .read()
             .await
-            .context("parse /alliances/ response")?;
+            .iter()
+            .map(|(cache_key, cached)| {
+                (
+                    cache_key.clone(),
+                    PersistedResponse::new(cached, now, unix_now),
+                )
+            })
+            .collect();
+
+        fs::write(path, serde_json::to_string(&responses)?).await?;
+
+        Ok(())
+    }
+
+    pub async fn load_cache(&self, path: impl AsRef<Path>) -> anyhow::Result<usize> {
+        let Ok(json_data) = fs::read_to_string(path).await else {
+            return Ok(0);
+        };
+
+        let responses: HashMap<String, PersistedResponse> = serde_json::from_str(&json_data)?;
+        let now = Instant::now();
+        let unix_now = unix_now();
+
+        let mut restored = 0;
+
+        for (cache_key, response) in responses {
+            if let Some(cached) = response.restore(now, unix_now) {
+                self.cache_response(cache_key, cached).await;
+                restored += 1;
+            }
+        }
+
+        Ok(restored)
+    }
+
+    // Expired responses are kept for their ETag until the cache is full, then they're
+    // dropped first, followed by the one closest to expiring.
+    async fn cache_response(&self, cache_key: String, response: CachedResponse) {
+        let mut cache = self.response_cache.write().await;
+
+        if cache.len() >= self.response_cache_capacity && !cache.contains_key(&cache_key) {
+            let now = Instant::now();
+            cache.retain(|_, cached| cached.expires_at > now);
+
+            if cache.len() >= self.response_cache_capacity {
+                let oldest = cache
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.expires_at)
+                    .map(|(cache_key, _)| cache_key.clone());
+
+                if let Some(oldest) = oldest {
+                    cache.remove(&oldest);
+                }
+            }
+
+            tracing::debug!(entries = cache.len(), "pruned esi response cache");
+        }
+
+        cache.insert(cache_key, response);
+    }
+
+    async fn get_value(&self, url: Url) -> ApiResult<(serde_json::Value, HeaderMap)> {
+        let cache_key = url.to_string();
+        let cached = self.response_cache.read().await.get(&cache_key).cloned();
+
+        if let Some(cached) = &cached {
+            if cached.expires_at > Instant::now() {
+                tracing::debug!(cache_key, "using unexpired cached response");
+
+                return Ok((cached.value.clone(), cached.headers.clone()));
+            }
+        }
+
+        let mut request = self.client.get(url.clone());
+
+        if let Some(etag) = cached.as_ref().and_then(|cached| cached.etag.as_ref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        let mut response = self.send_request(request.build()?).await?;
+
+        // Without a cached body there's nothing a 304 could refer to, so the resource is
+        // requested once more without the condition.
+        if response.status() == StatusCode::NOT_MODIFIED && cached.is_none() {
+            tracing::warn!(
+                cache_key,
+                "not modified without a cached response, retrying"
+            );
+
+            response = self.send_request(self.client.get(url).build()?).await?;
+
+            if response.status() == StatusCode::NOT_MODIFIED {
+                return Err(EsiError::Status(StatusCode::NOT_MODIFIED.as_u16()));
+            }
+        }
+
+        let headers = response.headers().clone();
+        let expires_at =
+            Instant::now() + cache_ttl(&headers, Utc::now()).unwrap_or(self.default_cache_ttl);
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                tracing::debug!(cache_key, "not modified, using cached response");
+
+                // A 304 only has to repeat the caching headers, anything else like `X-Pages`
+                // comes from the cached response.
+                let mut merged_headers = cached.headers.clone();
+                for name in [CACHE_CONTROL, EXPIRES, ETAG, LAST_MODIFIED] {
+                    if let Some(value) = headers.get(&name) {
+                        merged_headers.insert(name, value.clone());
+                    }
+                }
+
+                self.cache_response(
+                    cache_key,
+                    CachedResponse {
+                        etag: merged_headers
+                            .get(ETAG)
+                            .and_then(|etag| etag.to_str().ok())
+                            .map(str::to_string),
+                        headers: merged_headers.clone(),
+                        expires_at,
+                        ..cached.clone()
+                    },
+                )
+                .await;
+
+                return Ok((cached.value, merged_headers));
+            }
+        }
+
+        let body = response.bytes().await?;
+
+        tracing::debug!(cache_key, bytes = body.len(), "parse response body");
+
+        let value = serde_json::from_slice::<serde_json::Value>(&body)?;
+
+        self.cache_response(
+            cache_key,
+            CachedResponse {
+                etag: headers
+                    .get(ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(str::to_string),
+                value: value.clone(),
+                headers: headers.clone(),
+                expires_at,
+            },
+        )
+        .await;
+
+        Ok((value, headers))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: Url) -> ApiResult<T> {
+        let (value, _) = self.get_value(url).await?;
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    async fn post_json<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        url: Url,
+        body: &B,
+    ) -> ApiResult<T> {
+        let request = self.client.post(url).json(body).build()?;
+        let response = self.send_request(request).await?;
+
+        let body = response.bytes().await?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    async fn get_paginated_json<T: DeserializeOwned>(&self, url: Url) -> ApiResult<Vec<T>> {
+        let (value, headers) = self.get_value(url.clone()).await?;
+
+        let pages = match headers
+            .get(PAGES_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u32>().ok())
+        {
+            Some(pages) => pages,
+            None => {
+                tracing::warn!(%url, "response has no page count, assuming a single page");
+                1
+            }
+        };
+
+        let mut items = serde_json::from_value::<Vec<T>>(value)?;
+
+        if pages > 1 {
+            tracing::debug!(%url, pages, "fetch remaining pages");
+
+            let remaining_pages = try_join_all((2..=pages).map(|page| {
+                let mut page_url = url.clone();
+                page_url
+                    .query_pairs_mut()
+                    .append_pair("page", &page.to_string());
+
+                self.get_json::<Vec<T>>(page_url)
+            }))
+            .await?;
+
+            for page_items in remaining_pages {
+                items.extend(page_items);
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+// Only `corporations/{id}/` itself, `alliances/{id}/corporations/` lists ids.
+fn cached_corporation_id(cache_key: &str) -> Option<EsiID> {
+    let url = Url::parse(cache_key).ok()?;
+    let segments: Vec<_> = url
+        .path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    match segments.as_slice() {
+        [.., "corporations", corporation_id] => corporation_id.parse().ok(),
+        _ => None,
+    }
+}
+
+fn cache_ttl(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let header_value = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+    if let Some(cache_control) = header_value(CACHE_CONTROL) {
+        let directives: Vec<_> = cache_control
+            .split(',')
+            .map(|directive| directive.trim().to_ascii_lowercase())
+            .collect();
+
+        if directives
+            .iter()
+            .any(|directive| directive == "no-cache" || directive == "no-store")
+        {
+            return Some(Duration::ZERO);
+        }
+
+        if let Some(max_age) = directives.iter().find_map(|directive| {
+            directive
+                .strip_prefix("max-age=")
+                .and_then(|max_age| max_age.parse::<u64>().ok())
+        }) {
+            return Some(Duration::from_secs(max_age));
+        }
+    }
+
+    let expires = DateTime::parse_from_rfc2822(header_value(EXPIRES)?).ok()?;
+    let date = header_value(DATE)
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+        .unwrap_or(now);
+
+    Some(
+        (expires.with_timezone(&Utc) - date)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[async_trait]
+pub trait EsiClient: Debug + Send + Sync {
+    async fn get_alliance_ids(&self) -> ApiResult<Vec<EsiID>>;
+    async fn get_alliance(&self, alliance_id: EsiID) -> ApiResult<Alliance>;
+    async fn get_alliance_corporations(&self, alliance_id: EsiID) -> ApiResult<Vec<EsiID>>;
+    async fn get_corporation(&self, corporation_id: EsiID) -> ApiResult<Corporation>;
+    // Corporations already in the response cache, so their tickers can be indexed without
+    // any requests.
+    async fn cached_corporations(&self) -> Vec<(EsiID, Corporation)>;
+    async fn get_system(&self, system_id: EsiID) -> ApiResult<System>;
+    async fn get_constellation(&self, constellation_id: EsiID) -> ApiResult<Constellation>;
+    async fn get_region(&self, region_id: EsiID) -> ApiResult<Region>;
+    async fn resolve_names(&self, ids: &[EsiID]) -> ApiResult<HashMap<EsiID, String>>;
+    async fn get_alliance_names(&self, alliance_ids: &[EsiID])
+        -> ApiResult<HashMap<EsiID, String>>;
+    async fn search_system_id(&self, name: &str) -> ApiResult<Option<EsiID>>;
+    async fn search_entities(&self, name: &str) -> ApiResult<Vec<EntityMatch>>;
+    async fn get_sovereignty_structures(&self) -> ApiResult<Vec<SovereigntyStructure>>;
+}
+
+#[async_trait]
+impl EsiClient for Esi {
+    async fn get_alliance_ids(&self) -> ApiResult<Vec<EsiID>> {
+        let url = self.create_endpoint_url("alliances/")?;
+
+        tracing::debug!(?url, "fetch alliances");
+
+        let alliance_ids = self.get_paginated_json::<EsiID>(url).await?;
 
         tracing::debug!(?alliance_ids, "response");
 
         Ok(alliance_ids)
     }
 
-    pub async fn get_alliance(&self, alliance_id: EsiID) -> ApiResult<Alliance> {
+    async fn get_alliance(&self, alliance_id: EsiID) -> ApiResult<Alliance> {
         let resource = format!("alliances/{}/", alliance_id);
-        let url = create_endpoint_url(&resource).context("create url")?;
+        let url = self.create_endpoint_url(&resource)?;
 
         tracing::debug!(?url, "fetch alliance");
 
-        let response = self.client.get(url).send().await.context("fetch alliance")?;
-        let alliance = response.json::<Alliance>().await.context("parse alliance")?;
+        let alliance = self.get_json::<Alliance>(url).await?;
 
         tracing::debug!(?alliance, "response");
 
         Ok(alliance)
     }
 
-    pub async fn get_alliance_corporations(&self, alliance_id: EsiID) -> ApiResult<Vec<EsiID>> {
+    async fn get_alliance_corporations(&self, alliance_id: EsiID) -> ApiResult<Vec<EsiID>> {
         let resource = format!("alliances/{}/corporations/", alliance_id);
-        let url = create_endpoint_url(&resource).context("create url")?;
+        let url = self.create_endpoint_url(&resource)?;
 
         tracing::debug!(?url, "fetch alliance corporations");
 
-        let response = self.client.get(url).send().await.context("fetch alliance corporations")?;
-        let corporations = response.json::<Vec<EsiID>>().await.context("parse alliance corporations")?;
+        let corporations = self.get_json::<Vec<EsiID>>(url).await?;
 
         tracing::debug!(?corporations, "response");
 
         Ok(corporations)
     }
 
-    pub async fn get_corporation(&self, corporation_id: EsiID) -> ApiResult<Corporation> {
-        let resource = format!("corporations/{}", corporation_id);
-        let url = create_endpoint_url(&resource).context("create url")?;
+    async fn get_corporation(&self, corporation_id: EsiID) -> ApiResult<Corporation> {
+        let resource = format!("corporations/{}/", corporation_id);
+        let url = self.create_endpoint_url(&resource)?;
 
         tracing::debug!(?url, "fetch corporation");
 
-        let response = self.client.get(url).send().await.context("fetch corporation")?;
-        let corporation = response.json::<Corporation>().await.context("parse corporation")?;
+        let corporation = self.get_json::<Corporation>(url).await?;
 
         tracing::debug!(?corporation, "response");
 
         Ok(corporation)
     }
 
-    pub async fn get_system(&self, system_id: EsiID) -> ApiResult<System> {
-        let resource = format!("universe/systems/{}", system_id);
-        let url = create_endpoint_url(&resource).context("create url")?;
+    async fn cached_corporations(&self) -> Vec<(EsiID, Corporation)> {
+        self.response_cache
+            .read()
+            .await
+            .iter()
+            .filter_map(|(cache_key, cached)| {
+                let corporation_id = cached_corporation_id(cache_key)?;
+                let corporation = serde_json::from_value(cached.value.clone()).ok()?;
+
+                Some((corporation_id, corporation))
+            })
+            .collect()
+    }
+
+    async fn get_system(&self, system_id: EsiID) -> ApiResult<System> {
+        let resource = format!("universe/systems/{}/", system_id);
+        let url = self.create_endpoint_url(&resource)?;
 
         tracing::debug!(?url, "fetch system");
 
-        let response = self.client.get(url).send().await.context("fetch system")?;
-        let system = response.json::<System>().await.context("parse system")?;
+        let system = self.get_json::<System>(url).await?;
 
         tracing::debug!(?system, "response");
 
         Ok(system)
     }
 
-    pub async fn get_sovereignty_structures(&self) -> ApiResult<Vec<SovereigntyStructure>> {
-        let resource = "sovereignty/structures/";
-        let url = create_endpoint_url(&resource).context("create url")?;
+    async fn get_constellation(&self, constellation_id: EsiID) -> ApiResult<Constellation> {
+        let resource = format!("universe/constellations/{}/", constellation_id);
+        let url = self.create_endpoint_url(&resource)?;
 
-        tracing::debug!(?url, "fetch sovereignty structures");
+        tracing::debug!(?url, "fetch constellation");
 
-        let response = self.client.get(url).send().await.context("fetch sovereignty structures")?;
-        let sovereignty_structures = response.json::<Vec<SovereigntyStructure>>().await.context("parse sovereignty structures")?;
+        let constellation = self.get_json::<Constellation>(url).await?;
 
-        tracing::debug!(structure_count=sovereignty_structures.len(), "response");
+        tracing::debug!(?constellation, "response");
 
-        Ok(sovereignty_structures)
+        Ok(constellation)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use tracing_test::traced_test;
+    async fn get_region(&self, region_id: EsiID) -> ApiResult<Region> {
+        let resource = format!("universe/regions/{}/", region_id);
+        let url = self.create_endpoint_url(&resource)?;
+
+        tracing::debug!(?url, "fetch region");
+
+        let region = self.get_json::<Region>(url).await?;
+
+        tracing::debug!(?region, "response");
+
+        Ok(region)
+    }
+
+    async fn resolve_names(&self, ids: &[EsiID]) -> ApiResult<HashMap<EsiID, String>> {
+        let url = self.create_endpoint_url("universe/names/")?;
 
-    use super::Esi;
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+
+        tracing::debug!(?url, id_count = ids.len(), "resolve names");
+
+        let chunks = try_join_all(
+            ids.chunks(NAMES_CHUNK_SIZE)
+                .map(|chunk| self.post_json::<_, Vec<UniverseName>>(url.clone(), chunk)),
+        )
+        .await?;
+
+        let names: HashMap<EsiID, String> = chunks
+            .into_iter()
+            .flatten()
+            .map(|universe_name| (universe_name.id, universe_name.name))
+            .collect();
+
+        tracing::debug!(name_count = names.len(), "response");
+
+        Ok(names)
+    }
+
+    async fn get_alliance_names(
+        &self,
+        alliance_ids: &[EsiID],
+    ) -> ApiResult<HashMap<EsiID, String>> {
+        tracing::debug!(alliance_count = alliance_ids.len(), "fetch alliance names");
+
+        self.resolve_names(alliance_ids).await
+    }
+
+    async fn search_system_id(&self, name: &str) -> ApiResult<Option<EsiID>> {
+        let url = self.create_endpoint_url("universe/ids/")?;
+
+        tracing::debug!(?url, name, "search system id");
+
+        let ids = self
+            .post_json::<_, UniverseIds>(url, &[name.trim()])
+            .await?;
+
+        let system_id = ids.systems.first().map(|system| system.id);
+
+        tracing::debug!(?system_id, "response");
+
+        Ok(system_id)
+    }
+
+    async fn search_entities(&self, name: &str) -> ApiResult<Vec<EntityMatch>> {
+        let url = self.create_endpoint_url("universe/ids/")?;
+
+        tracing::debug!(?url, name, "search entities");
+
+        let ids = self
+            .post_json::<_, UniverseIds>(url, &[name.trim()])
+            .await?;
+
+        let matches: Vec<_> = ids
+            .alliances
+            .into_iter()
+            .map(|alliance| EntityMatch {
+                kind: EntityKind::Alliance,
+                id: alliance.id,
+                name: alliance.name,
+            })
+            .chain(ids.corporations.into_iter().map(|corporation| EntityMatch {
+                kind: EntityKind::Corporation,
+                id: corporation.id,
+                name: corporation.name,
+            }))
+            .collect();
+
+        tracing::debug!(?matches, "response");
+
+        Ok(matches)
+    }
+
+    async fn get_sovereignty_structures(&self) -> ApiResult<Vec<SovereigntyStructure>> {
+        let resource = "sovereignty/structures/";
+        let url = self.create_endpoint_url(resource)?;
+
+        tracing::debug!(?url, "fetch sovereignty structures");
+
+        let sovereignty_structures = self.get_paginated_json::<SovereigntyStructure>(url).await?;
+
+        tracing::debug!(structure_count = sovereignty_structures.len(), "response");
+
+        Ok(sovereignty_structures)
+    }
+}
+
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockEsi {
+    pub alliance_ids: Vec<EsiID>,
+    pub alliance_corporations: HashMap<EsiID, Vec<EsiID>>,
+    pub systems: HashMap<EsiID, System>,
+    pub sovereignty_structures: Vec<SovereigntyStructure>,
+    pub sovereignty_structure_requests: std::sync::atomic::AtomicUsize,
+    pub sovereignty_unavailable: std::sync::atomic::AtomicBool,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl EsiClient for MockEsi {
+    async fn get_alliance_ids(&self) -> ApiResult<Vec<EsiID>> {
+        Ok(self.alliance_ids.clone())
+    }
+
+    async fn get_alliance(&self, _alliance_id: EsiID) -> ApiResult<Alliance> {
+        Err(EsiError::NotFound)
+    }
+
+    async fn get_alliance_corporations(&self, alliance_id: EsiID) -> ApiResult<Vec<EsiID>> {
+        self.alliance_corporations
+            .get(&alliance_id)
+            .cloned()
+            .ok_or(EsiError::NotFound)
+    }
+
+    async fn get_corporation(&self, _corporation_id: EsiID) -> ApiResult<Corporation> {
+        Err(EsiError::NotFound)
+    }
+
+    async fn cached_corporations(&self) -> Vec<(EsiID, Corporation)> {
+        vec![]
+    }
+
+    async fn get_system(&self, system_id: EsiID) -> ApiResult<System> {
+        self.systems
+            .get(&system_id)
+            .cloned()
+            .ok_or(EsiError::NotFound)
+    }
+
+    async fn get_constellation(&self, _constellation_id: EsiID) -> ApiResult<Constellation> {
+        Err(EsiError::NotFound)
+    }
+
+    async fn get_region(&self, _region_id: EsiID) -> ApiResult<Region> {
+        Err(EsiError::NotFound)
+    }
+
+    async fn resolve_names(&self, ids: &[EsiID]) -> ApiResult<HashMap<EsiID, String>> {
+        Ok(ids
+            .iter()
+            .filter_map(|id| {
+                self.systems
+                    .get(id)
+                    .map(|system| (*id, system.name.clone()))
+            })
+            .collect())
+    }
+
+    async fn get_alliance_names(
+        &self,
+        alliance_ids: &[EsiID],
+    ) -> ApiResult<HashMap<EsiID, String>> {
+        self.resolve_names(alliance_ids).await
+    }
+
+    async fn search_system_id(&self, name: &str) -> ApiResult<Option<EsiID>> {
+        Ok(self
+            .systems
+            .values()
+            .find(|system| system.name == name)
+            .map(|system| system.system_id))
+    }
+
+    async fn search_entities(&self, _name: &str) -> ApiResult<Vec<EntityMatch>> {
+        Ok(vec![])
+    }
+
+    async fn get_sovereignty_structures(&self) -> ApiResult<Vec<SovereigntyStructure>> {
+        self.sovereignty_structure_requests
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        if self
+            .sovereignty_unavailable
+            .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            return Err(EsiError::Server(503));
+        }
+
+        Ok(self.sovereignty_structures.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use futures::future::try_join_all;
+    use reqwest::{
+        header::{HeaderMap, HeaderName, HeaderValue},
+        Url,
+    };
+    use tracing_test::traced_test;
+    use wiremock::{
+        matchers::{
+            body_json, header, header_exists, method, path, query_param, query_param_is_missing,
+        },
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{
+        cache_ttl, parse_base_url, Alliance, EntityKind, EntityMatch, Esi, EsiClient, EsiError,
+        EsiID, SecurityClass, SovereigntyStructure,
+    };
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_retries_server_errors() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![1, 2, 3]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut esi = Esi::new();
+        esi.retry_base_delay = Duration::from_millis(1);
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let alliance_ids = esi.get_json::<Vec<EsiID>>(url).await.unwrap();
+
+        assert!(alliance_ids == vec![1, 2, 3]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_gives_up_after_last_attempt() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let mut esi = Esi::new();
+        esi.retry_base_delay = Duration::from_millis(1);
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        assert!(esi.get_json::<Vec<EsiID>>(url).await.is_err());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_serves_unexpired_cached_response() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1, 2])
+                    .insert_header("Cache-Control", "public, max-age=300"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let first = esi.get_json::<Vec<EsiID>>(url.clone()).await.unwrap();
+        let second = esi.get_json::<Vec<EsiID>>(url).await.unwrap();
+
+        assert!(first == vec![1, 2]);
+        assert!(second == first);
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    #[traced_test]
+    #[test]
+    fn cache_ttl_from_max_age() {
+        let now = Utc::now();
+
+        assert!(
+            cache_ttl(&headers(&[("cache-control", "public, max-age=1200")]), now)
+                == Some(Duration::from_secs(1200))
+        );
+        assert!(cache_ttl(&headers(&[("cache-control", "no-cache")]), now) == Some(Duration::ZERO));
+        assert!(cache_ttl(&headers(&[]), now).is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn cache_ttl_from_expires() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 11, 0, 0).unwrap();
+
+        assert!(
+            cache_ttl(
+                &headers(&[
+                    ("date", "Mon, 15 Jan 2024 12:00:00 GMT"),
+                    ("expires", "Mon, 15 Jan 2024 12:05:00 GMT"),
+                ]),
+                now
+            ) == Some(Duration::from_secs(300))
+        );
+        assert!(
+            cache_ttl(
+                &headers(&[("expires", "Mon, 15 Jan 2024 11:00:30 GMT")]),
+                now
+            ) == Some(Duration::from_secs(30))
+        );
+        assert!(
+            cache_ttl(
+                &headers(&[("expires", "Mon, 15 Jan 2024 10:00:00 GMT")]),
+                now
+            ) == Some(Duration::ZERO)
+        );
+        assert!(cache_ttl(&headers(&[("expires", "tomorrow")]), now).is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_pauses_when_error_budget_is_low() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1])
+                    .insert_header("X-ESI-Error-Limit-Remain", "2")
+                    .insert_header("X-ESI-Error-Limit-Reset", "1")
+                    .insert_header("Cache-Control", "max-age=0"),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        esi.get_json::<Vec<EsiID>>(url.clone()).await.unwrap();
+
+        assert!(esi.error_budget_remaining() == 2);
+
+        let started = Instant::now();
+        esi.get_json::<Vec<EsiID>>(url).await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_paginated_json_concatenates_pages() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param_is_missing("page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1, 2])
+                    .insert_header("X-Pages", "3"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        for (page, body) in [("2", vec![3, 4]), ("3", vec![5])] {
+            Mock::given(method("GET"))
+                .and(query_param("page", page))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(body)
+                        .insert_header("X-Pages", "3"),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let alliance_ids = esi.get_paginated_json::<EsiID>(url).await.unwrap();
+
+        assert!(alliance_ids == vec![1, 2, 3, 4, 5]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_reuses_cached_response_when_not_modified() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(header("If-None-Match", "\"abc\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("ETag", "\"abc\""))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1, 2])
+                    .insert_header("ETag", "\"abc\"")
+                    .insert_header("Cache-Control", "max-age=0"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let first = esi.get_json::<Vec<EsiID>>(url.clone()).await.unwrap();
+        let second = esi.get_json::<Vec<EsiID>>(url).await.unwrap();
+
+        assert!(first == vec![1, 2]);
+        assert!(second == first);
+        assert!(logs_contain("not modified, using cached response"));
+        logs_assert(|lines: &[&str]| {
+            match lines
+                .iter()
+                .filter(|line| line.contains("parse response body"))
+                .count()
+            {
+                1 => Ok(()),
+                count => Err(format!("response body parsed {} times", count)),
+            }
+        });
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn response_cache_is_bounded() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1])
+                    .insert_header("Cache-Control", "max-age=300"),
+            )
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1])
+                    .insert_header("Cache-Control", "max-age=0"),
+            )
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new().with_response_cache_capacity(2);
+        let url = |id| Url::parse(&format!("{}/alliances/{}/", server.uri(), id)).unwrap();
+
+        for id in [1, 2, 3, 4] {
+            esi.get_json::<Vec<EsiID>>(url(id)).await.unwrap();
+        }
+
+        let cache = esi.response_cache.read().await;
+
+        assert!(cache.len() == 2);
+        assert!(cache.contains_key(url(4).as_str()));
+        assert!(!cache.contains_key(url(1).as_str()));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn response_cache_round_trips_through_file() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1, 2])
+                    .insert_header("Cache-Control", "max-age=300")
+                    .insert_header("X-Pages", "1"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let path = std::env::temp_dir().join("alliance_squawk_esi_cache_test.json");
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let esi = Esi::new();
+        esi.get_paginated_json::<EsiID>(url.clone()).await.unwrap();
+        esi.save_cache(&path).await.unwrap();
+
+        let restored = Esi::new();
+        let entries = restored.load_cache(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(entries == 1);
+        assert!(restored.get_paginated_json::<EsiID>(url).await.unwrap() == vec![1, 2]);
+        assert!(!logs_contain("response has no page count"));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn load_cache_skips_expired_responses_without_etag() {
+        let path = std::env::temp_dir().join("alliance_squawk_esi_cache_expired_test.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "https://esi.evetech.net/latest/alliances/": {
+                    "etag": null, "value": [1], "headers": [], "expires_at": 0
+                },
+                "https://esi.evetech.net/latest/alliances/1/": {
+                    "etag": "\"abc\"", "value": [1], "headers": [], "expires_at": 0
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let esi = Esi::new();
+        let entries = esi.load_cache(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(entries == 1);
+        assert!(esi
+            .response_cache
+            .read()
+            .await
+            .contains_key("https://esi.evetech.net/latest/alliances/1/"));
+        assert!(esi
+            .load_cache("alliance_squawk_missing_esi_cache.json")
+            .await
+            .is_ok_and(|entries| entries == 0));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_retries_unconditionally_after_unexpected_not_modified() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(304))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(header_exists("If-None-Match"))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![1, 2]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        assert!(esi.get_json::<Vec<EsiID>>(url).await.unwrap() == vec![1, 2]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_paginated_json_keeps_page_count_when_not_modified() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param_is_missing("page"))
+            .and(header("If-None-Match", "\"abc\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("ETag", "\"abc\""))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param_is_missing("page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1, 2])
+                    .insert_header("ETag", "\"abc\"")
+                    .insert_header("Cache-Control", "max-age=0")
+                    .insert_header("X-Pages", "2"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("page", "2"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![3])
+                    .insert_header("Cache-Control", "max-age=0")
+                    .insert_header("X-Pages", "2"),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let first = esi.get_paginated_json::<EsiID>(url.clone()).await.unwrap();
+        let second = esi.get_paginated_json::<EsiID>(url).await.unwrap();
+
+        assert!(first == vec![1, 2, 3]);
+        assert!(second == first);
+        assert!(!logs_contain("response has no page count"));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_paginated_json_warns_without_page_count() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![1, 2]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let alliance_ids = esi.get_paginated_json::<EsiID>(url).await.unwrap();
+
+        assert!(alliance_ids == vec![1, 2]);
+        assert!(logs_contain("response has no page count"));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_times_out_on_slow_response() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1])
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let mut esi = Esi::with_timeout(Duration::from_millis(200));
+        esi.retry_attempts = 1;
+        let url = Url::parse(&format!("{}/alliances/", server.uri())).unwrap();
+
+        let started = Instant::now();
+        let error = esi.get_json::<Vec<EsiID>>(url).await.unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(matches!(error, EsiError::Timeout));
+        assert!(error.to_string() == "esi request timed out");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_request_logs_latency() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![1]))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/corporations/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1])
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new().with_slow_request_threshold(Duration::from_millis(50));
+
+        esi.get_json::<Vec<EsiID>>(Url::parse(&format!("{}/alliances/", server.uri())).unwrap())
+            .await
+            .unwrap();
+
+        assert!(logs_contain("esi request endpoint=\"alliances\""));
+        assert!(logs_contain("status=200"));
+        assert!(logs_contain("elapsed_ms="));
+
+        esi.get_json::<Vec<EsiID>>(Url::parse(&format!("{}/corporations/", server.uri())).unwrap())
+            .await
+            .unwrap();
+
+        assert!(logs_contain("slow esi request endpoint=\"corporations\""));
+    }
+
+    #[traced_test]
+    #[test]
+    fn parses_esi_timestamps() {
+        let alliance: Alliance = serde_json::from_value(serde_json::json!({
+            "creator_corporation_id": 1000169,
+            "creator_id": 90000001,
+            "date_founded": "2010-11-04T13:11:00Z",
+            "executor_corporation_id": 1000169,
+            "name": "Goonswarm Federation",
+            "ticker": "CONDI"
+        }))
+        .unwrap();
+
+        assert!(alliance.date_founded == Utc.with_ymd_and_hms(2010, 11, 4, 13, 11, 0).unwrap());
+
+        let structure: SovereigntyStructure = serde_json::from_value(serde_json::json!({
+            "alliance_id": 1354830081,
+            "solar_system_id": 30004759,
+            "structure_id": 1024573519423_u64,
+            "structure_type_id": 32458,
+            "vulnerability_occupancy_level": 5.0,
+            "vulnerable_end_time": "2024-01-16T02:00:00Z",
+            "vulnerable_start_time": "2024-01-15T22:00:00Z"
+        }))
+        .unwrap();
+
+        assert!(
+            structure.vulnerable_start_time
+                == Some(Utc.with_ymd_and_hms(2024, 1, 15, 22, 0, 0).unwrap())
+        );
+        assert!(
+            structure.vulnerable_end_time.unwrap() - structure.vulnerable_start_time.unwrap()
+                == chrono::Duration::hours(4)
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn parses_missing_and_invalid_timestamps() {
+        let structure: SovereigntyStructure = serde_json::from_value(serde_json::json!({
+            "alliance_id": 1354830081,
+            "solar_system_id": 30004759,
+            "structure_id": 1024573519423_u64,
+            "structure_type_id": 32226
+        }))
+        .unwrap();
+
+        assert!(structure.vulnerable_start_time.is_none());
+        assert!(structure.vulnerable_end_time.is_none());
+
+        let invalid = serde_json::from_value::<SovereigntyStructure>(serde_json::json!({
+            "alliance_id": 1354830081,
+            "solar_system_id": 30004759,
+            "structure_id": 1024573519423_u64,
+            "structure_type_id": 32226,
+            "vulnerable_start_time": "yesterday"
+        }));
+
+        assert!(invalid.is_err());
+    }
+
+    #[traced_test]
+    #[test]
+    fn clones_share_client() {
+        let esi = Esi::new();
+        let clone = esi.clone();
+
+        assert!(Arc::ptr_eq(&esi.client, &clone.client));
+        assert!(Arc::ptr_eq(&Esi::shared().client, &Esi::shared().client));
+        assert!(!Arc::ptr_eq(&esi.client, &Esi::new().client));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn concurrent_requests_are_limited() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![1])
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .expect(6)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new().with_max_concurrency(2);
+
+        let started = Instant::now();
+        try_join_all((0..6).map(|alliance_id| {
+            let url = Url::parse(&format!("{}/alliances/{}/", server.uri(), alliance_id)).unwrap();
+
+            esi.get_json::<Vec<EsiID>>(url)
+        }))
+        .await
+        .unwrap();
+
+        // Six requests two at a time take at least three round trips.
+        assert!(started.elapsed() >= Duration::from_millis(600));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_json_maps_not_found_without_retrying() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let esi = Esi::new();
+        let url = Url::parse(&format!("{}/corporations/1/", server.uri())).unwrap();
+
+        let error = esi.get_json::<Vec<EsiID>>(url).await.unwrap_err();
+
+        assert!(matches!(error, EsiError::NotFound));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn cached_corporations_skip_other_responses() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/corporations/98000001/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "ceo_id": 2112000001_u64,
+                "creator_id": 2112000001_u64,
+                "member_count": 10,
+                "name": "Dreddit",
+                "tax_rate": 0.1,
+                "ticker": "B0RT"
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/99000001/corporations/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![98000001]))
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi = Esi::new().with_base_url(base_url);
+
+        esi.get_corporation(98000001).await.unwrap();
+        esi.get_alliance_corporations(99000001).await.unwrap();
+
+        let corporations = esi.cached_corporations().await;
+
+        assert!(corporations.len() == 1);
+        assert!(corporations[0].0 == 98000001);
+        assert!(corporations[0].1.ticker == "B0RT");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn endpoint_urls_end_with_slash() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi = Esi::new().with_base_url(base_url);
+
+        let _ = esi.get_alliance_ids().await;
+        let _ = esi.get_alliance(1).await;
+        let _ = esi.get_alliance_corporations(1).await;
+        let _ = esi.get_corporation(1).await;
+        let _ = esi.get_system(1).await;
+        let _ = esi.get_constellation(1).await;
+        let _ = esi.get_region(1).await;
+        let _ = esi.get_sovereignty_structures().await;
+
+        let requests = server.received_requests().await.unwrap();
+
+        assert!(requests.len() == 8);
+        assert!(requests
+            .iter()
+            .all(|request| request.url.path().ends_with('/')));
+        assert!(requests
+            .iter()
+            .all(|request| request.url.query() == Some("datasource=tranquility")));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn custom_base_url_keeps_path() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/proxy/latest/alliances/"))
+            .and(query_param("datasource", "tranquility"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![99000001, 99000002]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base_url = parse_base_url(&format!("{}/proxy/latest", server.uri())).unwrap();
+        let esi = Esi::new().with_base_url(base_url);
+
+        let alliance_ids = esi.get_alliance_ids().await.unwrap();
+
+        assert!(alliance_ids == vec![99000001, 99000002]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn security_class_from_status() {
+        assert!(SecurityClass::from_security_status(-1.0) == SecurityClass::Null);
+        assert!(SecurityClass::from_security_status(-0.01) == SecurityClass::Null);
+        assert!(SecurityClass::from_security_status(0.0) == SecurityClass::Low);
+        assert!(SecurityClass::from_security_status(0.3) == SecurityClass::Low);
+        assert!(SecurityClass::from_security_status(0.49) == SecurityClass::Low);
+        assert!(SecurityClass::from_security_status(0.5) == SecurityClass::High);
+        assert!(SecurityClass::from_security_status(1.0) == SecurityClass::High);
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_security_class() {
+        assert!("null".parse::<SecurityClass>().unwrap() == SecurityClass::Null);
+        assert!(" Lowsec ".parse::<SecurityClass>().unwrap() == SecurityClass::Low);
+        assert!("HIGH".parse::<SecurityClass>().unwrap() == SecurityClass::High);
+        assert!("wormhole".parse::<SecurityClass>().is_err());
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_base_url_adds_trailing_slash() {
+        assert!(
+            parse_base_url("http://localhost:8080/latest")
+                .unwrap()
+                .as_str()
+                == "http://localhost:8080/latest/"
+        );
+        assert!(
+            parse_base_url(" http://localhost:8080/latest/ ")
+                .unwrap()
+                .as_str()
+                == "http://localhost:8080/latest/"
+        );
+        assert!(parse_base_url("not a url").is_err());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn resolve_names_posts_ids() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/names/"))
+            .and(body_json(vec![30000142, 30002187]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "category": "solar_system", "id": 30000142, "name": "Jita" },
+                { "category": "solar_system", "id": 30002187, "name": "Amarr" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi = Esi::new().with_base_url(base_url);
+
+        let names = esi
+            .resolve_names(&[30002187, 30000142, 30002187])
+            .await
+            .unwrap();
+
+        assert!(names.len() == 2);
+        assert!(names[&30000142] == "Jita");
+        assert!(names[&30002187] == "Amarr");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn search_system_id() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/ids/"))
+            .and(body_json(vec!["jita"]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "systems": [{ "id": 30000142, "name": "Jita" }]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/ids/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi = Esi::new().with_base_url(base_url);
+
+        assert!(esi.search_system_id(" jita ").await.unwrap() == Some(30000142));
+        assert!(esi.search_system_id("jitaa").await.unwrap().is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn search_entities() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/ids/"))
+            .and(body_json(vec!["Goonswarm Federation"]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "alliances": [{ "id": 1354830081, "name": "Goonswarm Federation" }],
+                "corporations": [{ "id": 98000001, "name": "Goonswarm Federation" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi = Esi::new().with_base_url(base_url);
+
+        assert!(
+            esi.search_entities("Goonswarm Federation").await.unwrap()
+                == vec![
+                    EntityMatch {
+                        kind: EntityKind::Alliance,
+                        id: 1354830081,
+                        name: "Goonswarm Federation".to_string(),
+                    },
+                    EntityMatch {
+                        kind: EntityKind::Corporation,
+                        id: 98000001,
+                        name: "Goonswarm Federation".to_string(),
+                    },
+                ]
+        );
+    }
 
     #[traced_test]
     #[tokio::test]
@@ -0,0 +1,205 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde::Serialize;
+
+use crate::shutdown::{self, ShutdownReceiver};
+
+const GRACE_FACTOR: u64 = 2;
+const HEALTH_PATH: &str = "/healthz";
+
+pub type SharedHealth = Arc<RwLock<HealthState>>;
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct PollHealth {
+    pub interval_seconds: u64,
+    pub last_success: Option<u64>,
+}
+
+impl PollHealth {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval_seconds: interval.as_secs(),
+            last_success: None,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.last_success = Some(unix_now());
+    }
+
+    fn is_healthy(&self, started: u64, now: u64) -> bool {
+        let since = self.last_success.unwrap_or(started);
+
+        now.saturating_sub(since) <= self.interval_seconds * GRACE_FACTOR
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HealthState {
+    pub started: u64,
+    pub adm: PollHealth,
+    pub corporations: PollHealth,
+}
+
+impl HealthState {
+    pub fn shared() -> SharedHealth {
+        Arc::new(RwLock::new(Self {
+            started: unix_now(),
+            adm: PollHealth::default(),
+            corporations: PollHealth::default(),
+        }))
+    }
+
+    pub fn is_healthy(&self, now: u64) -> bool {
+        self.adm.is_healthy(self.started, now) && self.corporations.is_healthy(self.started, now)
+    }
+}
+
+#[derive(Serialize)]
+struct HealthResponse {
+    healthy: bool,
+    #[serde(flatten)]
+    state: HealthState,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn health_response(state: HealthState, now: u64) -> Response<Body> {
+    let healthy = state.is_healthy(now);
+
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let body = serde_json::to_string(&HealthResponse { healthy, state })
+        .expect("health state serializes to json");
+
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn handle(
+    health: SharedHealth,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if request.uri().path() != HEALTH_PATH {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap());
+    }
+
+    let state = *health.read().unwrap();
+
+    Ok(health_response(state, unix_now()))
+}
+
+pub async fn run(
+    health: SharedHealth,
+    port: Option<u16>,
+    mut shutdown: ShutdownReceiver,
+) -> anyhow::Result<()> {
+    let Some(port) = port else {
+        tracing::info!("health endpoint disabled");
+        return Ok(());
+    };
+
+    let address = SocketAddr::from(([0, 0, 0, 0], port));
+
+    tracing::info!(%address, "serving health endpoint");
+
+    let make_service = make_service_fn(move |_connection| {
+        let health = health.clone();
+
+        let service = service_fn(move |request| handle(health.clone(), request));
+
+        async move { Ok::<_, Infallible>(service) }
+    });
+
+    Server::try_bind(&address)?
+        .serve(make_service)
+        .with_graceful_shutdown(async move { shutdown::requested(&mut shutdown).await })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hyper::StatusCode;
+    use tracing_test::traced_test;
+
+    use super::{health_response, HealthState, PollHealth};
+
+    fn state(adm_last_success: Option<u64>, corporations_last_success: Option<u64>) -> HealthState {
+        HealthState {
+            started: 1000,
+            adm: PollHealth {
+                last_success: adm_last_success,
+                ..PollHealth::new(Duration::from_secs(3600))
+            },
+            corporations: PollHealth {
+                last_success: corporations_last_success,
+                ..PollHealth::new(Duration::from_secs(7200))
+            },
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn healthy_within_grace_period() {
+        let state = state(Some(10_000), Some(10_000));
+
+        assert!(state.is_healthy(10_000 + 7200));
+        assert!(!state.is_healthy(10_000 + 7201));
+    }
+
+    #[traced_test]
+    #[test]
+    fn healthy_during_startup_until_first_poll_is_overdue() {
+        let state = state(None, None);
+
+        assert!(state.is_healthy(1000 + 7200));
+        assert!(!state.is_healthy(1000 + 7201));
+    }
+
+    #[traced_test]
+    #[test]
+    fn stalled_corporation_poll_is_unhealthy() {
+        let state = state(Some(20_000), Some(1000));
+
+        assert!(!state.is_healthy(20_000));
+    }
+
+    #[traced_test]
+    #[test]
+    fn response_status_matches_health() {
+        assert!(health_response(state(Some(1000), Some(1000)), 1000).status() == StatusCode::OK);
+        assert!(
+            health_response(state(Some(1000), Some(1000)), 100_000).status()
+                == StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
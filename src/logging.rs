@@ -0,0 +1,48 @@
+use std::env;
+
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_env_value(value: Option<&str>) -> LogFormat {
+        match value.map(str::trim) {
+            Some(format) if format.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Pretty,
+        }
+    }
+}
+
+pub fn init() {
+    let format = LogFormat::from_env_value(env::var("LOG_FORMAT").ok().as_deref());
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match format {
+        LogFormat::Json => builder.json().init(),
+        LogFormat::Pretty => builder.init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::LogFormat;
+
+    #[traced_test]
+    #[test]
+    fn log_format_from_env_value() {
+        assert!(LogFormat::from_env_value(Some("json")) == LogFormat::Json);
+        assert!(LogFormat::from_env_value(Some("JSON")) == LogFormat::Json);
+        assert!(LogFormat::from_env_value(Some("pretty")) == LogFormat::Pretty);
+        assert!(LogFormat::from_env_value(None) == LogFormat::Pretty);
+    }
+}
@@ -1,94 +1,435 @@
-use std::env;
+use std::{collections::HashSet, env, sync::Arc, time::Duration};
 
-use bot::BotNotification;
-use esi::Esi;
+use bot::{BotConfig, BotNotification, BotServices, CorpFilter, DepartureAlert, DiscordWebhook};
+use config::env_flag;
+use esi::{Esi, EsiClient};
+use health::HealthState;
+use rate_limit::MessageRate;
+#[cfg(feature = "adm-history")]
+use services::adm_history::AdmHistoryStore;
 use services::{
-    adm_configuration::AdmConfiguration, adm_notification_service::AdmNotificationService,
-    adm_service::AdmService, corporations_service::CorporationsService,
+    adm_acks::{AdmAcks, DEFAULT_ACK_TTL},
+    adm_configuration::AdmConfiguration,
+    adm_notification_service::AdmNotificationService,
+    adm_service::{AdmService, SovStructureType},
+    channel_config::{ChannelConfig, NotificationChannels},
+    corp_watch::CorpWatchList,
+    corporations_service::{CorporationsConfig, CorporationsService},
+    event_log::EventLog,
     information_service::InformationService,
+    standings::Standings,
+    vulnerability_service::VulnerabilityService,
 };
+use sink::{FileSink, LogSink, NotificationSink};
+use theme::{Branding, Theme};
 
 mod bot;
 mod commands;
+mod config;
 mod esi;
+mod health;
+mod logging;
+mod metrics;
+mod quiet_hours;
+mod rate_limit;
 mod services;
+mod shutdown;
+mod sink;
+mod theme;
 
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
 
-    tracing_subscriber::fmt::init();
+    logging::init();
 
     let alliance_id = env::var("ALLIANCE_ID")
         .expect("`ALLIANCE_ID` configuration variable")
         .parse()
         .expect("`ALLIANCE_ID` is an integer");
 
-    let token = env::var("DISCORD_TOKEN").expect("`DISCORD_TOKEN` configuration variable");
+    let token = env::var("DISCORD_TOKEN").ok();
 
-    let notify_corp_channel_id = env::var("NOTIFY_CORP_CHANNEL_ID")
-        .expect("`NOTIFY_CORP_CHANNEL_ID` configuration variable")
-        .parse()
-        .expect("`NOTIFY_CORP_CHANNEL_ID` is a valid integer");
+    // Without a bot token notifications are posted through a webhook instead, which doesn't
+    // need notification channels.
+    let webhook: Option<DiscordWebhook> = match token {
+        Some(_) => None,
+        None => Some(
+            env::var("DISCORD_WEBHOOK_URL")
+                .expect("`DISCORD_TOKEN` or `DISCORD_WEBHOOK_URL` configuration variable")
+                .parse()
+                .expect("`DISCORD_WEBHOOK_URL` is a discord webhook url"),
+        ),
+    };
 
-    let notify_adm_channel_id = env::var("NOTIFY_ADM_CHANNEL_ID")
-        .expect("`NOTIFY_ADM_CHANNEL_ID` configuration variable")
-        .parse()
-        .expect("`NOTIFY_ADM_CHANNEL_ID` is a valid integer");
+    let notify_channel_id = |name: &str| -> u64 {
+        match env::var(name) {
+            Ok(channel_id) => channel_id
+                .parse()
+                .unwrap_or_else(|_| panic!("`{}` is a valid integer", name)),
+            Err(_) if webhook.is_some() => 0,
+            Err(_) => panic!("`{}` configuration variable", name),
+        }
+    };
+
+    let notify_corp_channel_id = notify_channel_id("NOTIFY_CORP_CHANNEL_ID");
+    let notify_adm_channel_id = notify_channel_id("NOTIFY_ADM_CHANNEL_ID");
+
+    let corp_min_members = env::var("CORP_MIN_MEMBERS")
+        .map(|min_members| {
+            min_members
+                .parse()
+                .expect("`CORP_MIN_MEMBERS` is an integer")
+        })
+        .unwrap_or(10);
+
+    let metrics_port = env::var("METRICS_PORT").ok().map(|port| {
+        port.parse::<u16>()
+            .expect("`METRICS_PORT` is a valid port number")
+    });
+
+    let health_port = env::var("HEALTH_PORT").ok().map(|port| {
+        port.parse::<u16>()
+            .expect("`HEALTH_PORT` is a valid port number")
+    });
+
+    let adm_critical_ping_role_id = env::var("ADM_CRITICAL_PING_ROLE_ID").ok().map(|role_id| {
+        role_id
+            .parse()
+            .expect("`ADM_CRITICAL_PING_ROLE_ID` is a valid integer")
+    });
+
+    let departure_alert = DepartureAlert {
+        alliance_id,
+        min_members: env::var("CORP_DEPARTURE_ALERT_MEMBERS")
+            .ok()
+            .map(|members| {
+                members
+                    .parse()
+                    .expect("`CORP_DEPARTURE_ALERT_MEMBERS` is a valid integer")
+            }),
+        ping_role_id: env::var("CORP_DEPARTURE_PING_ROLE_ID").ok().map(|role_id| {
+            role_id
+                .parse()
+                .expect("`CORP_DEPARTURE_PING_ROLE_ID` is a valid integer")
+        }),
+    };
+
+    let dry_run = env_flag("DRY_RUN", false);
+
+    let corp_filter = CorpFilter {
+        skip_war_ineligible: env_flag("CORP_SKIP_WAR_INELIGIBLE", false),
+        skip_faction: env_flag("CORP_SKIP_FACTION", false),
+        sov_security: env::var("CORP_SOV_SECURITY").ok().map(|sov_security| {
+            sov_security
+                .parse()
+                .expect("`CORP_SOV_SECURITY` is one of high, low or null")
+        }),
+    };
+
+    let message_rate = MessageRate {
+        messages: env::var("NOTIFY_RATE_LIMIT")
+            .map(|messages| messages.parse().expect("`NOTIFY_RATE_LIMIT` is an integer"))
+            .unwrap_or(MessageRate::default().messages),
+        ..Default::default()
+    };
+
+    let quiet_hours = env::var("QUIET_HOURS").ok().map(|quiet_hours| {
+        quiet_hours
+            .parse()
+            .expect("`QUIET_HOURS` is a range of UTC hours like `22-6`")
+    });
+
+    let mut sinks: Vec<Arc<dyn NotificationSink>> = vec![];
+
+    if env_flag("NOTIFICATION_LOG", false) {
+        sinks.push(Arc::new(LogSink));
+    }
+
+    if let Ok(path) = env::var("NOTIFICATION_FILE") {
+        sinks.push(Arc::new(FileSink::new(path)));
+    }
+
+    let theme = Theme::from_env().expect("theme colors are valid hex colors");
+
+    let standings_path =
+        env::var("STANDINGS_PATH").unwrap_or_else(|_| "standings.json".to_string());
+
+    let standings = Standings::load_from(standings_path)
+        .await
+        .expect("loading standings");
+
+    tracing::info!(alliances = standings.len(), "loaded standings");
 
     let (notification_sender, notification_receiver) =
         tokio::sync::mpsc::unbounded_channel::<BotNotification>();
 
-    let esi = Esi::new();
+    let health = HealthState::shared();
+
+    let shared_esi = Esi::shared();
+    let esi: Arc<dyn EsiClient> = Arc::new(shared_esi.clone());
     let information_service = InformationService::new(esi.clone());
 
+    let information_cache_path = env::var("INFORMATION_CACHE_PATH").ok();
+
+    if let Some(path) = &information_cache_path {
+        match shared_esi.load_cache(path).await {
+            Ok(entries) => tracing::info!(entries, path, "loaded esi response cache"),
+            Err(err) => tracing::warn!(?err, path, "couldn't load esi response cache"),
+        }
+    }
+
+    let branding = match (env::var("ALLIANCE_TICKER"), env::var("ALLIANCE_NAME")) {
+        (Ok(ticker), Ok(name)) => Branding {
+            ticker: Some(ticker),
+            name: Some(name),
+        },
+        (ticker, name) => match information_service.get_alliance(alliance_id).await {
+            Ok(alliance) => Branding {
+                ticker: Some(ticker.unwrap_or(alliance.ticker)),
+                name: Some(name.unwrap_or(alliance.name)),
+            },
+            Err(err) => {
+                tracing::warn!(?err, "couldn't resolve alliance branding");
+
+                Branding {
+                    ticker: ticker.ok(),
+                    name: name.ok(),
+                }
+            }
+        },
+    };
+
+    let channels_path =
+        env::var("NOTIFY_CHANNELS_PATH").unwrap_or_else(|_| "channels.json".to_string());
+
+    let default_channels = NotificationChannels {
+        corp: notify_corp_channel_id,
+        adm: notify_adm_channel_id,
+    };
+
+    let channels = match ChannelConfig::load_from(&channels_path, default_channels).await {
+        Ok(channels) => channels,
+        Err(err) => {
+            tracing::warn!(
+                ?err,
+                "couldn't load notification channels, using `NOTIFY_CORP_CHANNEL_ID` and `NOTIFY_ADM_CHANNEL_ID`"
+            );
+
+            ChannelConfig::new(channels_path, default_channels)
+        }
+    };
+
+    let bot_config = BotConfig {
+        channels,
+        corp_min_members,
+        adm_critical_ping_role_id,
+        dry_run,
+        theme,
+        branding,
+        corp_filter,
+        standings,
+        message_rate,
+        quiet_hours,
+        webhook,
+        sinks,
+        departure_alert,
+    };
+
     let adm_configuration = AdmConfiguration::load_configuration()
         .await
         .expect("loading adm configuration");
 
+    let adm_config_watch = env_flag("ADM_CONFIG_WATCH", false);
+
+    let watched_adm_configuration = adm_configuration.clone();
+
     let adm_service = AdmService::new(
         esi.clone(),
         alliance_id,
-        false,
+        HashSet::from([SovStructureType::IHub]),
         information_service.clone(),
         adm_configuration.clone(),
     );
 
-    let mut corporation_service =
-        CorporationsService::new(esi.clone(), notification_sender.clone());
+    let adm_service = match env::var("ADM_DEFAULT_IMPORTANCE") {
+        Ok(default_importance) => adm_service.with_default_importance(
+            default_importance
+                .parse()
+                .expect("`ADM_DEFAULT_IMPORTANCE` is one of `Red`, `Yellow` or `Green`"),
+        ),
+        Err(_) => adm_service,
+    };
+
+    #[cfg(feature = "adm-history")]
+    let adm_history_store = env::var("ADM_HISTORY_DB_PATH")
+        .ok()
+        .map(|path| AdmHistoryStore::open(path).expect("opening adm history database"));
+
+    #[cfg(feature = "adm-history")]
+    let adm_service = match &adm_history_store {
+        Some(adm_history_store) => adm_service.with_history_store(adm_history_store.clone()),
+        None => adm_service,
+    };
+
+    let event_log_capacity = env::var("EVENT_LOG_CAPACITY")
+        .map(|capacity| {
+            capacity
+                .parse()
+                .expect("`EVENT_LOG_CAPACITY` is an integer")
+        })
+        .unwrap_or(100);
+
+    let event_log = EventLog::new(event_log_capacity);
+
+    let adm_ack_ttl = env::var("ADM_ACK_TTL_HOURS")
+        .map(|hours| {
+            Duration::from_secs(
+                hours
+                    .parse::<u64>()
+                    .expect("`ADM_ACK_TTL_HOURS` is an integer")
+                    * 3600,
+            )
+        })
+        .unwrap_or(DEFAULT_ACK_TTL);
+
+    let corp_watch_path =
+        env::var("CORP_WATCH_PATH").unwrap_or_else(|_| "corp_watch.json".to_string());
+
+    let corp_watch = CorpWatchList::load_from(corp_watch_path)
+        .await
+        .expect("loading corp watch list");
+
+    let corporations_state_path = env::var("CORPORATIONS_STATE_PATH")
+        .unwrap_or_else(|_| "corporations.json".to_string())
+        .into();
+
+    let mut corporation_service = CorporationsService::new(
+        esi.clone(),
+        CorporationsConfig::from_env(),
+        notification_sender.clone(),
+        corporations_state_path,
+        corp_watch.clone(),
+        health.clone(),
+    );
+
+    let adm_history_path = env::var("ADM_HISTORY_PATH")
+        .unwrap_or_else(|_| "adm_history.json".to_string())
+        .into();
+
+    let (adm_refresh_sender, adm_refresh_receiver) = tokio::sync::mpsc::unbounded_channel();
+
+    let mut adm_notification_service = AdmNotificationService::new(
+        adm_service.clone(),
+        notification_sender.clone(),
+        adm_history_path,
+        health.clone(),
+    )
+    .with_refresh_requests(adm_refresh_receiver);
+
+    #[cfg(feature = "adm-history")]
+    if let Some(adm_history_store) = adm_history_store {
+        adm_notification_service = adm_notification_service.with_history_store(adm_history_store);
+    }
+
+    let mut vulnerability_service =
+        VulnerabilityService::new(adm_service.clone(), notification_sender.clone());
+
+    let (shutdown_sender, shutdown_receiver) = shutdown::channel();
+
+    tokio::spawn(async move {
+        shutdown::wait_for_signal().await;
 
-    let mut adm_notification_service =
-        AdmNotificationService::new(adm_service.clone(), notification_sender.clone());
+        tracing::info!("shutting down");
+
+        let _ = shutdown_sender.send(true);
+    });
+
+    let bot_shutdown = shutdown_receiver.clone();
+    let adm_shutdown = shutdown_receiver.clone();
+    let corporation_shutdown = shutdown_receiver.clone();
+    let vulnerability_shutdown = shutdown_receiver.clone();
+    let metrics_shutdown = shutdown_receiver.clone();
+    let adm_config_shutdown = shutdown_receiver.clone();
+    let health_shutdown = shutdown_receiver;
 
     let result = tokio::try_join!(
         tokio::spawn(async move {
-            if let Err(why) = bot::run(
-                information_service,
+            let services = BotServices {
+                information: information_service,
                 adm_configuration,
                 adm_service,
-                notification_receiver,
-                token,
-                notify_adm_channel_id,
-                notify_corp_channel_id,
-            )
-            .await
-            {
+                corp_watch,
+                adm_refresh: adm_refresh_sender,
+                event_log,
+                adm_acks: AdmAcks::new(adm_ack_ttl),
+            };
+
+            let result = match token {
+                Some(token) => {
+                    bot::run(
+                        services,
+                        notification_receiver,
+                        token,
+                        bot_config,
+                        bot_shutdown,
+                    )
+                    .await
+                }
+                None => {
+                    bot::run_webhook(services, notification_receiver, bot_config, bot_shutdown)
+                        .await
+                }
+            };
+
+            if let Err(why) = result {
                 tracing::error!(?why, "could not start bot");
             }
         }),
         tokio::spawn(async move {
-            if let Err(why) = adm_notification_service.run().await {
+            if let Err(why) = adm_notification_service.run(adm_shutdown).await {
                 tracing::error!(?why, "adm service stopped");
             }
         }),
         tokio::spawn(async move {
-            if let Err(why) = corporation_service.run().await {
+            if let Err(why) = corporation_service.run(corporation_shutdown).await {
                 tracing::error!(?why, "corporation service stopped");
             }
+        }),
+        tokio::spawn(async move {
+            if let Err(why) = vulnerability_service.run(vulnerability_shutdown).await {
+                tracing::error!(?why, "vulnerability service stopped");
+            }
+        }),
+        tokio::spawn(async move {
+            if !adm_config_watch {
+                return;
+            }
+
+            if let Err(why) = watched_adm_configuration.watch(adm_config_shutdown).await {
+                tracing::error!(?why, "adm configuration watch stopped");
+            }
+        }),
+        tokio::spawn(async move {
+            if let Err(why) = metrics::run(metrics_port, metrics_shutdown).await {
+                tracing::error!(?why, "metrics server stopped");
+            }
+        }),
+        tokio::spawn(async move {
+            if let Err(why) = health::run(health, health_port, health_shutdown).await {
+                tracing::error!(?why, "health server stopped");
+            }
         })
     );
 
     if let Err(why) = result {
         tracing::error!(?why, "exiting with error");
     }
+
+    if let Some(path) = information_cache_path {
+        match shared_esi.save_cache(&path).await {
+            Ok(()) => tracing::info!(path, "saved esi response cache"),
+            Err(err) => tracing::error!(?err, path, "couldn't save esi response cache"),
+        }
+    }
 }
@@ -0,0 +1,256 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::Write,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+
+use crate::shutdown::{self, ShutdownReceiver};
+
+static ESI_REQUESTS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+static ESI_ERRORS: AtomicU64 = AtomicU64::new(0);
+static ADM_SYSTEMS_CRITICAL: AtomicU64 = AtomicU64::new(0);
+static CORP_EVENTS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+static ESI_LATENCY: Mutex<BTreeMap<String, Histogram>> = Mutex::new(BTreeMap::new());
+
+const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, upper_bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if value <= upper_bound {
+                *bucket += 1;
+            }
+        }
+
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+const CONTENT_TYPE_TEXT: &str = "text/plain; version=0.0.4";
+
+pub fn record_esi_request(endpoint: &str) {
+    *ESI_REQUESTS
+        .lock()
+        .unwrap()
+        .entry(endpoint.to_string())
+        .or_default() += 1;
+}
+
+pub fn record_esi_latency(endpoint: &str, elapsed: Duration) {
+    ESI_LATENCY
+        .lock()
+        .unwrap()
+        .entry(endpoint.to_string())
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn record_esi_error() {
+    ESI_ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn set_adm_systems_critical(count: u64) {
+    ADM_SYSTEMS_CRITICAL.store(count, Ordering::Relaxed);
+}
+
+pub fn record_corp_event(kind: &str) {
+    *CORP_EVENTS
+        .lock()
+        .unwrap()
+        .entry(kind.to_string())
+        .or_default() += 1;
+}
+
+pub fn endpoint_label(path: &str) -> String {
+    path.trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            if segment.chars().all(|c| c.is_ascii_digit()) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn render() -> String {
+    let mut output = String::new();
+
+    writeln!(output, "# TYPE esi_requests_total counter").unwrap();
+    for (endpoint, count) in ESI_REQUESTS.lock().unwrap().iter() {
+        writeln!(
+            output,
+            "esi_requests_total{{endpoint=\"{endpoint}\"}} {count}"
+        )
+        .unwrap();
+    }
+
+    writeln!(output, "# TYPE esi_request_duration_seconds histogram").unwrap();
+    for (endpoint, histogram) in ESI_LATENCY.lock().unwrap().iter() {
+        for (count, upper_bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+            writeln!(
+                output,
+                "esi_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",le=\"{upper_bound}\"}} {count}"
+            )
+            .unwrap();
+        }
+        writeln!(
+            output,
+            "esi_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",le=\"+Inf\"}} {}",
+            histogram.count
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "esi_request_duration_seconds_sum{{endpoint=\"{endpoint}\"}} {}",
+            histogram.sum
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "esi_request_duration_seconds_count{{endpoint=\"{endpoint}\"}} {}",
+            histogram.count
+        )
+        .unwrap();
+    }
+
+    writeln!(output, "# TYPE esi_errors_total counter").unwrap();
+    writeln!(
+        output,
+        "esi_errors_total {}",
+        ESI_ERRORS.load(Ordering::Relaxed)
+    )
+    .unwrap();
+
+    writeln!(output, "# TYPE adm_systems_critical gauge").unwrap();
+    writeln!(
+        output,
+        "adm_systems_critical {}",
+        ADM_SYSTEMS_CRITICAL.load(Ordering::Relaxed)
+    )
+    .unwrap();
+
+    writeln!(output, "# TYPE corp_events_total counter").unwrap();
+    for (kind, count) in CORP_EVENTS.lock().unwrap().iter() {
+        writeln!(output, "corp_events_total{{kind=\"{kind}\"}} {count}").unwrap();
+    }
+
+    output
+}
+
+async fn handle(_request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, CONTENT_TYPE_TEXT)
+        .body(Body::from(render()))
+        .unwrap())
+}
+
+pub async fn run(port: Option<u16>, mut shutdown: ShutdownReceiver) -> anyhow::Result<()> {
+    let Some(port) = port else {
+        tracing::info!("metrics disabled");
+        return Ok(());
+    };
+
+    let address = SocketAddr::from(([0, 0, 0, 0], port));
+
+    tracing::info!(%address, "serving metrics");
+
+    let make_service =
+        make_service_fn(|_connection| async { Ok::<_, Infallible>(service_fn(handle)) });
+
+    Server::try_bind(&address)?
+        .serve(make_service)
+        .with_graceful_shutdown(async move { shutdown::requested(&mut shutdown).await })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use std::time::Duration;
+
+    use super::{
+        endpoint_label, record_corp_event, record_esi_latency, record_esi_request, render,
+        Histogram,
+    };
+
+    #[traced_test]
+    #[test]
+    fn endpoint_label_replaces_ids() {
+        assert!(endpoint_label("/latest/corporations/98000001/") == "latest/corporations/:id");
+        assert!(
+            endpoint_label("/latest/sovereignty/structures/") == "latest/sovereignty/structures"
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn render_includes_recorded_counters() {
+        record_esi_request("latest/alliances/:id");
+        record_corp_event("left");
+
+        let output = render();
+
+        assert!(output.contains("esi_requests_total{endpoint=\"latest/alliances/:id\"}"));
+        assert!(output.contains("corp_events_total{kind=\"left\"}"));
+        assert!(output.contains("esi_errors_total "));
+        assert!(output.contains("adm_systems_critical "));
+    }
+
+    #[traced_test]
+    #[test]
+    fn histogram_counts_cumulative_buckets() {
+        let mut histogram = Histogram::default();
+
+        histogram.observe(0.07);
+        histogram.observe(0.3);
+        histogram.observe(20.0);
+
+        assert!(histogram.buckets == [0, 1, 1, 2, 2, 2, 2, 2]);
+        assert!(histogram.count == 3);
+    }
+
+    #[traced_test]
+    #[test]
+    fn render_includes_latency_histogram() {
+        record_esi_latency("latest/systems/:id", Duration::from_millis(300));
+
+        let output = render();
+
+        assert!(output.contains(
+            "esi_request_duration_seconds_bucket{endpoint=\"latest/systems/:id\",le=\"0.5\"}"
+        ));
+        assert!(output.contains(
+            "esi_request_duration_seconds_bucket{endpoint=\"latest/systems/:id\",le=\"+Inf\"}"
+        ));
+        assert!(
+            output.contains("esi_request_duration_seconds_count{endpoint=\"latest/systems/:id\"}")
+        );
+    }
+}
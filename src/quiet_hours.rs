@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+use crate::{bot::BotNotification, services::adm_service::Status};
+
+const SECONDS_PER_HOUR: u64 = 3600;
+const HOURS_PER_DAY: u64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub start_hour: u64,
+    pub end_hour: u64,
+}
+
+impl FromStr for QuietHours {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<QuietHours> {
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("`{}` is not a range like `22-6`", value))?;
+
+        let start_hour: u64 = start.trim().parse()?;
+        let end_hour: u64 = end.trim().parse()?;
+
+        if start_hour >= HOURS_PER_DAY || end_hour >= HOURS_PER_DAY {
+            anyhow::bail!("quiet hours must be between 0 and 23");
+        }
+
+        if start_hour == end_hour {
+            anyhow::bail!("quiet hours start and end must differ");
+        }
+
+        Ok(QuietHours {
+            start_hour,
+            end_hour,
+        })
+    }
+}
+
+impl QuietHours {
+    // The window includes `start_hour` and ends before `end_hour`, wrapping past midnight
+    // when the end is before the start.
+    pub fn contains(&self, now: u64) -> bool {
+        let hour = (now / SECONDS_PER_HOUR) % HOURS_PER_DAY;
+
+        if self.start_hour < self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+
+    pub fn should_send_now(&self, notification: &BotNotification, now: u64) -> bool {
+        if !self.contains(now) {
+            return true;
+        }
+
+        match notification {
+            BotNotification::NotifyAdm(system_adm) => {
+                matches!(system_adm.status, Status::Critical(_))
+            }
+            BotNotification::NotifyAdmRecovered(_)
+            | BotNotification::NotifyCorpJoinAlliance(..)
+            | BotNotification::NotifyAllianceCreated(_)
+            | BotNotification::NotifyMemberChange(..) => false,
+            BotNotification::NotifyCorpLeftAlliance(..)
+            | BotNotification::NotifyCorpMovedAlliance(..)
+            | BotNotification::NotifyAllianceClosed(_)
+            | BotNotification::NotifyAdmStillCritical(..)
+            | BotNotification::NotifySovLost(_)
+            | BotNotification::NotifyVulnerable(..)
+            | BotNotification::AdmDigest(_)
+            | BotNotification::AdmStartupReport(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::{
+        bot::BotNotification,
+        services::adm_service::{Status, SystemAdm},
+    };
+
+    use super::QuietHours;
+
+    const HOUR: u64 = 3600;
+
+    fn adm(status: Status) -> BotNotification {
+        BotNotification::NotifyAdm(SystemAdm::for_test(30000142, status))
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_quiet_hours() {
+        assert!(
+            "22-6".parse::<QuietHours>().unwrap()
+                == QuietHours {
+                    start_hour: 22,
+                    end_hour: 6
+                }
+        );
+        assert!(" 1 - 5 ".parse::<QuietHours>().is_ok());
+        assert!("22".parse::<QuietHours>().is_err());
+        assert!("22-24".parse::<QuietHours>().is_err());
+        assert!("6-6".parse::<QuietHours>().is_err());
+    }
+
+    #[traced_test]
+    #[test]
+    fn in_window_sends_only_critical() {
+        let quiet_hours: QuietHours = "1-5".parse().unwrap();
+        let now = 2 * HOUR;
+
+        assert!(quiet_hours.should_send_now(&adm(Status::Critical(0.8)), now));
+        assert!(!quiet_hours.should_send_now(&adm(Status::Warning(1.1)), now));
+        assert!(quiet_hours.should_send_now(&BotNotification::NotifyCorpLeftAlliance(1, 2), now));
+        assert!(!quiet_hours.should_send_now(&BotNotification::NotifyCorpJoinAlliance(1, 2), now));
+    }
+
+    #[traced_test]
+    #[test]
+    fn out_of_window_sends_everything() {
+        let quiet_hours: QuietHours = "1-5".parse().unwrap();
+
+        for now in [0, 5 * HOUR, 12 * HOUR] {
+            assert!(quiet_hours.should_send_now(&adm(Status::Warning(1.1)), now));
+            assert!(
+                quiet_hours.should_send_now(&BotNotification::NotifyCorpJoinAlliance(1, 2), now)
+            );
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn window_wraps_past_midnight() {
+        let quiet_hours: QuietHours = "22-6".parse().unwrap();
+        let day = 24 * HOUR;
+
+        assert!(quiet_hours.contains(day + 22 * HOUR));
+        assert!(quiet_hours.contains(day + 23 * HOUR + 59 * 60));
+        assert!(quiet_hours.contains(day));
+        assert!(quiet_hours.contains(day + 5 * HOUR));
+        assert!(!quiet_hours.contains(day + 6 * HOUR));
+        assert!(!quiet_hours.contains(day + 21 * HOUR));
+        assert!(!quiet_hours.should_send_now(&adm(Status::Warning(1.1)), day + 23 * HOUR));
+    }
+}
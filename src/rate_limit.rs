@@ -0,0 +1,229 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MessageRate {
+    pub messages: u32,
+    pub period: Duration,
+}
+
+impl Default for MessageRate {
+    fn default() -> Self {
+        MessageRate {
+            messages: 5,
+            period: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: MessageRate, now: Instant) -> Self {
+        TokenBucket {
+            tokens: rate.messages as f64,
+            updated: now,
+        }
+    }
+
+    // Takes a token and returns how long to wait before it may be used. Tokens may go
+    // negative so that queued messages are spaced out at the refill rate.
+    fn take(&mut self, rate: MessageRate, now: Instant) -> Duration {
+        let capacity = rate.messages as f64;
+        let refill_per_second = capacity / rate.period.as_secs_f64();
+
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * refill_per_second).min(capacity) - 1.0;
+        self.updated = now;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / refill_per_second)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChannelRateLimiter {
+    rate: Option<MessageRate>,
+    buckets: HashMap<u64, TokenBucket>,
+}
+
+impl ChannelRateLimiter {
+    pub fn new(rate: MessageRate) -> Self {
+        ChannelRateLimiter {
+            rate: Some(rate).filter(|rate| rate.messages > 0 && !rate.period.is_zero()),
+            buckets: Default::default(),
+        }
+    }
+
+    fn delay(&mut self, channel_id: u64, now: Instant) -> Duration {
+        let Some(rate) = self.rate else {
+            return Duration::ZERO;
+        };
+
+        self.buckets
+            .entry(channel_id)
+            .or_insert_with(|| TokenBucket::new(rate, now))
+            .take(rate, now)
+    }
+
+    pub async fn acquire(&mut self, channel_id: u64) {
+        let delay = self.delay(channel_id, Instant::now());
+
+        if !delay.is_zero() {
+            tracing::debug!(
+                channel_id,
+                delay_ms = delay.as_millis() as u64,
+                "pacing notification"
+            );
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+// Each channel is paced by its own task, so a burst for one channel doesn't hold back
+// messages for the others. The task stops once the returned sender is dropped.
+pub fn spawn_channel_queue<T, F, Fut>(
+    rate: MessageRate,
+    channel_id: u64,
+    send: F,
+) -> UnboundedSender<T>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut rate_limiter = ChannelRateLimiter::new(rate);
+
+        while let Some(message) = receiver.recv().await {
+            rate_limiter.acquire(channel_id).await;
+            send(message).await;
+        }
+    });
+
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tracing_test::traced_test;
+
+    use super::{spawn_channel_queue, ChannelRateLimiter, MessageRate};
+
+    const RATE: MessageRate = MessageRate {
+        messages: 2,
+        period: Duration::from_secs(2),
+    };
+
+    #[traced_test]
+    #[test]
+    fn burst_then_paced() {
+        let mut limiter = ChannelRateLimiter::new(RATE);
+        let now = Instant::now();
+
+        assert!(limiter.delay(1, now) == Duration::ZERO);
+        assert!(limiter.delay(1, now) == Duration::ZERO);
+        assert!(limiter.delay(1, now) == Duration::from_secs(1));
+        assert!(limiter.delay(1, now) == Duration::from_secs(2));
+    }
+
+    #[traced_test]
+    #[test]
+    fn tokens_refill_over_time() {
+        let mut limiter = ChannelRateLimiter::new(RATE);
+        let now = Instant::now();
+
+        limiter.delay(1, now);
+        limiter.delay(1, now);
+
+        assert!(limiter.delay(1, now + Duration::from_secs(1)) == Duration::ZERO);
+        assert!(limiter.delay(1, now + Duration::from_secs(10)) == Duration::ZERO);
+        assert!(limiter.delay(1, now + Duration::from_secs(10)) == Duration::ZERO);
+        assert!(limiter.delay(1, now + Duration::from_secs(10)) == Duration::from_secs(1));
+    }
+
+    #[traced_test]
+    #[test]
+    fn channels_are_limited_separately() {
+        let mut limiter = ChannelRateLimiter::new(RATE);
+        let now = Instant::now();
+
+        limiter.delay(1, now);
+        limiter.delay(1, now);
+
+        assert!(limiter.delay(1, now) > Duration::ZERO);
+        assert!(limiter.delay(2, now) == Duration::ZERO);
+    }
+
+    #[traced_test]
+    #[test]
+    fn disabled_limiter_never_waits() {
+        let mut limiter = ChannelRateLimiter::new(MessageRate {
+            messages: 0,
+            ..RATE
+        });
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert!(limiter.delay(1, now) == Duration::ZERO);
+        }
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn channel_queues_are_paced_separately() {
+        let rate = MessageRate {
+            messages: 1,
+            period: Duration::from_millis(300),
+        };
+        let (sent, mut received) = tokio::sync::mpsc::unbounded_channel();
+
+        let queue = |channel_id| {
+            let sent = sent.clone();
+
+            spawn_channel_queue(rate, channel_id, move |message: u32| {
+                let sent = sent.clone();
+                async move { sent.send((channel_id, message)).unwrap() }
+            })
+        };
+
+        let (first, second) = (queue(1), queue(2));
+        let started = Instant::now();
+
+        for message in [1, 2, 3] {
+            first.send(message).unwrap();
+        }
+        second.send(4).unwrap();
+
+        let mut order = vec![];
+        for _ in 0..2 {
+            order.push(received.recv().await.unwrap());
+        }
+
+        assert!(order.contains(&(1, 1)));
+        assert!(order.contains(&(2, 4)));
+        assert!(started.elapsed() < rate.period);
+
+        assert!(received.recv().await.unwrap() == (1, 2));
+        assert!(received.recv().await.unwrap() == (1, 3));
+        assert!(started.elapsed() >= rate.period * 2);
+    }
+}
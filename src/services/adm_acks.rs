@@ -0,0 +1,89 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tokio::sync::RwLock;
+
+use crate::esi::EsiID;
+
+pub const DEFAULT_ACK_TTL: Duration = Duration::from_secs(6 * 3600);
+
+#[derive(Debug, Clone)]
+struct Ack {
+    user: String,
+    acked_at: u64,
+}
+
+// Systems an FC has acknowledged from an ADM alert, used to hold back repeated alerts
+// until the acknowledgement expires.
+#[derive(Debug, Clone)]
+pub struct AdmAcks {
+    ttl: Duration,
+    acks: Arc<RwLock<HashMap<EsiID, Ack>>>,
+}
+
+impl AdmAcks {
+    pub fn new(ttl: Duration) -> AdmAcks {
+        AdmAcks {
+            ttl,
+            acks: Default::default(),
+        }
+    }
+
+    pub async fn acknowledge(&self, system_id: EsiID, user: &str, now: u64) {
+        self.acks.write().await.insert(
+            system_id,
+            Ack {
+                user: user.to_string(),
+                acked_at: now,
+            },
+        );
+    }
+
+    pub async fn acknowledged_by(&self, system_id: EsiID, now: u64) -> Option<String> {
+        let mut acks = self.acks.write().await;
+
+        match acks.get(&system_id) {
+            Some(ack) if now.saturating_sub(ack.acked_at) < self.ttl.as_secs() => {
+                Some(ack.user.clone())
+            }
+            Some(_) => {
+                acks.remove(&system_id);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tracing_test::traced_test;
+
+    use super::AdmAcks;
+
+    #[traced_test]
+    #[tokio::test]
+    async fn ack_expires_after_ttl() {
+        let acks = AdmAcks::new(Duration::from_secs(3600));
+
+        acks.acknowledge(30000142, "fc", 1000).await;
+
+        assert!(acks.acknowledged_by(30000142, 1000).await.as_deref() == Some("fc"));
+        assert!(acks.acknowledged_by(30000142, 4599).await.as_deref() == Some("fc"));
+        assert!(acks.acknowledged_by(30002187, 1000).await.is_none());
+        assert!(acks.acknowledged_by(30000142, 4600).await.is_none());
+        assert!(acks.acknowledged_by(30000142, 1000).await.is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn acknowledge_again_renews_ack() {
+        let acks = AdmAcks::new(Duration::from_secs(3600));
+
+        acks.acknowledge(30000142, "fc", 1000).await;
+        acks.acknowledge(30000142, "other fc", 3000).await;
+
+        assert!(acks.acknowledged_by(30000142, 5000).await.as_deref() == Some("other fc"));
+    }
+}
@@ -1,10 +1,20 @@
+use anyhow::Context as _;
+use notify::{RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 use tokio::{fs, sync::RwLock};
 
-const CONFIGURATION_FILE: &'static str = "adm.toml";
+use crate::shutdown::{self, ShutdownReceiver};
 
-#[derive(Clone, Copy, Serialize, Deserialize)]
+const CONFIGURATION_FILE: &str = "adm.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Importance {
     Red,
     Yellow,
@@ -12,6 +22,16 @@ pub enum Importance {
 }
 
 impl Importance {
+    pub const ALL: [Importance; 3] = [Importance::Red, Importance::Yellow, Importance::Green];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Importance::Red => "Red",
+            Importance::Yellow => "Yellow",
+            Importance::Green => "Green",
+        }
+    }
+
     pub fn warning_threshold(&self) -> f32 {
         match self {
             Importance::Red => 4.2,
@@ -29,43 +49,196 @@ impl Importance {
     }
 }
 
+impl FromStr for Importance {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Importance> {
+        Importance::ALL
+            .into_iter()
+            .find(|importance| importance.name().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| anyhow::anyhow!("`{}` is not one of Red, Yellow or Green", value))
+    }
+}
+
 impl std::fmt::Display for Importance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({:.1} - {:.1})",
+            self.name(),
+            self.critical_threshold(),
+            self.warning_threshold()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigurationFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigurationFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ConfigurationFormat::Json,
+            _ => ConfigurationFormat::Toml,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
         match self {
-            Importance::Red => write!(f, "Red (4.0 - 4.2)"),
-            Importance::Yellow => write!(f, "Yellow (3.0 - 3.2)"),
-            Importance::Green => write!(f, "Green (1.0 - 1.2)"),
+            ConfigurationFormat::Toml => "toml",
+            ConfigurationFormat::Json => "json",
         }
     }
+
+    pub fn parse(&self, data: &str) -> anyhow::Result<Configuration> {
+        Ok(match self {
+            ConfigurationFormat::Toml => toml::from_str(data)?,
+            ConfigurationFormat::Json => serde_json::from_str(data)?,
+        })
+    }
+
+    pub fn serialize(&self, configuration: &Configuration) -> anyhow::Result<String> {
+        Ok(match self {
+            ConfigurationFormat::Toml => toml::to_string(configuration)?,
+            ConfigurationFormat::Json => serde_json::to_string_pretty(configuration)?,
+        })
+    }
 }
 
-#[derive(Default, Serialize, Deserialize)]
-struct Configuration {
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Configuration {
     importance: HashMap<String, Importance>,
+    #[serde(default)]
+    custom: HashMap<String, (f32, f32)>,
+    #[serde(default)]
+    pinned: BTreeSet<String>,
+}
+
+impl Configuration {
+    pub fn system_count(&self) -> usize {
+        self.importance
+            .keys()
+            .chain(self.custom.keys())
+            .collect::<HashSet<_>>()
+            .len()
+    }
 }
 
 #[derive(Clone)]
 pub struct AdmConfiguration {
+    path: PathBuf,
     config: Arc<RwLock<Configuration>>,
 }
 
 impl AdmConfiguration {
     pub async fn load_configuration() -> anyhow::Result<AdmConfiguration> {
-        let configuration = if let Ok(toml_data) = fs::read_to_string(CONFIGURATION_FILE).await {
-            toml::from_str(&toml_data)?
+        let path = env::var("ADM_CONFIG_PATH").unwrap_or_else(|_| CONFIGURATION_FILE.to_string());
+
+        AdmConfiguration::load_configuration_from(path).await
+    }
+
+    pub async fn load_configuration_from(
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<AdmConfiguration> {
+        let path = path.as_ref().to_path_buf();
+
+        let format = ConfigurationFormat::from_path(&path);
+
+        let configuration = if let Ok(data) = fs::read_to_string(&path).await {
+            format.parse(&data)?
         } else {
             Default::default()
         };
 
         Ok(AdmConfiguration {
+            path,
             config: Arc::new(RwLock::new(configuration)),
         })
     }
 
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let data = fs::read_to_string(&self.path).await?;
+        let configuration = ConfigurationFormat::from_path(&self.path).parse(&data)?;
+
+        *self.config.write().await = configuration;
+
+        tracing::info!(path = ?self.path, "reloaded adm configuration");
+
+        Ok(())
+    }
+
+    pub async fn watch(&self, mut shutdown: ShutdownReceiver) -> anyhow::Result<()> {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+
+        // Editors usually replace the file, so watch the directory rather than the file itself.
+        let directory = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+        tracing::info!(path = ?self.path, "watching adm configuration");
+
+        loop {
+            let event = tokio::select! {
+                _ = shutdown::requested(&mut shutdown) => break,
+                event = receiver.recv() => event,
+            };
+
+            let event: notify::Event = match event {
+                Some(Ok(event)) => event,
+                Some(Err(err)) => {
+                    tracing::warn!(?err, "adm configuration watch error");
+                    continue;
+                }
+                None => break,
+            };
+
+            let changed = (event.kind.is_create() || event.kind.is_modify())
+                && event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == self.path.file_name());
+
+            if changed {
+                if let Err(err) = self.reload().await {
+                    tracing::warn!(?err, "couldn't reload adm configuration");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     async fn save_configuration(&self, configuration: &Configuration) -> anyhow::Result<()> {
-        let toml_data = toml::to_string(configuration)?;
+        let data = self.format().serialize(configuration)?;
+
+        write_atomic(&self.path, &data).await
+    }
 
-        fs::write(CONFIGURATION_FILE, toml_data).await?;
+    pub fn format(&self) -> ConfigurationFormat {
+        ConfigurationFormat::from_path(&self.path)
+    }
+
+    pub async fn configuration(&self) -> Configuration {
+        self.config.read().await.clone()
+    }
+
+    pub async fn replace(&self, configuration: Configuration) -> anyhow::Result<()> {
+        let mut config = self.config.write().await;
+
+        self.save_configuration(&configuration).await?;
+        *config = configuration;
+
+        tracing::info!(path = ?self.path, "replaced adm configuration");
 
         Ok(())
     }
@@ -77,11 +250,57 @@ impl AdmConfiguration {
     ) -> anyhow::Result<()> {
         let mut config = self.config.write().await;
 
-        config
+        let mut updated = config.clone();
+        updated
             .importance
             .insert(system_name.to_string(), importance);
+        updated.custom.remove(system_name);
+
+        self.save_configuration(&updated).await?;
+        *config = updated;
 
-        self.save_configuration(&config).await
+        Ok(())
+    }
+
+    pub async fn set_custom_thresholds(
+        &self,
+        system_name: &str,
+        warning_threshold: f32,
+        critical_threshold: f32,
+    ) -> anyhow::Result<()> {
+        if warning_threshold <= critical_threshold {
+            anyhow::bail!("warning threshold must be greater than critical threshold");
+        }
+
+        let mut config = self.config.write().await;
+
+        let mut updated = config.clone();
+        updated.custom.insert(
+            system_name.to_string(),
+            (warning_threshold, critical_threshold),
+        );
+
+        self.save_configuration(&updated).await?;
+        *config = updated;
+
+        Ok(())
+    }
+
+    pub async fn remove_importance(&self, system_name: &str) -> anyhow::Result<bool> {
+        let mut config = self.config.write().await;
+
+        let mut updated = config.clone();
+        let removed_importance = updated.importance.remove(system_name).is_some();
+        let removed_custom = updated.custom.remove(system_name).is_some();
+
+        if !removed_importance && !removed_custom {
+            return Ok(false);
+        }
+
+        self.save_configuration(&updated).await?;
+        *config = updated;
+
+        Ok(true)
     }
 
     pub async fn get_importance(&self, system_name: &str) -> Option<Importance> {
@@ -92,4 +311,328 @@ impl AdmConfiguration {
             .get(system_name)
             .copied()
     }
+
+    pub async fn get_custom_thresholds(&self, system_name: &str) -> Option<(f32, f32)> {
+        self.config.read().await.custom.get(system_name).copied()
+    }
+
+    pub async fn get_thresholds(&self, system_name: &str) -> Option<(f32, f32)> {
+        if let Some(thresholds) = self.get_custom_thresholds(system_name).await {
+            return Some(thresholds);
+        }
+
+        self.get_importance(system_name).await.map(|importance| {
+            (
+                importance.warning_threshold(),
+                importance.critical_threshold(),
+            )
+        })
+    }
+
+    pub async fn pinned_systems(&self) -> BTreeSet<String> {
+        self.config.read().await.pinned.clone()
+    }
+
+    pub async fn all_importances(&self) -> Vec<(String, Importance)> {
+        let mut importances: Vec<_> = self
+            .config
+            .read()
+            .await
+            .importance
+            .iter()
+            .map(|(system_name, importance)| (system_name.clone(), *importance))
+            .collect();
+
+        importances.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        importances
+    }
+}
+
+// The data is written to a temporary file next to `path` and renamed over it, so a crash
+// while writing leaves the previous file intact.
+pub(crate) async fn write_atomic(path: &Path, data: &str) -> anyhow::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    if let Err(err) = fs::write(&temp_path, data).await {
+        let _ = fs::remove_file(&temp_path).await;
+
+        return Err(err).with_context(|| format!("couldn't write {}", temp_path.display()));
+    }
+
+    fs::rename(&temp_path, path)
+        .await
+        .with_context(|| format!("couldn't replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use std::{
+        collections::{BTreeSet, HashMap},
+        path::Path,
+    };
+
+    use super::{write_atomic, AdmConfiguration, Configuration, ConfigurationFormat, Importance};
+
+    #[traced_test]
+    #[test]
+    fn importance_display() {
+        assert!(Importance::Red.to_string() == "Red (4.0 - 4.2)");
+        assert!(Importance::Yellow.to_string() == "Yellow (3.0 - 3.2)");
+        assert!(Importance::Green.to_string() == "Green (1.0 - 1.2)");
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_importance() {
+        assert!("red".parse::<Importance>().unwrap() == Importance::Red);
+        assert!(" Yellow ".parse::<Importance>().unwrap() == Importance::Yellow);
+        assert!("GREEN".parse::<Importance>().unwrap() == Importance::Green);
+        assert!("blue".parse::<Importance>().is_err());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn remove_importance() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_remove_test.toml");
+        let _ = std::fs::remove_file(&path);
+
+        let configuration = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+
+        configuration
+            .set_importance("JITA", Importance::Red)
+            .await
+            .unwrap();
+
+        assert!(configuration.remove_importance("JITA").await.unwrap());
+        assert!(configuration.get_importance("JITA").await.is_none());
+        assert!(!configuration.remove_importance("JITA").await.unwrap());
+
+        let reloaded = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reloaded.get_importance("JITA").await.is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn custom_thresholds_override_importance() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_custom_test.toml");
+        let _ = std::fs::remove_file(&path);
+
+        let configuration = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+
+        configuration
+            .set_importance("JITA", Importance::Red)
+            .await
+            .unwrap();
+
+        assert!(configuration.get_thresholds("JITA").await == Some((4.2, 4.0)));
+
+        configuration
+            .set_custom_thresholds("JITA", 5.0, 4.5)
+            .await
+            .unwrap();
+
+        assert!(configuration.get_thresholds("JITA").await == Some((5.0, 4.5)));
+        assert!(configuration.get_thresholds("AMARR").await.is_none());
+
+        let reloaded = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reloaded.get_thresholds("JITA").await == Some((5.0, 4.5)));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn custom_thresholds_rejects_inverted_thresholds() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_inverted_test.toml");
+
+        let configuration = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+
+        assert!(configuration
+            .set_custom_thresholds("JITA", 4.0, 4.5)
+            .await
+            .is_err());
+        assert!(configuration.get_thresholds("JITA").await.is_none());
+    }
+
+    fn configuration() -> Configuration {
+        Configuration {
+            importance: HashMap::from([
+                ("1DQ1-A".to_string(), Importance::Red),
+                ("T5ZI-S".to_string(), Importance::Yellow),
+            ]),
+            custom: HashMap::from([("GE-8JV".to_string(), (2.5, 2.0))]),
+            pinned: BTreeSet::from(["1DQ1-A".to_string()]),
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn configuration_format_from_extension() {
+        assert!(ConfigurationFormat::from_path(Path::new("adm.toml")) == ConfigurationFormat::Toml);
+        assert!(ConfigurationFormat::from_path(Path::new("adm.json")) == ConfigurationFormat::Json);
+        assert!(ConfigurationFormat::from_path(Path::new("adm")) == ConfigurationFormat::Toml);
+    }
+
+    #[traced_test]
+    #[test]
+    fn configuration_round_trips_through_formats() {
+        for format in [ConfigurationFormat::Toml, ConfigurationFormat::Json] {
+            let data = format.serialize(&configuration()).unwrap();
+
+            assert!(format.parse(&data).unwrap() == configuration());
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn configuration_system_count() {
+        let mut configuration = configuration();
+
+        assert!(configuration.system_count() == 3);
+
+        configuration
+            .custom
+            .insert("1DQ1-A".to_string(), (5.0, 4.5));
+
+        assert!(configuration.system_count() == 3);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn exported_configuration_imports_into_other_format() {
+        let toml_path = std::env::temp_dir().join("alliance_squawk_adm_export_test.toml");
+        let json_path = std::env::temp_dir().join("alliance_squawk_adm_import_test.json");
+        let _ = std::fs::remove_file(&json_path);
+
+        let exported = AdmConfiguration::load_configuration_from(&toml_path)
+            .await
+            .unwrap();
+        exported.replace(configuration()).await.unwrap();
+
+        let data = ConfigurationFormat::Json
+            .serialize(&exported.configuration().await)
+            .unwrap();
+
+        let imported = AdmConfiguration::load_configuration_from(&json_path)
+            .await
+            .unwrap();
+        imported
+            .replace(ConfigurationFormat::Json.parse(&data).unwrap())
+            .await
+            .unwrap();
+
+        let reloaded = AdmConfiguration::load_configuration_from(&json_path)
+            .await
+            .unwrap();
+        std::fs::remove_file(&toml_path).unwrap();
+        std::fs::remove_file(&json_path).unwrap();
+
+        assert!(reloaded.configuration().await == configuration());
+        assert!(reloaded.get_thresholds("GE-8JV").await == Some((2.5, 2.0)));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn json_configuration_saved_as_json() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_json_test.json");
+        let _ = std::fs::remove_file(&path);
+
+        let configuration = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+
+        configuration
+            .set_importance("JITA", Importance::Yellow)
+            .await
+            .unwrap();
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        let reloaded = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(serde_json::from_str::<serde_json::Value>(&data).is_ok());
+        assert!(reloaded.get_importance("JITA").await == Some(Importance::Yellow));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn reload_picks_up_file_changes() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_reload_test.toml");
+        std::fs::write(&path, "[importance]\nJITA = \"Green\"\n").unwrap();
+
+        let configuration = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+
+        assert!(configuration.get_importance("JITA").await == Some(Importance::Green));
+
+        std::fs::write(&path, "[importance]\nJITA = \"Red\"\n").unwrap();
+        configuration.reload().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(configuration.get_importance("JITA").await == Some(Importance::Red));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn write_atomic_replaces_file() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_atomic_test.toml");
+        let temp_path = std::env::temp_dir().join("alliance_squawk_adm_atomic_test.toml.tmp");
+        std::fs::write(&path, "[importance]\nJITA = \"Green\"\n").unwrap();
+
+        write_atomic(&path, "[importance]\nJITA = \"Red\"\n")
+            .await
+            .unwrap();
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(data == "[importance]\nJITA = \"Red\"\n");
+        assert!(!temp_path.exists());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn failed_save_keeps_configuration() {
+        let path = std::env::temp_dir().join("alliance_squawk_adm_failed_save_test.toml");
+        let temp_path = std::env::temp_dir().join("alliance_squawk_adm_failed_save_test.toml.tmp");
+        std::fs::write(&path, "[importance]\nJITA = \"Green\"\n").unwrap();
+        let _ = std::fs::remove_dir(&temp_path);
+
+        let configuration = AdmConfiguration::load_configuration_from(&path)
+            .await
+            .unwrap();
+
+        // A directory in place of the temporary file makes the write fail.
+        std::fs::create_dir(&temp_path).unwrap();
+
+        let result = configuration.set_importance("JITA", Importance::Red).await;
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir(&temp_path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+        assert!(configuration.get_importance("JITA").await == Some(Importance::Green));
+        assert!(data == "[importance]\nJITA = \"Green\"\n");
+    }
 }
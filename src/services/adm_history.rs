@@ -0,0 +1,175 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use rusqlite::{params, Connection};
+
+use crate::esi::EsiID;
+
+use super::adm_service::{Status, SystemAdm};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdmHistoryEntry {
+    pub system_id: EsiID,
+    pub status: Status,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdmHistoryStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl AdmHistoryStore {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<AdmHistoryStore> {
+        AdmHistoryStore::from_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> anyhow::Result<AdmHistoryStore> {
+        AdmHistoryStore::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(connection: Connection) -> anyhow::Result<AdmHistoryStore> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS adm_readings (
+                system_id INTEGER NOT NULL,
+                adm REAL NOT NULL,
+                status TEXT NOT NULL,
+                timestamp INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS adm_readings_system_timestamp
+                ON adm_readings (system_id, timestamp);",
+        )?;
+
+        Ok(AdmHistoryStore {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    pub fn record(&self, timestamp: u64, system_adms: &[SystemAdm]) -> anyhow::Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO adm_readings (system_id, adm, status, timestamp)
+                VALUES (?1, ?2, ?3, ?4)",
+            )?;
+
+            for system_adm in system_adms {
+                statement.execute(params![
+                    system_adm.system_id,
+                    system_adm.status.adm(),
+                    status_name(system_adm.status),
+                    timestamp
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    pub fn readings(&self, system_id: EsiID, since: u64) -> anyhow::Result<Vec<AdmHistoryEntry>> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection.prepare_cached(
+            "SELECT adm, status, timestamp FROM adm_readings
+            WHERE system_id = ?1 AND timestamp >= ?2
+            ORDER BY timestamp",
+        )?;
+
+        let rows = statement.query_map(params![system_id, since], |row| {
+            Ok((
+                row.get::<_, f32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u64>(2)?,
+            ))
+        })?;
+
+        let mut entries = vec![];
+
+        for row in rows {
+            let (adm, status, timestamp) = row?;
+
+            entries.push(AdmHistoryEntry {
+                system_id,
+                status: parse_status(&status, adm)?,
+                timestamp,
+            });
+        }
+
+        Ok(entries)
+    }
+}
+
+fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::Good(_) => "good",
+        Status::Warning(_) => "warning",
+        Status::Critical(_) => "critical",
+    }
+}
+
+fn parse_status(name: &str, adm: f32) -> anyhow::Result<Status> {
+    match name {
+        "good" => Ok(Status::Good(adm)),
+        "warning" => Ok(Status::Warning(adm)),
+        "critical" => Ok(Status::Critical(adm)),
+        name => Err(anyhow::anyhow!("unknown adm status `{}`", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::services::adm_service::{Status, SystemAdm};
+
+    use super::{AdmHistoryEntry, AdmHistoryStore};
+
+    #[traced_test]
+    #[test]
+    fn readings_since_timestamp() {
+        let store = AdmHistoryStore::open_in_memory().unwrap();
+
+        store
+            .record(
+                1000,
+                &[
+                    SystemAdm::for_test(1, Status::Good(3.0)),
+                    SystemAdm::for_test(2, Status::Warning(1.5)),
+                ],
+            )
+            .unwrap();
+        store
+            .record(2000, &[SystemAdm::for_test(1, Status::Critical(0.8))])
+            .unwrap();
+        store
+            .record(3000, &[SystemAdm::for_test(1, Status::Warning(1.2))])
+            .unwrap();
+
+        let readings = store.readings(1, 2000).unwrap();
+
+        assert!(
+            readings
+                == vec![
+                    AdmHistoryEntry {
+                        system_id: 1,
+                        status: Status::Critical(0.8),
+                        timestamp: 2000,
+                    },
+                    AdmHistoryEntry {
+                        system_id: 1,
+                        status: Status::Warning(1.2),
+                        timestamp: 3000,
+                    },
+                ]
+        );
+        assert!(store.readings(2, 0).unwrap().len() == 1);
+        assert!(store.readings(3, 0).unwrap().is_empty());
+    }
+}
@@ -1,58 +1,278 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    env, mem,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::{
+    fs,
+    sync::mpsc::{UnboundedReceiver, UnboundedSender},
+};
+
+use crate::{
+    bot::BotNotification,
+    config::env_flag,
+    esi::EsiID,
+    health::{unix_now, PollHealth, SharedHealth},
+    metrics,
+    shutdown::{self, ShutdownReceiver},
+};
 
-use crate::{bot::BotNotification, esi::EsiID};
+#[cfg(feature = "adm-history")]
+use super::adm_history::AdmHistoryStore;
+use super::adm_service::{AdmService, Status, SystemAdm};
 
-use super::adm_service::{AdmService, Status};
+const DEFAULT_ADM_POLL_SECONDS: u64 = 3600;
+const MIN_ADM_POLL_SECONDS: u64 = 300;
+const DEFAULT_ADM_DEBOUNCE_POLLS: u32 = 2;
 
-const ADM_UPDATE_TIME_SECONDS: u64 = 3600;
+const MIN_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+
+const SECONDS_PER_HOUR: u64 = 3600;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CriticalTimer {
+    since: u64,
+    last_ping: u64,
+}
+
+pub type AdmRefreshSender = UnboundedSender<()>;
+pub type AdmRefreshReceiver = UnboundedReceiver<()>;
 
 pub struct AdmNotificationService {
     adm: AdmService,
     last_adm_update: Option<Instant>,
+    poll_interval: Duration,
+    digest_hour: Option<u64>,
+    last_digest_day: Option<u64>,
     notifications: UnboundedSender<BotNotification>,
     history: HashMap<EsiID, Status>,
+    history_path: PathBuf,
+    debounce_polls: u32,
+    pending: HashMap<EsiID, (Status, u32)>,
+    critical_reping: Option<u64>,
+    critical_timers: HashMap<EsiID, CriticalTimer>,
+    health: SharedHealth,
+    refresh_requests: Option<AdmRefreshReceiver>,
+    retry_backoff: Option<Duration>,
+    retry_at: Option<Instant>,
+    startup_report: bool,
+    include_tcus: bool,
+    #[cfg(feature = "adm-history")]
+    history_store: Option<AdmHistoryStore>,
 }
 
 impl AdmNotificationService {
-    pub fn new(adm: AdmService, notifications: UnboundedSender<BotNotification>) -> Self {
+    pub fn new(
+        adm: AdmService,
+        notifications: UnboundedSender<BotNotification>,
+        history_path: PathBuf,
+        health: SharedHealth,
+    ) -> Self {
+        let poll_interval = poll_interval(env::var("ADM_POLL_SECONDS").ok().as_deref());
+
+        Self::with_poll_interval(adm, notifications, history_path, health, poll_interval)
+    }
+
+    fn with_poll_interval(
+        adm: AdmService,
+        notifications: UnboundedSender<BotNotification>,
+        history_path: PathBuf,
+        health: SharedHealth,
+        poll_interval: Duration,
+    ) -> Self {
+        let history = match load_history(&history_path) {
+            Ok(history) => {
+                tracing::info!(?history_path, systems = history.len(), "loaded adm history");
+                history
+            }
+            Err(err) => {
+                tracing::warn!(?history_path, ?err, "couldn't load adm history");
+                Default::default()
+            }
+        };
+
+        tracing::info!(
+            poll_seconds = poll_interval.as_secs(),
+            "adm notification poll interval"
+        );
+
+        let digest_hour = digest_hour(env::var("ADM_DIGEST_HOUR").ok().as_deref());
+
+        if let Some(digest_hour) = digest_hour {
+            tracing::info!(digest_hour, "adm digest scheduled");
+        }
+
+        let debounce_polls = debounce_polls(env::var("ADM_DEBOUNCE_POLLS").ok().as_deref());
+
+        let critical_reping =
+            critical_reping(env::var("ADM_CRITICAL_REPING_HOURS").ok().as_deref());
+
+        if let Some(critical_reping) = critical_reping {
+            tracing::info!(
+                reping_hours = critical_reping / SECONDS_PER_HOUR,
+                "adm critical re-ping enabled"
+            );
+        }
+
+        let startup_report = env_flag("ADM_STARTUP_REPORT", false);
+
+        health.write().unwrap().adm = PollHealth::new(poll_interval);
+
+        let include_tcus = adm.include_tcus();
+
         AdmNotificationService {
             adm,
             notifications,
             last_adm_update: None,
-            history: Default::default(),
+            poll_interval,
+            digest_hour,
+            last_digest_day: None,
+            history,
+            history_path,
+            debounce_polls,
+            pending: Default::default(),
+            critical_reping,
+            critical_timers: Default::default(),
+            health,
+            refresh_requests: None,
+            retry_backoff: None,
+            retry_at: None,
+            startup_report,
+            include_tcus,
+            #[cfg(feature = "adm-history")]
+            history_store: None,
         }
     }
 
+    #[cfg(feature = "adm-history")]
+    pub fn with_history_store(mut self, history_store: AdmHistoryStore) -> Self {
+        self.history_store = Some(history_store);
+        self
+    }
+
+    pub fn with_refresh_requests(mut self, refresh_requests: AdmRefreshReceiver) -> Self {
+        self.refresh_requests = Some(refresh_requests);
+        self
+    }
+
+    async fn save_history(&self) -> anyhow::Result<()> {
+        let json_data = serde_json::to_string(&self.history)?;
+
+        fs::write(&self.history_path, json_data).await?;
+
+        Ok(())
+    }
+
     pub async fn send_adm_notifications(&mut self) -> anyhow::Result<()> {
         self.last_adm_update = Some(Instant::now());
 
-        let system_adms = self.adm.get_adm_status().await?;
+        let include_tcus = self.adm.include_tcus();
+        let structure_types_changed =
+            mem::replace(&mut self.include_tcus, include_tcus) != include_tcus;
+
+        let system_adms = self.adm.refresh_adm_status().await?;
+
+        metrics::set_adm_systems_critical(
+            system_adms
+                .iter()
+                .filter(|system_adm| matches!(system_adm.status, Status::Critical(_)))
+                .count() as u64,
+        );
+
+        #[cfg(feature = "adm-history")]
+        if let Some(history_store) = &self.history_store {
+            if let Err(err) = history_store.record(unix_now(), &system_adms) {
+                tracing::error!(?err, "couldn't record adm history");
+            }
+        }
+
+        // Sov loss is judged from the structures themselves, a system missing from the adm
+        // status because a lookup failed is still held.
+        let held_systems: HashSet<EsiID> = self
+            .adm
+            .alliance_structures()
+            .await?
+            .iter()
+            .map(|sovereignty_structure| sovereignty_structure.solar_system_id)
+            .collect();
+
+        // Systems that appear or disappear because TCUs were toggled weren't gained or lost,
+        // their history is reset without notifying.
+        if structure_types_changed {
+            let untracked: Vec<EsiID> = self
+                .history
+                .keys()
+                .filter(|system_id| !held_systems.contains(system_id))
+                .copied()
+                .collect();
+
+            for system_id in &untracked {
+                self.history.remove(system_id);
+                self.pending.remove(system_id);
+                self.critical_timers.remove(system_id);
+            }
+
+            for system_adm in &system_adms {
+                self.history
+                    .entry(system_adm.system_id)
+                    .or_insert(system_adm.status);
+            }
+
+            tracing::info!(
+                include_tcus,
+                untracked = untracked.len(),
+                "included sov structures changed, reset adm history"
+            );
+        }
+
+        let lost_systems = lost_systems(&self.history, &held_systems);
+        let now = unix_now();
 
         for system_adm in system_adms {
             let prev_adm = self.history.remove(&system_adm.system_id);
 
-            if let Err(_) = match (system_adm.status, prev_adm) {
-                (Status::Warning(_), Some(Status::Good(_))) => self
-                    .notifications
-                    .send(BotNotification::NotifyAdm(system_adm)),
-                (Status::Critical(_), Some(Status::Warning(_))) => self
-                    .notifications
-                    .send(BotNotification::NotifyAdm(system_adm)),
-                (Status::Warning(_), None) => self
-                    .notifications
-                    .send(BotNotification::NotifyAdm(system_adm)),
-                (Status::Critical(_), None) => self
-                    .notifications
-                    .send(BotNotification::NotifyAdm(system_adm)),
-                (_, _) => Ok(()),
-            } {
+            let (status, pending) = confirmed_status(
+                prev_adm,
+                self.pending.remove(&system_adm.system_id),
+                system_adm.status,
+                self.debounce_polls,
+            );
+
+            if let Some(pending) = pending {
+                tracing::debug!(?system_adm, polls = pending.1, "adm status change pending");
+
+                self.pending.insert(system_adm.system_id, pending);
+            }
+
+            let system_adm = SystemAdm {
+                status,
+                previous_adm: system_adm
+                    .previous_adm
+                    .or(prev_adm.map(|prev_adm| prev_adm.adm())),
+                ..system_adm
+            };
+
+            let notification = track_critical(
+                &mut self.critical_timers,
+                system_adm,
+                adm_notification(system_adm, prev_adm),
+                now,
+                self.critical_reping,
+            );
+
+            if notification
+                .is_some_and(|notification| self.notifications.send(notification).is_err())
+            {
                 tracing::error!(?system_adm, "couldn't send adm status to bot");
 
+                if let Some(prev_adm) = prev_adm {
+                    self.history.insert(system_adm.system_id, prev_adm);
+                }
+
                 return Err(anyhow::Error::msg("couldn't send notification to bot")
                     .context("bot not running"));
             }
@@ -60,23 +280,1071 @@ impl AdmNotificationService {
             self.history.insert(system_adm.system_id, system_adm.status);
         }
 
+        for system_id in lost_systems {
+            self.history.remove(&system_id);
+            self.pending.remove(&system_id);
+            self.critical_timers.remove(&system_id);
+
+            if self
+                .notifications
+                .send(BotNotification::NotifySovLost(system_id))
+                .is_err()
+            {
+                tracing::error!(system_id, "couldn't send sov lost to bot");
+
+                return Err(anyhow::Error::msg("couldn't send notification to bot")
+                    .context("bot not running"));
+            }
+        }
+
+        if let Err(err) = self.save_history().await {
+            tracing::error!(?err, "couldn't save adm history");
+        }
+
+        self.health.write().unwrap().adm.record_success();
+
+        Ok(())
+    }
+
+    async fn send_adm_report(
+        &mut self,
+        report: fn(Vec<SystemAdm>) -> BotNotification,
+    ) -> anyhow::Result<()> {
+        // Good systems are included so the report can summarize alliance wide health.
+        let system_adms = self.adm.get_adm_status().await?;
+
+        tracing::info!(systems = system_adms.len(), "send adm report");
+
+        if self.notifications.send(report(system_adms)).is_err() {
+            return Err(
+                anyhow::Error::msg("couldn't send notification to bot").context("bot not running")
+            );
+        }
+
         Ok(())
     }
 
-    pub async fn run(&mut self) -> anyhow::Result<()> {
+    async fn poll_adm(&mut self) {
+        match self.send_adm_notifications().await {
+            Ok(()) => {
+                self.retry_backoff = None;
+                self.retry_at = None;
+
+                if mem::take(&mut self.startup_report) {
+                    if let Err(err) = self
+                        .send_adm_report(BotNotification::AdmStartupReport)
+                        .await
+                    {
+                        tracing::error!(?err, "couldn't send adm startup report");
+                    }
+                }
+            }
+            // Nobody is left to receive notifications, `run` stops instead of retrying.
+            Err(err) if self.notifications.is_closed() => {
+                tracing::warn!(?err, "notification channel closed, not retrying");
+            }
+            Err(err) => {
+                let backoff = retry_backoff(self.retry_backoff);
+
+                tracing::warn!(
+                    ?err,
+                    backoff_seconds = backoff.as_secs(),
+                    "couldn't send adm notifications, retrying"
+                );
+
+                self.retry_backoff = Some(backoff);
+                self.retry_at = Some(Instant::now() + backoff);
+            }
+        }
+    }
+
+    pub async fn run(&mut self, mut shutdown: ShutdownReceiver) -> anyhow::Result<()> {
         loop {
-            match self.last_adm_update {
-                Some(last_alliance_queue_update)
-                    if last_alliance_queue_update.elapsed()
-                        >= Duration::from_secs(ADM_UPDATE_TIME_SECONDS) =>
-                {
-                    self.send_adm_notifications().await?;
+            if self.notifications.is_closed() {
+                tracing::warn!("notification channel closed, stopping adm notification service");
+                break;
+            }
+
+            if let Some(digest_hour) = self.digest_hour {
+                let now = unix_now();
+
+                if digest_due(now, digest_hour, self.last_digest_day) {
+                    self.last_digest_day = Some(now / SECONDS_PER_DAY);
+
+                    if let Err(err) = self.send_adm_report(BotNotification::AdmDigest).await {
+                        tracing::error!(?err, "couldn't send adm digest");
+                    }
                 }
-                None => self.send_adm_notifications().await?,
-                _ => {}
+            }
+
+            let poll_due = match self.last_adm_update {
+                Some(last_adm_update) => last_adm_update.elapsed() >= self.poll_interval,
+                None => true,
             };
+            let retry_due = self
+                .retry_at
+                .is_some_and(|retry_at| Instant::now() >= retry_at);
+
+            if poll_due || retry_due {
+                self.poll_adm().await;
+            }
+
+            tokio::select! {
+                _ = shutdown::requested(&mut shutdown) => break,
+                _ = refresh_requested(&mut self.refresh_requests) => {
+                    tracing::info!("adm refresh requested");
+
+                    self.last_adm_update = None;
+                }
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+            }
+        }
+
+        if let Err(err) = self.save_history().await {
+            tracing::error!(?err, "couldn't save adm history");
+        }
+
+        tracing::info!("adm notification service stopped");
+
+        Ok(())
+    }
+}
+
+async fn refresh_requested(refresh_requests: &mut Option<AdmRefreshReceiver>) {
+    if let Some(refresh_requests) = refresh_requests {
+        if refresh_requests.recv().await.is_some() {
+            return;
+        }
+    }
+
+    std::future::pending().await
+}
+
+fn retry_backoff(previous: Option<Duration>) -> Duration {
+    match previous {
+        Some(previous) => (previous * 2).min(MAX_RETRY_BACKOFF),
+        None => MIN_RETRY_BACKOFF,
+    }
+}
+
+fn poll_interval(poll_seconds: Option<&str>) -> Duration {
+    let poll_seconds = match poll_seconds.map(str::parse::<u64>) {
+        Some(Ok(poll_seconds)) if poll_seconds < MIN_ADM_POLL_SECONDS => {
+            tracing::warn!(
+                poll_seconds,
+                min_poll_seconds = MIN_ADM_POLL_SECONDS,
+                "adm poll interval too short, clamping"
+            );
+            MIN_ADM_POLL_SECONDS
+        }
+        Some(Ok(poll_seconds)) => poll_seconds,
+        Some(Err(err)) => {
+            tracing::warn!(?err, "invalid adm poll interval, using default");
+            DEFAULT_ADM_POLL_SECONDS
+        }
+        None => DEFAULT_ADM_POLL_SECONDS,
+    };
+
+    Duration::from_secs(poll_seconds)
+}
+
+fn debounce_polls(debounce_polls: Option<&str>) -> u32 {
+    match debounce_polls.map(str::parse::<u32>) {
+        Some(Ok(0)) => {
+            tracing::warn!("adm debounce polls must be at least 1, using 1");
+            1
+        }
+        Some(Ok(debounce_polls)) => debounce_polls,
+        Some(Err(err)) => {
+            tracing::warn!(?err, "invalid adm debounce polls, using default");
+            DEFAULT_ADM_DEBOUNCE_POLLS
+        }
+        None => DEFAULT_ADM_DEBOUNCE_POLLS,
+    }
+}
+
+fn confirmed_status(
+    prev_status: Option<Status>,
+    pending: Option<(Status, u32)>,
+    status: Status,
+    required_polls: u32,
+) -> (Status, Option<(Status, u32)>) {
+    let prev_status = match prev_status {
+        Some(prev_status) if mem::discriminant(&prev_status) != mem::discriminant(&status) => {
+            prev_status
+        }
+        _ => return (status, None),
+    };
+
+    let polls = match pending {
+        Some((pending_status, polls))
+            if mem::discriminant(&pending_status) == mem::discriminant(&status) =>
+        {
+            polls + 1
+        }
+        _ => 1,
+    };
+
+    if polls >= required_polls {
+        (status, None)
+    } else {
+        (prev_status, Some((status, polls)))
+    }
+}
+
+fn critical_reping(reping_hours: Option<&str>) -> Option<u64> {
+    match reping_hours.map(str::parse::<u64>) {
+        Some(Ok(0)) | None => None,
+        Some(Ok(reping_hours)) => Some(reping_hours * SECONDS_PER_HOUR),
+        Some(Err(err)) => {
+            tracing::warn!(?err, "invalid adm critical re-ping hours, re-ping disabled");
+            None
+        }
+    }
+}
+
+// Tracks how long each system has been critical. Systems that stay critical without a new
+// notification are re-pinged each time another `reping_seconds` have passed since the last ping.
+fn track_critical(
+    critical_timers: &mut HashMap<EsiID, CriticalTimer>,
+    system_adm: SystemAdm,
+    notification: Option<BotNotification>,
+    now: u64,
+    reping_seconds: Option<u64>,
+) -> Option<BotNotification> {
+    if !matches!(system_adm.status, Status::Critical(_)) {
+        critical_timers.remove(&system_adm.system_id);
+        return notification;
+    }
+
+    let timer = CriticalTimer {
+        since: now,
+        last_ping: now,
+    };
+
+    if notification.is_some() {
+        critical_timers.insert(system_adm.system_id, timer);
+        return notification;
+    }
+
+    let timer = critical_timers.entry(system_adm.system_id).or_insert(timer);
+
+    let reping_seconds = reping_seconds?;
+
+    if now.saturating_sub(timer.last_ping) < reping_seconds {
+        return None;
+    }
+
+    timer.last_ping = now;
+
+    Some(BotNotification::NotifyAdmStillCritical(
+        system_adm,
+        now.saturating_sub(timer.since),
+    ))
+}
+
+fn digest_hour(digest_hour: Option<&str>) -> Option<u64> {
+    match digest_hour.map(str::parse::<u64>) {
+        Some(Ok(digest_hour)) if digest_hour < 24 => Some(digest_hour),
+        Some(Ok(digest_hour)) => {
+            tracing::warn!(digest_hour, "adm digest hour out of range, digest disabled");
+            None
+        }
+        Some(Err(err)) => {
+            tracing::warn!(?err, "invalid adm digest hour, digest disabled");
+            None
+        }
+        None => None,
+    }
+}
+
+fn digest_due(now: u64, digest_hour: u64, last_digest_day: Option<u64>) -> bool {
+    let day = now / SECONDS_PER_DAY;
+    let hour = (now % SECONDS_PER_DAY) / SECONDS_PER_HOUR;
+
+    hour == digest_hour && last_digest_day != Some(day)
+}
+
+fn load_history(path: &PathBuf) -> anyhow::Result<HashMap<EsiID, Status>> {
+    let json_data = std::fs::read_to_string(path)?;
+
+    Ok(serde_json::from_str(&json_data)?)
+}
+
+fn adm_notification(system_adm: SystemAdm, prev_status: Option<Status>) -> Option<BotNotification> {
+    match (system_adm.status, prev_status) {
+        (Status::Warning(_), Some(Status::Good(_))) => Some(BotNotification::NotifyAdm(system_adm)),
+        (Status::Critical(_), Some(Status::Warning(_))) => {
+            Some(BotNotification::NotifyAdm(system_adm))
+        }
+        (Status::Warning(_), None) => Some(BotNotification::NotifyAdm(system_adm)),
+        (Status::Critical(_), None) => Some(BotNotification::NotifyAdm(system_adm)),
+        (Status::Good(_), Some(Status::Warning(_) | Status::Critical(_))) => {
+            Some(BotNotification::NotifyAdmRecovered(system_adm))
+        }
+        (_, _) => None,
+    }
+}
+
+fn lost_systems(previous: &HashMap<EsiID, Status>, held: &HashSet<EsiID>) -> Vec<EsiID> {
+    let mut lost: Vec<EsiID> = previous
+        .keys()
+        .filter(|system_id| !held.contains(system_id))
+        .copied()
+        .collect();
+
+    lost.sort_unstable();
+    lost
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        path::PathBuf,
+        sync::Arc,
+        time::Duration,
+    };
+
+    use reqwest::Url;
+    use tracing_test::traced_test;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::{
+        bot::BotNotification,
+        esi::{Esi, EsiClient},
+        health::HealthState,
+        services::{
+            adm_configuration::AdmConfiguration,
+            adm_service::{AdmService, SovStructureType, Status, SystemAdm},
+            information_service::InformationService,
+        },
+        shutdown,
+    };
+
+    use super::{
+        adm_notification, confirmed_status, critical_reping, debounce_polls, digest_due,
+        digest_hour, lost_systems, poll_interval, retry_backoff, track_critical,
+        AdmNotificationService, MAX_RETRY_BACKOFF, MIN_RETRY_BACKOFF, SECONDS_PER_DAY,
+        SECONDS_PER_HOUR,
+    };
+
+    #[cfg(feature = "adm-history")]
+    use crate::services::adm_history::AdmHistoryStore;
+
+    const ALLIANCE_ID: u64 = 99000001;
+
+    async fn critical_system_adm_service(server: &MockServer) -> AdmService {
+        adm_service(
+            server,
+            serde_json::json!([{
+                "alliance_id": ALLIANCE_ID,
+                "solar_system_id": 30000142,
+                "structure_id": 1,
+                "structure_type_id": 32458,
+                "vulnerability_occupancy_level": 0.5
+            }]),
+        )
+        .await
+    }
+
+    async fn adm_service(
+        server: &MockServer,
+        sovereignty_structures: serde_json::Value,
+    ) -> AdmService {
+        Mock::given(method("GET"))
+            .and(path("/sovereignty/structures/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(sovereignty_structures))
+            .mount(server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/universe/systems/30000142/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "system_id": 30000142,
+                "constellation_id": 20000020,
+                "name": "Jita",
+                "security_status": 0.9
+            })))
+            .mount(server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi: Arc<dyn EsiClient> = Arc::new(
+            Esi::new()
+                .with_base_url(base_url)
+                .with_default_cache_ttl(Duration::ZERO),
+        );
+        let information = InformationService::new(esi.clone());
+        let configuration = AdmConfiguration::load_configuration_from(
+            std::env::temp_dir().join("alliance_squawk_missing_adm.toml"),
+        )
+        .await
+        .unwrap();
+
+        AdmService::new(
+            esi,
+            ALLIANCE_ID,
+            HashSet::from([SovStructureType::IHub]),
+            information,
+            configuration,
+        )
+    }
+
+    fn history_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("alliance_squawk_{}.json", name))
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_notification_recovered_from_critical() {
+        let notification = adm_notification(
+            SystemAdm::for_test(30000142, Status::Good(5.0)),
+            Some(Status::Critical(1.0)),
+        );
+
+        assert!(matches!(
+            notification,
+            Some(BotNotification::NotifyAdmRecovered(_))
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_notification_recovered_from_warning() {
+        let notification = adm_notification(
+            SystemAdm::for_test(30000142, Status::Good(5.0)),
+            Some(Status::Warning(3.1)),
+        );
+
+        assert!(matches!(
+            notification,
+            Some(BotNotification::NotifyAdmRecovered(_))
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn adm_notification_good_stays_silent() {
+        let notification = adm_notification(
+            SystemAdm::for_test(30000142, Status::Good(5.0)),
+            Some(Status::Good(4.0)),
+        );
+
+        assert!(notification.is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_adm_notifications_uses_persisted_history() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_seeded_test");
+        std::fs::write(&path, r#"{"30000142":{"Critical":0.5}}"#).unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        service.send_adm_notifications().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn startup_report_fires_once() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_startup_report_test");
+        std::fs::write(&path, r#"{"30000142":{"Critical":0.5}}"#).unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+        service.startup_report = true;
+
+        service.poll_adm().await;
+        service.poll_adm().await;
+        std::fs::remove_file(&path).unwrap();
+
+        let mut reports = vec![];
+
+        while let Ok(notification) = receiver.try_recv() {
+            reports.push(notification);
+        }
+
+        assert!(reports.len() == 1);
+        assert!(matches!(
+            &reports[0],
+            BotNotification::AdmStartupReport(system_adms) if system_adms.len() == 1
+        ));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_adm_notifications_saves_history() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_saved_test");
+        let _ = std::fs::remove_file(&path);
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        service.send_adm_notifications().await.unwrap();
+
+        let history = super::load_history(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyAdm(_))
+        ));
+        assert!(history.get(&30000142) == Some(&Status::Critical(0.5)));
+    }
+
+    #[cfg(feature = "adm-history")]
+    #[traced_test]
+    #[tokio::test]
+    async fn send_adm_notifications_records_adm_history() {
+        let server = MockServer::start().await;
+        let history_store = AdmHistoryStore::open_in_memory().unwrap();
+        let adm = critical_system_adm_service(&server)
+            .await
+            .with_history_store(history_store.clone());
+
+        let path = history_path("adm_history_db_test");
+
+        let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm.clone(), sender, path.clone(), HealthState::shared())
+                .with_history_store(history_store);
+
+        service.send_adm_notifications().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let readings = adm.adm_history(30000142, 0).unwrap();
+
+        assert!(readings.len() == 1);
+        assert!(readings[0].status == Status::Critical(0.5));
+    }
+
+    #[traced_test]
+    #[test]
+    fn poll_interval_defaults_and_clamps() {
+        assert!(poll_interval(None) == Duration::from_secs(3600));
+        assert!(poll_interval(Some("900")) == Duration::from_secs(900));
+        assert!(poll_interval(Some("60")) == Duration::from_secs(300));
+        assert!(poll_interval(Some("hourly")) == Duration::from_secs(3600));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn with_poll_interval_sets_poll_health() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+        let health = HealthState::shared();
+
+        let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let service = AdmNotificationService::with_poll_interval(
+            adm,
+            sender,
+            history_path("adm_history_poll_test"),
+            health.clone(),
+            Duration::from_secs(600),
+        );
+
+        assert!(service.poll_interval == Duration::from_secs(600));
+        assert!(health.read().unwrap().adm.interval_seconds == 600);
+    }
+
+    #[traced_test]
+    #[test]
+    fn lost_systems_reports_disappeared_systems() {
+        let previous = HashMap::from([
+            (30000142, Status::Good(4.0)),
+            (30000144, Status::Critical(0.5)),
+        ]);
+        let held = HashSet::from([30000142]);
+
+        assert!(lost_systems(&previous, &held) == vec![30000144]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn lost_systems_ignores_appeared_systems() {
+        let previous = HashMap::from([(30000142, Status::Good(4.0))]);
+        let held = HashSet::from([30000142, 30000144]);
+
+        assert!(lost_systems(&previous, &held).is_empty());
+        assert!(lost_systems(&HashMap::new(), &held).is_empty());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_adm_notifications_reports_sov_lost() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_sov_lost_test");
+        std::fs::write(
+            &path,
+            r#"{"30000142":{"Critical":0.5},"30000144":{"Good":4.0}}"#,
+        )
+        .unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        service.send_adm_notifications().await.unwrap();
+
+        let history = super::load_history(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifySovLost(30000144))
+        ));
+        assert!(!history.contains_key(&30000144));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_adm_notifications_keeps_sov_when_system_lookup_fails() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/universe/systems/30000144/"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let adm = adm_service(
+            &server,
+            serde_json::json!([
+                {
+                    "alliance_id": ALLIANCE_ID,
+                    "solar_system_id": 30000142,
+                    "structure_id": 1,
+                    "structure_type_id": 32458,
+                    "vulnerability_occupancy_level": 0.5
+                },
+                {
+                    "alliance_id": ALLIANCE_ID,
+                    "solar_system_id": 30000144,
+                    "structure_id": 2,
+                    "structure_type_id": 32458,
+                    "vulnerability_occupancy_level": 4.0
+                }
+            ]),
+        )
+        .await;
+
+        let path = history_path("adm_history_system_lookup_failed_test");
+        std::fs::write(
+            &path,
+            r#"{"30000142":{"Critical":0.5},"30000144":{"Good":4.0}}"#,
+        )
+        .unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        service.send_adm_notifications().await.unwrap();
+
+        let history = super::load_history(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(receiver.try_recv().is_err());
+        assert!(history.contains_key(&30000144));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn toggling_tcus_resets_history_without_notifying() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/universe/systems/30000144/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "system_id": 30000144,
+                "constellation_id": 20000020,
+                "name": "Perimeter",
+                "security_status": 0.9
+            })))
+            .mount(&server)
+            .await;
+
+        let adm = adm_service(
+            &server,
+            serde_json::json!([
+                {
+                    "alliance_id": ALLIANCE_ID,
+                    "solar_system_id": 30000142,
+                    "structure_id": 1,
+                    "structure_type_id": 32458,
+                    "vulnerability_occupancy_level": 0.5
+                },
+                {
+                    "alliance_id": ALLIANCE_ID,
+                    "solar_system_id": 30000144,
+                    "structure_id": 2,
+                    "structure_type_id": 32226,
+                    "vulnerability_occupancy_level": 0.5
+                }
+            ]),
+        )
+        .await;
+        adm.set_include_tcus(true);
+
+        let path = history_path("adm_history_toggle_tcus_test");
+        std::fs::write(
+            &path,
+            r#"{"30000142":{"Critical":0.5},"30000144":{"Critical":0.5}}"#,
+        )
+        .unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm.clone(), sender, path.clone(), HealthState::shared());
+
+        service.send_adm_notifications().await.unwrap();
+
+        adm.set_include_tcus(false);
+        service.send_adm_notifications().await.unwrap();
+
+        assert!(receiver.try_recv().is_err());
+        assert!(!super::load_history(&path).unwrap().contains_key(&30000144));
+
+        adm.set_include_tcus(true);
+        service.send_adm_notifications().await.unwrap();
+
+        let history = super::load_history(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(receiver.try_recv().is_err());
+        assert!(history.contains_key(&30000144));
+    }
+
+    #[traced_test]
+    #[test]
+    fn digest_hour_parsing() {
+        assert!(digest_hour(None).is_none());
+        assert!(digest_hour(Some("9")) == Some(9));
+        assert!(digest_hour(Some("24")).is_none());
+        assert!(digest_hour(Some("noon")).is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn digest_due_fires_once_per_day() {
+        let day = 19_000;
+        let nine_am = day * SECONDS_PER_DAY + 9 * SECONDS_PER_HOUR;
+
+        assert!(!digest_due(nine_am - 1, 9, None));
+        assert!(digest_due(nine_am, 9, None));
+        assert!(digest_due(nine_am + 1800, 9, Some(day - 1)));
+        assert!(!digest_due(nine_am + 1800, 9, Some(day)));
+        assert!(!digest_due(nine_am + SECONDS_PER_HOUR, 9, None));
+    }
+
+    #[traced_test]
+    #[test]
+    fn digest_due_across_day_boundary() {
+        let day = 19_000;
+        let midnight = (day + 1) * SECONDS_PER_DAY;
+
+        assert!(!digest_due(midnight - 1, 0, Some(day)));
+        assert!(digest_due(midnight, 0, Some(day)));
+        assert!(!digest_due(midnight + 60, 0, Some(day + 1)));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn run_returns_after_shutdown() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_shutdown_test");
+
+        let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        let (shutdown_sender, shutdown_receiver) = shutdown::channel();
+        shutdown_sender.send(true).unwrap();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), service.run(shutdown_receiver)).await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Ok(Ok(()))));
+    }
+
+    #[traced_test]
+    #[test]
+    fn retry_backoff_doubles_up_to_max() {
+        assert!(retry_backoff(None) == MIN_RETRY_BACKOFF);
+        assert!(retry_backoff(Some(MIN_RETRY_BACKOFF)) == MIN_RETRY_BACKOFF * 2);
+        assert!(retry_backoff(Some(MAX_RETRY_BACKOFF)) == MAX_RETRY_BACKOFF);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn closed_notification_channel_stops_service() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_backoff_test");
+        let _ = std::fs::remove_file(&path);
+
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        drop(receiver);
+
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        service.poll_adm().await;
+
+        assert!(service.retry_backoff.is_none());
+        assert!(service.retry_at.is_none());
+        assert!(logs_contain("notification channel closed, not retrying"));
+
+        let (_shutdown_sender, shutdown_receiver) = shutdown::channel();
+        let result = tokio::time::timeout(Duration::from_secs(1), service.run(shutdown_receiver))
+            .await
+            .expect("service stops without a shutdown request");
+        let _ = std::fs::remove_file(&path);
+
+        assert!(result.is_ok());
+        assert!(logs_contain("stopping adm notification service"));
+    }
+
+    async fn sovereignty_requests(server: &MockServer) -> usize {
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.path() == "/sovereignty/structures/")
+            .count()
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn refresh_request_polls_immediately() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_refresh_test");
+
+        let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let (refresh_sender, refresh_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared())
+                .with_refresh_requests(refresh_receiver);
+
+        let (shutdown_sender, shutdown_receiver) = shutdown::channel();
+        let handle = tokio::spawn(async move { service.run(shutdown_receiver).await });
+
+        let polled = |count| {
+            let server = &server;
+            async move {
+                while sovereignty_requests(server).await < count {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(5), polled(1))
+            .await
+            .unwrap();
+
+        refresh_sender.send(()).unwrap();
+
+        let refreshed = tokio::time::timeout(Duration::from_secs(5), polled(2)).await;
+
+        shutdown_sender.send(true).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(refreshed.is_ok());
+        assert!(sovereignty_requests(&server).await == 2);
+    }
+
+    #[traced_test]
+    #[test]
+    fn debounce_polls_defaults_and_minimum() {
+        assert!(debounce_polls(None) == 2);
+        assert!(debounce_polls(Some("3")) == 3);
+        assert!(debounce_polls(Some("0")) == 1);
+        assert!(debounce_polls(Some("twice")) == 2);
+    }
+
+    #[traced_test]
+    #[test]
+    fn confirmed_status_ignores_single_poll_blip() {
+        let (status, pending) =
+            confirmed_status(Some(Status::Good(2.0)), None, Status::Warning(1.9), 2);
+
+        assert!(status == Status::Good(2.0));
+        assert!(pending == Some((Status::Warning(1.9), 1)));
+
+        let (status, pending) = confirmed_status(Some(status), pending, Status::Good(2.1), 2);
+
+        assert!(status == Status::Good(2.1));
+        assert!(pending.is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn confirmed_status_accepts_sustained_change() {
+        let (status, pending) =
+            confirmed_status(Some(Status::Good(2.0)), None, Status::Warning(1.9), 2);
+        let (status, pending) = confirmed_status(Some(status), pending, Status::Warning(1.8), 2);
+
+        assert!(status == Status::Warning(1.8));
+        assert!(pending.is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn confirmed_status_without_history_is_immediate() {
+        let (status, pending) = confirmed_status(None, None, Status::Critical(0.5), 2);
+
+        assert!(status == Status::Critical(0.5));
+        assert!(pending.is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn send_adm_notifications_debounces_status_change() {
+        let server = MockServer::start().await;
+        let adm = critical_system_adm_service(&server).await;
+
+        let path = history_path("adm_history_debounce_test");
+        std::fs::write(&path, r#"{"30000142":{"Warning":1.5}}"#).unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service =
+            AdmNotificationService::new(adm, sender, path.clone(), HealthState::shared());
+
+        service.send_adm_notifications().await.unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        service.send_adm_notifications().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyAdm(_))
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn critical_reping_parsing() {
+        assert!(critical_reping(Some("4")) == Some(4 * SECONDS_PER_HOUR));
+        assert!(critical_reping(Some("0")).is_none());
+        assert!(critical_reping(Some("soon")).is_none());
+        assert!(critical_reping(None).is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn track_critical_repings_until_recovered() {
+        let mut critical_timers = HashMap::new();
+        let reping = Some(4 * SECONDS_PER_HOUR);
+        let critical = SystemAdm::for_test(30000142, Status::Critical(0.5));
+
+        let entered = track_critical(
+            &mut critical_timers,
+            critical,
+            adm_notification(critical, Some(Status::Warning(1.1))),
+            0,
+            reping,
+        );
+        assert!(matches!(entered, Some(BotNotification::NotifyAdm(_))));
+
+        let polls: Vec<_> = (1..=9)
+            .map(|hour| {
+                track_critical(
+                    &mut critical_timers,
+                    critical,
+                    None,
+                    hour * SECONDS_PER_HOUR,
+                    reping,
+                )
+            })
+            .collect();
+
+        assert!(polls
+            .iter()
+            .enumerate()
+            .all(|(index, notification)| match index + 1 {
+                4 => matches!(
+                    notification,
+                    Some(BotNotification::NotifyAdmStillCritical(_, seconds))
+                        if *seconds == 4 * SECONDS_PER_HOUR
+                ),
+                8 => matches!(
+                    notification,
+                    Some(BotNotification::NotifyAdmStillCritical(_, seconds))
+                        if *seconds == 8 * SECONDS_PER_HOUR
+                ),
+                _ => notification.is_none(),
+            }));
+
+        let recovered = SystemAdm::for_test(30000142, Status::Good(2.0));
+        let notification = track_critical(
+            &mut critical_timers,
+            recovered,
+            adm_notification(recovered, Some(Status::Critical(0.5))),
+            10 * SECONDS_PER_HOUR,
+            reping,
+        );
+
+        assert!(matches!(
+            notification,
+            Some(BotNotification::NotifyAdmRecovered(_))
+        ));
+        assert!(critical_timers.is_empty());
+        assert!(track_critical(
+            &mut critical_timers,
+            recovered,
+            None,
+            20 * SECONDS_PER_HOUR,
+            reping
+        )
+        .is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn track_critical_without_reping_stays_silent() {
+        let mut critical_timers = HashMap::new();
+        let critical = SystemAdm::for_test(30000142, Status::Critical(0.5));
 
-            tokio::time::sleep(Duration::from_millis(100)).await;
+        for hour in 0..48 {
+            assert!(track_critical(
+                &mut critical_timers,
+                critical,
+                None,
+                hour * SECONDS_PER_HOUR,
+                None
+            )
+            .is_none());
         }
     }
 }
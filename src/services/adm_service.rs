@@ -1,60 +1,366 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Display,
+    sync::{Arc, RwLock as SyncRwLock},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
 use crate::{
-    esi::{Esi, EsiID},
+    esi::{EsiClient, EsiID, SecurityClass, SovereigntyStructure},
     services::adm_configuration::Importance,
 };
 
+#[cfg(feature = "adm-history")]
+use super::adm_history::{AdmHistoryEntry, AdmHistoryStore};
 use super::{adm_configuration::AdmConfiguration, information_service::InformationService};
 
-const TCU_STRUCTURE_ID: EsiID = 32226;
+const TCU_STRUCTURE_TYPE_ID: EsiID = 32226;
+const IHUB_STRUCTURE_TYPE_ID: EsiID = 32458;
+
+const SOVEREIGNTY_CACHE_TTL: Duration = Duration::from_secs(300);
+
+type SovereigntyCache = Arc<RwLock<Option<(Instant, Vec<SovereigntyStructure>)>>>;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+struct AdmReading {
+    current: f32,
+    previous: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SovStructureType {
+    Tcu,
+    IHub,
+}
+
+impl SovStructureType {
+    pub fn from_type_id(structure_type_id: EsiID) -> Option<Self> {
+        match structure_type_id {
+            TCU_STRUCTURE_TYPE_ID => Some(SovStructureType::Tcu),
+            IHUB_STRUCTURE_TYPE_ID => Some(SovStructureType::IHub),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SovStructureType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SovStructureType::Tcu => write!(f, "TCU"),
+            SovStructureType::IHub => write!(f, "IHub"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Status {
     Good(f32),
     Warning(f32),
     Critical(f32),
 }
 
+impl Status {
+    pub fn adm(&self) -> f32 {
+        match self {
+            Status::Good(adm) | Status::Warning(adm) | Status::Critical(adm) => *adm,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    pub fn classify(previous_adm: Option<f32>, adm: f32) -> Trend {
+        match previous_adm {
+            Some(previous_adm) if adm > previous_adm => Trend::Rising,
+            Some(previous_adm) if adm < previous_adm => Trend::Falling,
+            _ => Trend::Steady,
+        }
+    }
+}
+
+impl Display for Trend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trend::Rising => write!(f, "▲"),
+            Trend::Falling => write!(f, "▼"),
+            Trend::Steady => write!(f, "▬"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum SystemImportance {
+    Configured(Importance),
+    Custom(f32, f32),
+    Default(Importance),
+}
+
+impl Display for SystemImportance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SystemImportance::Configured(importance) => write!(f, "{}", importance.name()),
+            SystemImportance::Custom(warning_threshold, critical_threshold) => write!(
+                f,
+                "Custom ({:.1} - {:.1})",
+                critical_threshold, warning_threshold
+            ),
+            SystemImportance::Default(importance) => write!(f, "{} (default)", importance.name()),
+        }
+    }
+}
+
+impl SystemImportance {
+    // Custom thresholds don't correspond to any importance level.
+    pub fn importance(&self) -> Option<Importance> {
+        match self {
+            SystemImportance::Configured(importance) | SystemImportance::Default(importance) => {
+                Some(*importance)
+            }
+            SystemImportance::Custom(..) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub struct SystemAdm {
     pub system_id: EsiID,
+    pub structure_type_id: EsiID,
     pub status: Status,
+    pub previous_adm: Option<f32>,
+    pub importance: SystemImportance,
+}
+
+impl SystemAdm {
+    // An IHub of default importance without ADM history.
+    #[cfg(test)]
+    pub(crate) fn for_test(system_id: EsiID, status: Status) -> SystemAdm {
+        SystemAdm {
+            system_id,
+            structure_type_id: 32458,
+            status,
+            previous_adm: None,
+            importance: SystemImportance::Default(Importance::Green),
+        }
+    }
+
+    pub fn structure_label(&self) -> String {
+        match SovStructureType::from_type_id(self.structure_type_id) {
+            Some(structure_type) => structure_type.to_string(),
+            None => format!("Structure {}", self.structure_type_id),
+        }
+    }
+
+    pub fn trend(&self) -> Trend {
+        Trend::classify(self.previous_adm, self.status.adm())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdmSnapshot {
+    pub systems: Vec<SystemAdm>,
+    // Age of the sovereignty data when ESI was unavailable and the last good fetch was served.
+    pub stale_for: Option<Duration>,
 }
 
 #[derive(Clone)]
 pub struct AdmService {
-    esi: Esi,
+    esi: Arc<dyn EsiClient>,
     alliance_id: EsiID,
-    include_tcus: bool,
+    // Shared so commands can change which structures are included while running.
+    structure_types: Arc<SyncRwLock<HashSet<SovStructureType>>>,
     information: InformationService,
     configuration: AdmConfiguration,
+    default_importance: Importance,
+    sovereignty_cache: SovereigntyCache,
+    adm_readings: Arc<RwLock<HashMap<EsiID, AdmReading>>>,
+    #[cfg(feature = "adm-history")]
+    history_store: Option<AdmHistoryStore>,
 }
 
 impl AdmService {
     pub fn new(
-        esi: Esi,
+        esi: Arc<dyn EsiClient>,
         alliance_id: EsiID,
-        include_tcus: bool,
+        structure_types: HashSet<SovStructureType>,
         information: InformationService,
         configuration: AdmConfiguration,
     ) -> AdmService {
         AdmService {
             esi,
             alliance_id,
-            include_tcus,
+            structure_types: Arc::new(SyncRwLock::new(structure_types)),
             information,
             configuration,
+            default_importance: Importance::Green,
+            sovereignty_cache: Default::default(),
+            adm_readings: Default::default(),
+            #[cfg(feature = "adm-history")]
+            history_store: None,
+        }
+    }
+
+    // Thresholds used for systems without an importance in the ADM configuration.
+    pub fn with_default_importance(mut self, default_importance: Importance) -> Self {
+        self.default_importance = default_importance;
+        self
+    }
+
+    #[cfg(feature = "adm-history")]
+    pub fn with_history_store(mut self, history_store: AdmHistoryStore) -> Self {
+        self.history_store = Some(history_store);
+        self
+    }
+
+    #[cfg(feature = "adm-history")]
+    pub fn adm_history(
+        &self,
+        system_id: EsiID,
+        since: u64,
+    ) -> anyhow::Result<Vec<AdmHistoryEntry>> {
+        match &self.history_store {
+            Some(history_store) => history_store.readings(system_id, since),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub async fn pinned_systems(&self) -> BTreeSet<String> {
+        self.configuration.pinned_systems().await
+    }
+
+    pub async fn system_importance(&self, system_name: &str) -> SystemImportance {
+        if let Some((warning_threshold, critical_threshold)) =
+            self.configuration.get_custom_thresholds(system_name).await
+        {
+            return SystemImportance::Custom(warning_threshold, critical_threshold);
+        }
+
+        match self.configuration.get_importance(system_name).await {
+            Some(importance) => SystemImportance::Configured(importance),
+            None => SystemImportance::Default(self.default_importance),
+        }
+    }
+
+    async fn get_sovereignty_structures(
+        &self,
+        force_refresh: bool,
+    ) -> anyhow::Result<Vec<SovereigntyStructure>> {
+        let mut cache = self.sovereignty_cache.write().await;
+
+        match cache.as_ref() {
+            Some((fetched, sovereignty_structures))
+                if !force_refresh && fetched.elapsed() < SOVEREIGNTY_CACHE_TTL =>
+            {
+                tracing::debug!("using cached sovereignty structures");
+
+                Ok(sovereignty_structures.clone())
+            }
+            _ => {
+                let sovereignty_structures = self.esi.get_sovereignty_structures().await?;
+
+                *cache = Some((Instant::now(), sovereignty_structures.clone()));
+
+                Ok(sovereignty_structures)
+            }
         }
     }
 
+    async fn get_sovereignty_structures_or_stale(
+        &self,
+    ) -> anyhow::Result<(Vec<SovereigntyStructure>, Option<Duration>)> {
+        match self.get_sovereignty_structures(false).await {
+            Ok(sovereignty_structures) => Ok((sovereignty_structures, None)),
+            Err(err) => match self.sovereignty_cache.read().await.as_ref() {
+                Some((fetched, sovereignty_structures)) => {
+                    tracing::warn!(
+                        ?err,
+                        stale_seconds = fetched.elapsed().as_secs(),
+                        "esi unavailable, using stale sovereignty structures"
+                    );
+
+                    Ok((sovereignty_structures.clone(), Some(fetched.elapsed())))
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    pub async fn alliance_structures(&self) -> anyhow::Result<Vec<SovereigntyStructure>> {
+        Ok(self
+            .get_sovereignty_structures(false)
+            .await?
+            .into_iter()
+            .filter(|sovereignty_structure| {
+                sovereignty_structure.alliance_id == self.alliance_id
+                    && self.includes_structure_type(sovereignty_structure.structure_type_id)
+            })
+            .collect())
+    }
+
+    // Security classes of the systems an alliance holds any sovereignty structure in.
+    pub async fn sov_security_classes(
+        &self,
+        alliance_id: EsiID,
+    ) -> anyhow::Result<HashSet<SecurityClass>> {
+        let system_ids: HashSet<_> = self
+            .get_sovereignty_structures(false)
+            .await?
+            .into_iter()
+            .filter(|sovereignty_structure| sovereignty_structure.alliance_id == alliance_id)
+            .map(|sovereignty_structure| sovereignty_structure.solar_system_id)
+            .collect();
+
+        let mut security_classes = HashSet::new();
+
+        for system_id in system_ids {
+            let system = self.information.get_system(system_id).await?;
+
+            security_classes.insert(system.security_class());
+        }
+
+        Ok(security_classes)
+    }
+
     pub async fn get_adm_status(&self) -> anyhow::Result<Vec<SystemAdm>> {
-        let sovereignty_structures = self.esi.get_sovereignty_structures().await?;
+        self.adm_status(false).await
+    }
 
+    pub async fn refresh_adm_status(&self) -> anyhow::Result<Vec<SystemAdm>> {
+        self.adm_status(true).await
+    }
+
+    // Falls back to the last successful sovereignty fetch when ESI is unavailable, for reports
+    // where stale data is better than none. Notifications always use fresh data.
+    pub async fn get_adm_snapshot(&self) -> anyhow::Result<AdmSnapshot> {
+        let (sovereignty_structures, stale_for) =
+            self.get_sovereignty_structures_or_stale().await?;
+
+        Ok(AdmSnapshot {
+            systems: self.system_adms(&sovereignty_structures).await,
+            stale_for,
+        })
+    }
+
+    async fn adm_status(&self, force_refresh: bool) -> anyhow::Result<Vec<SystemAdm>> {
+        let sovereignty_structures = self.get_sovereignty_structures(force_refresh).await?;
+
+        Ok(self.system_adms(&sovereignty_structures).await)
+    }
+
+    async fn system_adms(&self, sovereignty_structures: &[SovereigntyStructure]) -> Vec<SystemAdm> {
         let sovereignty_structures: Vec<_> = sovereignty_structures
             .iter()
             .filter(|sovereignty_structure| {
                 sovereignty_structure.alliance_id == self.alliance_id
-                    && (self.include_tcus
-                        || sovereignty_structure.structure_type_id != TCU_STRUCTURE_ID)
+                    && self.includes_structure_type(sovereignty_structure.structure_type_id)
                     && sovereignty_structure
                         .vulnerability_occupancy_level
                         .is_some()
@@ -77,14 +383,14 @@ impl AdmService {
                 .get_system(sov_structure.solar_system_id)
                 .await
             {
-                let importance = self
+                let (adm_warning_threshold, adm_critical_threshold) = self
                     .configuration
-                    .get_importance(&system.name)
+                    .get_thresholds(&system.name)
                     .await
-                    .unwrap_or(Importance::Green);
-
-                let adm_warning_threshold = importance.warning_threshold();
-                let adm_critical_threshold = importance.critical_threshold();
+                    .unwrap_or((
+                        self.default_importance.warning_threshold(),
+                        self.default_importance.critical_threshold(),
+                    ));
 
                 let status = AdmService::select_adm_status(
                     adm,
@@ -100,7 +406,10 @@ impl AdmService {
 
                 systems.push(SystemAdm {
                     system_id: sov_structure.solar_system_id,
+                    structure_type_id: sov_structure.structure_type_id,
                     status,
+                    previous_adm: None,
+                    importance: self.system_importance(&system.name).await,
                 });
             } else {
                 tracing::error!(
@@ -110,12 +419,54 @@ impl AdmService {
             }
         }
 
-        Ok(systems)
+        let mut systems = dedup_system_adms(systems);
+
+        let mut readings = self.adm_readings.write().await;
+
+        for system_adm in systems.iter_mut() {
+            system_adm.previous_adm =
+                record_adm_reading(&mut readings, system_adm.system_id, system_adm.status.adm());
+        }
+
+        systems
+    }
+
+    fn includes_structure_type(&self, structure_type_id: EsiID) -> bool {
+        let structure_types = self
+            .structure_types
+            .read()
+            .expect("structure types lock poisoned");
+
+        includes_structure_type(&structure_types, structure_type_id)
+    }
+
+    pub fn include_tcus(&self) -> bool {
+        self.structure_types
+            .read()
+            .expect("structure types lock poisoned")
+            .contains(&SovStructureType::Tcu)
     }
 
+    pub fn set_include_tcus(&self, include_tcus: bool) {
+        let mut structure_types = self
+            .structure_types
+            .write()
+            .expect("structure types lock poisoned");
+
+        if include_tcus {
+            structure_types.insert(SovStructureType::Tcu);
+        } else {
+            structure_types.remove(&SovStructureType::Tcu);
+        }
+
+        tracing::info!(include_tcus, "changed included sov structures");
+    }
+
+    // Both thresholds are inclusive: an ADM equal to a threshold is in that band, and
+    // critical wins when the bands overlap.
     fn select_adm_status(adm: f32, warning_threshold: f32, critical_threshold: f32) -> Status {
-        let is_critical_state = adm < critical_threshold;
-        let is_warning_state = adm < warning_threshold;
+        let is_critical_state = adm <= critical_threshold;
+        let is_warning_state = adm <= warning_threshold;
 
         match (is_warning_state, is_critical_state) {
             (_, true) => Status::Critical(adm),
@@ -125,11 +476,108 @@ impl AdmService {
     }
 }
 
+// Only a changed reading moves the current ADM into previous, so re-reading cached
+// sovereignty structures doesn't flatten the trend.
+fn record_adm_reading(
+    readings: &mut HashMap<EsiID, AdmReading>,
+    system_id: EsiID,
+    adm: f32,
+) -> Option<f32> {
+    let reading = readings.entry(system_id).or_insert(AdmReading {
+        current: adm,
+        previous: None,
+    });
+
+    if reading.current != adm {
+        reading.previous = Some(reading.current);
+        reading.current = adm;
+    }
+
+    reading.previous
+}
+
+fn dedup_system_adms(system_adms: Vec<SystemAdm>) -> Vec<SystemAdm> {
+    let mut positions: HashMap<EsiID, usize> = HashMap::new();
+    let mut deduped: Vec<SystemAdm> = vec![];
+
+    for system_adm in system_adms {
+        match positions.get(&system_adm.system_id) {
+            Some(&position) => {
+                if system_adm.status.adm() < deduped[position].status.adm() {
+                    deduped[position] = system_adm;
+                }
+            }
+            None => {
+                positions.insert(system_adm.system_id, deduped.len());
+                deduped.push(system_adm);
+            }
+        }
+    }
+
+    deduped
+}
+
+fn includes_structure_type(
+    structure_types: &HashSet<SovStructureType>,
+    structure_type_id: EsiID,
+) -> bool {
+    SovStructureType::from_type_id(structure_type_id)
+        .is_some_and(|structure_type| structure_types.contains(&structure_type))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{atomic::Ordering, Arc},
+        time::Instant,
+    };
+
     use tracing_test::traced_test;
 
-    use super::{AdmService, Status};
+    use crate::{
+        esi::{MockEsi, SovereigntyStructure, System},
+        services::{
+            adm_configuration::{AdmConfiguration, Importance},
+            information_service::InformationService,
+        },
+    };
+
+    use super::{
+        dedup_system_adms, includes_structure_type, record_adm_reading, AdmService,
+        SovStructureType, Status, SystemAdm, SystemImportance, Trend, SOVEREIGNTY_CACHE_TTL,
+    };
+
+    const ALLIANCE_ID: u64 = 99000001;
+
+    fn sovereignty_structure(
+        alliance_id: u64,
+        solar_system_id: u64,
+        structure_type_id: u64,
+        vulnerability_occupancy_level: Option<f32>,
+    ) -> SovereigntyStructure {
+        SovereigntyStructure {
+            alliance_id,
+            solar_system_id,
+            structure_id: solar_system_id + structure_type_id,
+            structure_type_id,
+            vulnerability_occupancy_level,
+            vulnerable_end_time: None,
+            vulnerable_start_time: None,
+        }
+    }
+
+    fn system(system_id: u64, name: &str) -> (u64, System) {
+        (
+            system_id,
+            System {
+                system_id,
+                constellation_id: 20000020,
+                name: name.to_string(),
+                security_status: -0.5,
+            },
+        )
+    }
 
     #[traced_test]
     #[test]
@@ -154,4 +602,287 @@ mod tests {
 
         assert!(status == Status::Warning(1.2));
     }
+
+    #[traced_test]
+    #[test]
+    fn select_adm_status_boundaries() {
+        let status = |adm| AdmService::select_adm_status(adm, 1.2, 1.0);
+
+        assert!(status(0.9) == Status::Critical(0.9));
+        assert!(status(1.0) == Status::Critical(1.0));
+        assert!(status(1.1) == Status::Warning(1.1));
+        assert!(status(1.2) == Status::Warning(1.2));
+        assert!(status(1.3) == Status::Good(1.3));
+    }
+
+    #[traced_test]
+    #[test]
+    fn select_adm_status_equal_thresholds() {
+        let status = |adm| AdmService::select_adm_status(adm, 1.0, 1.0);
+
+        assert!(status(1.0) == Status::Critical(1.0));
+        assert!(status(1.1) == Status::Good(1.1));
+    }
+
+    #[traced_test]
+    #[test]
+    fn sov_structure_type_from_type_id() {
+        assert!(SovStructureType::from_type_id(32458) == Some(SovStructureType::IHub));
+        assert!(SovStructureType::from_type_id(32226) == Some(SovStructureType::Tcu));
+        assert!(SovStructureType::from_type_id(1).is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn includes_only_allowed_structure_types() {
+        let ihubs = HashSet::from([SovStructureType::IHub]);
+        let all = HashSet::from([SovStructureType::IHub, SovStructureType::Tcu]);
+
+        assert!(includes_structure_type(&ihubs, 32458));
+        assert!(!includes_structure_type(&ihubs, 32226));
+        assert!(includes_structure_type(&all, 32226));
+        assert!(!includes_structure_type(&all, 1));
+        assert!(!includes_structure_type(&HashSet::new(), 32458));
+    }
+
+    #[traced_test]
+    #[test]
+    fn system_adm_structure_label() {
+        let system_adm = |structure_type_id| SystemAdm {
+            structure_type_id,
+            ..SystemAdm::for_test(30000142, Status::Good(4.0))
+        };
+
+        assert!(system_adm(32458).structure_label() == "IHub");
+        assert!(system_adm(32226).structure_label() == "TCU");
+        assert!(system_adm(1).structure_label() == "Structure 1");
+    }
+
+    #[traced_test]
+    #[test]
+    fn dedup_system_adms_keeps_worst_status() {
+        let system_adms = dedup_system_adms(vec![
+            SystemAdm::for_test(1, Status::Good(4.0)),
+            SystemAdm::for_test(2, Status::Warning(1.5)),
+            SystemAdm::for_test(1, Status::Critical(0.8)),
+            SystemAdm::for_test(2, Status::Good(3.0)),
+        ]);
+
+        assert!(
+            system_adms
+                == vec![
+                    SystemAdm::for_test(1, Status::Critical(0.8)),
+                    SystemAdm::for_test(2, Status::Warning(1.5)),
+                ]
+        );
+    }
+
+    #[traced_test]
+    #[test]
+    fn dedup_system_adms_keeps_unique_systems() {
+        let system_adms = vec![
+            SystemAdm::for_test(1, Status::Good(4.0)),
+            SystemAdm::for_test(2, Status::Warning(1.5)),
+        ];
+
+        assert!(dedup_system_adms(system_adms.clone()) == system_adms);
+    }
+
+    async fn adm_service(esi: Arc<MockEsi>) -> AdmService {
+        let information = InformationService::new(esi.clone());
+        let configuration = AdmConfiguration::load_configuration_from(
+            std::env::temp_dir().join("alliance_squawk_missing_adm.toml"),
+        )
+        .await
+        .unwrap();
+
+        AdmService::new(
+            esi,
+            ALLIANCE_ID,
+            HashSet::from([SovStructureType::IHub]),
+            information,
+            configuration,
+        )
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_adm_status_with_mock_esi() {
+        let esi = Arc::new(MockEsi {
+            systems: HashMap::from([
+                system(1, "1DQ1-A"),
+                system(2, "T5ZI-S"),
+                system(3, "GE-8JV"),
+            ]),
+            sovereignty_structures: vec![
+                sovereignty_structure(ALLIANCE_ID, 1, 32458, Some(0.8)),
+                sovereignty_structure(ALLIANCE_ID, 1, 32226, None),
+                sovereignty_structure(ALLIANCE_ID, 2, 32458, Some(1.1)),
+                sovereignty_structure(ALLIANCE_ID, 3, 32458, Some(5.0)),
+                sovereignty_structure(1354830081, 3, 32458, Some(0.1)),
+            ],
+            ..Default::default()
+        });
+
+        let adm = adm_service(esi).await;
+
+        let system_adms = adm.get_adm_status().await.unwrap();
+
+        assert!(system_adms.len() == 3);
+        assert!(matches!(system_adms[0].status, Status::Critical(_)));
+        assert!(matches!(system_adms[1].status, Status::Warning(_)));
+        assert!(matches!(system_adms[2].status, Status::Good(_)));
+        assert!(system_adms
+            .iter()
+            .all(|system_adm| system_adm.structure_type_id == 32458));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn set_include_tcus_changes_adm_status() {
+        let esi = Arc::new(MockEsi {
+            systems: HashMap::from([system(1, "1DQ1-A"), system(2, "T5ZI-S")]),
+            sovereignty_structures: vec![
+                sovereignty_structure(ALLIANCE_ID, 1, 32458, Some(4.0)),
+                sovereignty_structure(ALLIANCE_ID, 2, 32226, Some(0.8)),
+            ],
+            ..Default::default()
+        });
+
+        let adm = adm_service(esi).await;
+
+        assert!(!adm.include_tcus());
+        assert!(adm.get_adm_status().await.unwrap().len() == 1);
+
+        adm.clone().set_include_tcus(true);
+
+        let system_adms = adm.get_adm_status().await.unwrap();
+
+        assert!(adm.include_tcus());
+        assert!(system_adms.len() == 2);
+        assert!(system_adms
+            .iter()
+            .any(|system_adm| system_adm.system_id == 2 && system_adm.structure_type_id == 32226));
+
+        adm.set_include_tcus(false);
+
+        assert!(adm.get_adm_status().await.unwrap().len() == 1);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_adm_status_uses_default_importance() {
+        let esi = Arc::new(MockEsi {
+            systems: HashMap::from([system(1, "1DQ1-A"), system(2, "T5ZI-S")]),
+            sovereignty_structures: vec![
+                sovereignty_structure(ALLIANCE_ID, 1, 32458, Some(3.1)),
+                sovereignty_structure(ALLIANCE_ID, 2, 32458, Some(2.5)),
+            ],
+            ..Default::default()
+        });
+
+        let adm = adm_service(esi)
+            .await
+            .with_default_importance(Importance::Yellow);
+
+        let system_adms = adm.get_adm_status().await.unwrap();
+
+        assert!(system_adms.len() == 2);
+        assert!(system_adms.iter().all(
+            |system_adm| system_adm.importance == SystemImportance::Default(Importance::Yellow)
+        ));
+        assert!(system_adms
+            .iter()
+            .any(|system_adm| system_adm.system_id == 1
+                && matches!(system_adm.status, Status::Warning(_))));
+        assert!(system_adms
+            .iter()
+            .any(|system_adm| system_adm.system_id == 2
+                && matches!(system_adm.status, Status::Critical(_))));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_adm_status_caches_sovereignty_structures() {
+        let esi = Arc::new(MockEsi {
+            systems: HashMap::from([system(1, "1DQ1-A")]),
+            sovereignty_structures: vec![sovereignty_structure(ALLIANCE_ID, 1, 32458, Some(4.0))],
+            ..Default::default()
+        });
+
+        let adm = adm_service(esi.clone()).await;
+
+        adm.get_adm_status().await.unwrap();
+        adm.get_adm_status().await.unwrap();
+
+        assert!(esi.sovereignty_structure_requests.load(Ordering::Relaxed) == 1);
+
+        adm.refresh_adm_status().await.unwrap();
+
+        assert!(esi.sovereignty_structure_requests.load(Ordering::Relaxed) == 2);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_adm_snapshot_serves_stale_data_when_esi_unavailable() {
+        let esi = Arc::new(MockEsi {
+            systems: HashMap::from([system(1, "1DQ1-A")]),
+            sovereignty_structures: vec![sovereignty_structure(ALLIANCE_ID, 1, 32458, Some(0.8))],
+            ..Default::default()
+        });
+
+        let adm = adm_service(esi.clone()).await;
+
+        let snapshot = adm.get_adm_snapshot().await.unwrap();
+
+        assert!(snapshot.stale_for.is_none());
+
+        esi.sovereignty_unavailable.store(true, Ordering::Relaxed);
+
+        assert!(adm.refresh_adm_status().await.is_err());
+
+        if let Some((fetched, _)) = adm.sovereignty_cache.write().await.as_mut() {
+            *fetched = Instant::now()
+                .checked_sub(SOVEREIGNTY_CACHE_TTL * 2)
+                .unwrap();
+        }
+
+        let stale = adm.get_adm_snapshot().await.unwrap();
+
+        assert!(stale.stale_for.is_some());
+        assert!(stale.systems == snapshot.systems);
+        assert!(matches!(stale.systems[0].status, Status::Critical(_)));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_adm_snapshot_without_prior_fetch_fails() {
+        let esi = Arc::new(MockEsi::default());
+        esi.sovereignty_unavailable.store(true, Ordering::Relaxed);
+
+        let adm = adm_service(esi).await;
+
+        assert!(adm.get_adm_snapshot().await.is_err());
+    }
+
+    #[traced_test]
+    #[test]
+    fn trend_classify() {
+        assert!(Trend::classify(Some(2.0), 2.0) == Trend::Steady);
+        assert!(Trend::classify(Some(2.0), 2.4) == Trend::Rising);
+        assert!(Trend::classify(Some(2.0), 1.6) == Trend::Falling);
+        assert!(Trend::classify(None, 1.6) == Trend::Steady);
+    }
+
+    #[traced_test]
+    #[test]
+    fn record_adm_reading_keeps_last_changed_reading() {
+        let mut readings = HashMap::new();
+
+        assert!(record_adm_reading(&mut readings, 1, 2.0).is_none());
+        assert!(record_adm_reading(&mut readings, 1, 2.0).is_none());
+        assert!(record_adm_reading(&mut readings, 1, 1.8) == Some(2.0));
+        assert!(record_adm_reading(&mut readings, 1, 1.8) == Some(2.0));
+        assert!(record_adm_reading(&mut readings, 1, 2.1) == Some(1.8));
+    }
 }
@@ -0,0 +1,171 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::{fs, sync::RwLock};
+
+use super::adm_configuration::write_atomic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NotificationChannels {
+    pub corp: u64,
+    pub adm: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelConfig {
+    path: PathBuf,
+    channels: Arc<RwLock<NotificationChannels>>,
+}
+
+impl ChannelConfig {
+    pub fn new(path: impl AsRef<Path>, channels: NotificationChannels) -> ChannelConfig {
+        ChannelConfig {
+            path: path.as_ref().to_path_buf(),
+            channels: Arc::new(RwLock::new(channels)),
+        }
+    }
+
+    // Channels set at runtime are persisted and take precedence over `defaults`.
+    pub async fn load_from(
+        path: impl AsRef<Path>,
+        defaults: NotificationChannels,
+    ) -> anyhow::Result<ChannelConfig> {
+        let channels = if let Ok(json_data) = fs::read_to_string(path.as_ref()).await {
+            let channels: NotificationChannels = serde_json::from_str(&json_data)
+                .with_context(|| format!("couldn't parse {}", path.as_ref().display()))?;
+
+            warn_overridden(
+                path.as_ref(),
+                "NOTIFY_CORP_CHANNEL_ID",
+                defaults.corp,
+                channels.corp,
+            );
+            warn_overridden(
+                path.as_ref(),
+                "NOTIFY_ADM_CHANNEL_ID",
+                defaults.adm,
+                channels.adm,
+            );
+
+            channels
+        } else {
+            defaults
+        };
+
+        Ok(ChannelConfig::new(path, channels))
+    }
+
+    async fn save(&self, channels: &NotificationChannels) -> anyhow::Result<()> {
+        let json_data = serde_json::to_string(channels)?;
+
+        write_atomic(&self.path, &json_data).await
+    }
+
+    pub async fn channels(&self) -> NotificationChannels {
+        *self.channels.read().await
+    }
+
+    pub async fn set_corp_channel(&self, channel_id: u64) -> anyhow::Result<()> {
+        let mut channels = self.channels.write().await;
+
+        let updated = NotificationChannels {
+            corp: channel_id,
+            ..*channels
+        };
+
+        self.save(&updated).await?;
+        *channels = updated;
+
+        Ok(())
+    }
+
+    pub async fn set_adm_channel(&self, channel_id: u64) -> anyhow::Result<()> {
+        let mut channels = self.channels.write().await;
+
+        let updated = NotificationChannels {
+            adm: channel_id,
+            ..*channels
+        };
+
+        self.save(&updated).await?;
+        *channels = updated;
+
+        Ok(())
+    }
+}
+
+// A channel set with `/set_corp_channel` or `/set_adm_channel` keeps being used after the
+// environment variable is changed, which is easy to miss.
+fn warn_overridden(path: &Path, variable: &str, env_channel_id: u64, channel_id: u64) {
+    if env_channel_id != channel_id {
+        tracing::warn!(
+            path = %path.display(),
+            env_channel_id,
+            channel_id,
+            "`{}` is overridden by the persisted channel",
+            variable
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{ChannelConfig, NotificationChannels};
+
+    const DEFAULTS: NotificationChannels = NotificationChannels { corp: 1, adm: 2 };
+
+    #[traced_test]
+    #[tokio::test]
+    async fn set_channels_persists() {
+        let path = std::env::temp_dir().join("alliance_squawk_channels_test.json");
+        let _ = std::fs::remove_file(&path);
+
+        let config = ChannelConfig::load_from(&path, DEFAULTS).await.unwrap();
+
+        assert!(config.channels().await == DEFAULTS);
+
+        config.set_adm_channel(20).await.unwrap();
+        config.clone().set_corp_channel(10).await.unwrap();
+
+        assert!(config.channels().await == NotificationChannels { corp: 10, adm: 20 });
+
+        let reloaded = ChannelConfig::load_from(&path, DEFAULTS).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reloaded.channels().await == NotificationChannels { corp: 10, adm: 20 });
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn persisted_channels_override_defaults_with_warning() {
+        let path = std::env::temp_dir().join("alliance_squawk_channels_override_test.json");
+        std::fs::write(&path, r#"{"corp":1,"adm":20}"#).unwrap();
+
+        let config = ChannelConfig::load_from(&path, DEFAULTS).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(config.channels().await == NotificationChannels { corp: 1, adm: 20 });
+        assert!(logs_contain(
+            "`NOTIFY_ADM_CHANNEL_ID` is overridden by the persisted channel"
+        ));
+        assert!(!logs_contain("`NOTIFY_CORP_CHANNEL_ID` is overridden"));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn invalid_channels_file_is_an_error() {
+        let path = std::env::temp_dir().join("alliance_squawk_channels_invalid_test.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let result = ChannelConfig::load_from(&path, DEFAULTS).await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
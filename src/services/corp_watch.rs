@@ -0,0 +1,105 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use tokio::{fs, sync::RwLock};
+
+use crate::esi::EsiID;
+
+#[derive(Debug, Clone)]
+pub struct CorpWatchList {
+    path: PathBuf,
+    alliances: Arc<RwLock<BTreeSet<EsiID>>>,
+}
+
+impl CorpWatchList {
+    pub async fn load_from(path: impl AsRef<Path>) -> anyhow::Result<CorpWatchList> {
+        let path = path.as_ref().to_path_buf();
+
+        let alliances = if let Ok(json_data) = fs::read_to_string(&path).await {
+            serde_json::from_str(&json_data)?
+        } else {
+            Default::default()
+        };
+
+        Ok(CorpWatchList {
+            path,
+            alliances: Arc::new(RwLock::new(alliances)),
+        })
+    }
+
+    async fn save(&self, alliances: &BTreeSet<EsiID>) -> anyhow::Result<()> {
+        let json_data = serde_json::to_string(alliances)?;
+
+        fs::write(&self.path, json_data).await?;
+
+        Ok(())
+    }
+
+    pub async fn watch(&self, alliance_id: EsiID) -> anyhow::Result<bool> {
+        let mut alliances = self.alliances.write().await;
+
+        if !alliances.insert(alliance_id) {
+            return Ok(false);
+        }
+
+        self.save(&alliances).await?;
+
+        Ok(true)
+    }
+
+    pub async fn unwatch(&self, alliance_id: EsiID) -> anyhow::Result<bool> {
+        let mut alliances = self.alliances.write().await;
+
+        if !alliances.remove(&alliance_id) {
+            return Ok(false);
+        }
+
+        self.save(&alliances).await?;
+
+        Ok(true)
+    }
+
+    pub async fn is_watched(&self, alliance_id: EsiID) -> bool {
+        self.alliances.read().await.contains(&alliance_id)
+    }
+
+    pub async fn alliances(&self) -> Vec<EsiID> {
+        self.alliances.read().await.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::CorpWatchList;
+
+    #[traced_test]
+    #[tokio::test]
+    async fn watch_list_persists() {
+        let path = std::env::temp_dir().join("alliance_squawk_corp_watch_test.json");
+        let _ = std::fs::remove_file(&path);
+
+        let watch_list = CorpWatchList::load_from(&path).await.unwrap();
+
+        assert!(watch_list.watch(99000002).await.unwrap());
+        assert!(watch_list.watch(99000001).await.unwrap());
+        assert!(!watch_list.watch(99000001).await.unwrap());
+
+        let reloaded = CorpWatchList::load_from(&path).await.unwrap();
+
+        assert!(reloaded.alliances().await == vec![99000001, 99000002]);
+
+        assert!(reloaded.unwatch(99000002).await.unwrap());
+        assert!(!reloaded.unwatch(99000002).await.unwrap());
+
+        let reloaded = CorpWatchList::load_from(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reloaded.is_watched(99000001).await);
+        assert!(!reloaded.is_watched(99000002).await);
+    }
+}
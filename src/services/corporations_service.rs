@@ -1,64 +1,423 @@
-use crate::{bot::BotNotification, esi::{Esi, EsiID}};
+use crate::{
+    bot::BotNotification,
+    config::{env_flag, env_value},
+    esi::{EsiClient, EsiID},
+    health::{unix_now, PollHealth, SharedHealth},
+    metrics,
+    shutdown::{self, ShutdownReceiver},
+};
+
+use super::corp_watch::CorpWatchList;
+use serde::{Deserialize, Serialize};
 use std::{
     cmp,
     collections::{HashMap, HashSet, VecDeque},
+    env,
+    path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::{fs, sync::mpsc::UnboundedSender};
+
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(300);
+const ALLIANCE_LIST_RETRY: Duration = Duration::from_secs(60);
+const DEPARTURE_HOLD: Duration = Duration::from_secs(900);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CorpEventFilter {
+    #[default]
+    All,
+    SovHolders,
+    Region(EsiID),
+}
+
+impl CorpEventFilter {
+    fn parse(value: &str) -> Option<CorpEventFilter> {
+        match value.trim() {
+            "" | "all" => Some(CorpEventFilter::All),
+            "sov" => Some(CorpEventFilter::SovHolders),
+            value => value
+                .strip_prefix("region:")
+                .and_then(|region_id| region_id.trim().parse().ok())
+                .map(CorpEventFilter::Region),
+        }
+    }
+}
+
+// On a cold start every alliance is scanned once to learn the current corporations
+// before any corporation notifications are sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Warmup {
+    Scanning,
+    Complete,
+}
+
+#[derive(Debug, Clone)]
+pub struct CorporationsConfig {
+    pub full_refresh: Duration,
+    pub batch_interval: Duration,
+    pub batch_size: usize,
+    pub tracked_alliances: Option<Vec<EsiID>>,
+    pub event_filter: CorpEventFilter,
+    pub member_change_percent: Option<f64>,
+    pub warmup: bool,
+    pub large_alliance_size: Option<usize>,
+    pub large_alliance_chunk: usize,
+    pub deprioritize_large_alliances: bool,
+}
+
+impl Default for CorporationsConfig {
+    fn default() -> Self {
+        CorporationsConfig {
+            full_refresh: Duration::from_secs(3600 * 2),
+            batch_interval: Duration::from_secs(10),
+            batch_size: 20,
+            tracked_alliances: None,
+            event_filter: CorpEventFilter::All,
+            member_change_percent: None,
+            warmup: true,
+            large_alliance_size: None,
+            large_alliance_chunk: 50,
+            deprioritize_large_alliances: false,
+        }
+    }
+}
+
+impl CorporationsConfig {
+    pub fn from_env() -> Self {
+        let default = CorporationsConfig::default();
+
+        CorporationsConfig {
+            full_refresh: Duration::from_secs(env_value(
+                "CORP_FULL_REFRESH_SECONDS",
+                default.full_refresh.as_secs(),
+            )),
+            batch_interval: Duration::from_secs(env_value(
+                "CORP_BATCH_INTERVAL_SECONDS",
+                default.batch_interval.as_secs(),
+            )),
+            batch_size: Some(env_value("CORP_BATCH_SIZE", default.batch_size))
+                .filter(|batch_size| *batch_size > 0)
+                .unwrap_or(default.batch_size),
+            tracked_alliances: env::var("TRACK_ALLIANCE_IDS")
+                .ok()
+                .map(|alliance_ids| parse_alliance_ids(&alliance_ids))
+                .filter(|alliance_ids| !alliance_ids.is_empty()),
+            event_filter: env::var("CORP_EVENT_FILTER")
+                .ok()
+                .and_then(|filter| {
+                    let parsed = CorpEventFilter::parse(&filter);
+                    if parsed.is_none() {
+                        tracing::warn!(filter, "ignoring invalid corp event filter");
+                    }
+                    parsed
+                })
+                .unwrap_or(default.event_filter),
+            // Zero disables the optional features below.
+            member_change_percent: Some(env_value("CORP_MEMBER_CHANGE_PERCENT", 0.0))
+                .filter(|percent| *percent > 0.0),
+            warmup: env_flag("CORP_WARMUP", default.warmup),
+            large_alliance_size: Some(env_value("CORP_LARGE_ALLIANCE_SIZE", 0))
+                .filter(|size| *size > 0),
+            large_alliance_chunk: Some(env_value(
+                "CORP_LARGE_ALLIANCE_CHUNK",
+                default.large_alliance_chunk,
+            ))
+            .filter(|chunk| *chunk > 0)
+            .unwrap_or(default.large_alliance_chunk),
+            deprioritize_large_alliances: env_flag(
+                "CORP_DEPRIORITIZE_LARGE_ALLIANCES",
+                default.deprioritize_large_alliances,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct PendingDeparture {
+    alliance_id: EsiID,
+    left_at: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct CorporationsState {
+    alliance_seen: HashSet<EsiID>,
+    corporation_alliance: HashMap<EsiID, EsiID>,
+    #[serde(default)]
+    corporation_members: HashMap<EsiID, u64>,
+    #[serde(default)]
+    pending_departures: HashMap<EsiID, PendingDeparture>,
+}
+
+impl CorporationsState {
+    fn load(path: &PathBuf) -> anyhow::Result<CorporationsState> {
+        let json_data = std::fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&json_data)?)
+    }
+}
 
 #[derive(Debug)]
 pub struct CorporationsService {
-    esi: Esi,
+    esi: Arc<dyn EsiClient>,
+    config: CorporationsConfig,
     alliance_queue: VecDeque<EsiID>,
+    known_alliances: Option<HashSet<EsiID>>,
+    sov_alliances: Option<HashSet<EsiID>>,
+    system_regions: HashMap<EsiID, EsiID>,
+    constellation_regions: HashMap<EsiID, EsiID>,
+    warmup: Warmup,
 
-    alliance_seen: HashSet<EsiID>,
-    corporation_alliance: HashMap<EsiID, EsiID>,
+    state: CorporationsState,
+    state_path: PathBuf,
 
     last_alliance_queue_update: Option<Instant>,
+    alliance_queue_retry_at: Option<Instant>,
     last_alliance_queue_process: Option<Instant>,
+    last_state_save: Option<Instant>,
 
     notifications: UnboundedSender<BotNotification>,
+    corp_watch: CorpWatchList,
+    health: SharedHealth,
 }
 
 impl CorporationsService {
-    pub fn new(esi: Esi, notifications: UnboundedSender<BotNotification>) -> CorporationsService {
+    pub fn new(
+        esi: Arc<dyn EsiClient>,
+        config: CorporationsConfig,
+        notifications: UnboundedSender<BotNotification>,
+        state_path: PathBuf,
+        corp_watch: CorpWatchList,
+        health: SharedHealth,
+    ) -> CorporationsService {
+        let state = match CorporationsState::load(&state_path) {
+            Ok(state) => {
+                tracing::info!(
+                    ?state_path,
+                    alliances = state.alliance_seen.len(),
+                    corporations = state.corporation_alliance.len(),
+                    "loaded corporation state"
+                );
+                state
+            }
+            Err(err) => {
+                tracing::warn!(?state_path, ?err, "couldn't load corporation state");
+                Default::default()
+            }
+        };
+
+        health.write().unwrap().corporations = PollHealth::new(config.full_refresh);
+
         CorporationsService {
             esi,
+            config,
             alliance_queue: Default::default(),
-            alliance_seen: Default::default(),
-            corporation_alliance: Default::default(),
+            known_alliances: None,
+            sov_alliances: None,
+            system_regions: Default::default(),
+            constellation_regions: Default::default(),
+            warmup: Warmup::Complete,
+            state,
+            state_path,
             last_alliance_queue_update: None,
+            alliance_queue_retry_at: None,
             last_alliance_queue_process: None,
+            last_state_save: None,
             notifications,
+            corp_watch,
+            health,
+        }
+    }
+
+    async fn save_state(&mut self) {
+        self.last_state_save = Some(Instant::now());
+
+        let result = match serde_json::to_string(&self.state) {
+            Ok(json_data) => fs::write(&self.state_path, json_data)
+                .await
+                .map_err(anyhow::Error::from),
+            Err(err) => Err(err.into()),
+        };
+
+        match result {
+            Ok(()) => tracing::debug!(state_path = ?self.state_path, "saved corporation state"),
+            Err(err) => tracing::error!(?err, "couldn't save corporation state"),
         }
     }
 
     async fn update_alliance_queue(&mut self) {
         self.last_alliance_queue_update = Some(Instant::now());
+        self.alliance_queue_retry_at = None;
+
+        if let Some(tracked_alliances) = &self.config.tracked_alliances {
+            warn_discarded_queue(&self.alliance_queue);
 
-        let queue = &mut self.alliance_queue;
+            self.alliance_queue.clear();
+            self.alliance_queue.extend(tracked_alliances);
 
-        if !queue.is_empty() {
-            tracing::warn!(
-                "processing queue contains {} items, it will be cleared",
-                queue.len()
+            tracing::info!(
+                "queued {} tracked alliances to be processed",
+                self.alliance_queue.len()
             );
-        }
 
-        queue.clear();
+            self.health.write().unwrap().corporations.record_success();
+        } else {
+            let had_alliances = !self.alliance_queue.is_empty()
+                || !self.state.alliance_seen.is_empty()
+                || self
+                    .known_alliances
+                    .as_ref()
+                    .is_some_and(|known_alliances| !known_alliances.is_empty());
+
+            match self.esi.get_alliance_ids().await {
+                Ok(alliance_ids) if alliance_ids.is_empty() && had_alliances => {
+                    tracing::warn!(
+                        retry_seconds = ALLIANCE_LIST_RETRY.as_secs(),
+                        "esi returned no alliances, keeping previous queue"
+                    );
 
-        match self.esi.get_alliance_ids().await {
-            Ok(alliance_ids) => {
-                for alliance_id in alliance_ids {
-                    queue.push_back(alliance_id);
+                    self.alliance_queue_retry_at = Some(Instant::now() + ALLIANCE_LIST_RETRY);
                 }
+                Ok(alliance_ids) => {
+                    warn_discarded_queue(&self.alliance_queue);
+
+                    self.alliance_queue.clear();
+                    self.alliance_queue.extend(&alliance_ids);
+
+                    self.health.write().unwrap().corporations.record_success();
+
+                    let alliance_ids: HashSet<EsiID> = alliance_ids.into_iter().collect();
+
+                    if let Some(known_alliances) = &self.known_alliances {
+                        self.notify_alliance_changes(known_alliances, &alliance_ids);
+                    }
+
+                    self.known_alliances = Some(alliance_ids);
+                }
+                Err(err) => {
+                    tracing::error!(?err, "error fetching alliances");
+
+                    self.alliance_queue_retry_at = Some(Instant::now() + ALLIANCE_LIST_RETRY);
+                }
+            }
+
+            tracing::info!(
+                "queued {} alliances to be processed",
+                self.alliance_queue.len()
+            );
+        }
+
+        if let (Some(large_alliance_size), true) = (
+            self.config.large_alliance_size,
+            self.config.deprioritize_large_alliances,
+        ) {
+            let corporation_counts = alliance_corporation_counts(&self.state.corporation_alliance);
+
+            deprioritize_large(
+                &mut self.alliance_queue,
+                &corporation_counts,
+                large_alliance_size,
+            );
+        }
+
+        let watched_alliances = self.corp_watch.alliances().await;
+
+        prioritize_watched(&mut self.alliance_queue, &watched_alliances);
+
+        self.update_sov_alliances().await;
+    }
+
+    async fn system_region(&mut self, system_id: EsiID) -> anyhow::Result<EsiID> {
+        if let Some(region_id) = self.system_regions.get(&system_id) {
+            return Ok(*region_id);
+        }
+
+        let system = self.esi.get_system(system_id).await?;
+
+        let region_id = match self.constellation_regions.get(&system.constellation_id) {
+            Some(region_id) => *region_id,
+            None => {
+                let constellation = self.esi.get_constellation(system.constellation_id).await?;
+
+                self.constellation_regions
+                    .insert(system.constellation_id, constellation.region_id);
+
+                constellation.region_id
             }
+        };
+
+        self.system_regions.insert(system_id, region_id);
+
+        Ok(region_id)
+    }
+
+    async fn update_sov_alliances(&mut self) {
+        if self.config.event_filter == CorpEventFilter::All {
+            return;
+        }
+
+        let sovereignty_structures = match self.esi.get_sovereignty_structures().await {
+            Ok(sovereignty_structures) => sovereignty_structures,
             Err(err) => {
-                tracing::error!(?err, "error fetching alliances");
+                tracing::error!(?err, "error fetching sovereignty structures");
+                return;
             }
+        };
+
+        let mut sov_alliances = HashSet::new();
+
+        for structure in sovereignty_structures {
+            if let CorpEventFilter::Region(region_id) = self.config.event_filter {
+                match self.system_region(structure.solar_system_id).await {
+                    Ok(system_region_id) if system_region_id == region_id => {}
+                    Ok(_) => continue,
+                    Err(err) => {
+                        tracing::warn!(
+                            system_id = structure.solar_system_id,
+                            ?err,
+                            "couldn't resolve system region"
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            sov_alliances.insert(structure.alliance_id);
         }
 
-        tracing::info!("queued {} alliances to be processed", queue.len());
+        tracing::info!(
+            alliances = sov_alliances.len(),
+            filter = ?self.config.event_filter,
+            "updated sov holding alliances"
+        );
+
+        self.sov_alliances = Some(sov_alliances);
+    }
+
+    fn notify_alliance_changes(
+        &self,
+        previous_alliances: &HashSet<EsiID>,
+        current_alliances: &HashSet<EsiID>,
+    ) {
+        let (created, closed) = alliance_set_delta(previous_alliances, current_alliances);
+
+        let notifications = created
+            .into_iter()
+            .map(BotNotification::NotifyAllianceCreated)
+            .chain(
+                closed
+                    .into_iter()
+                    .map(BotNotification::NotifyAllianceClosed),
+            );
+
+        for notification in notifications {
+            if self.notifications.send(notification).is_err() {
+                tracing::warn!(
+                    "couldn't send alliance notification because event channel was closed"
+                );
+                return;
+            }
+        }
     }
 
     async fn process_alliance_queue(&mut self, limit: Option<usize>) {
@@ -74,7 +433,7 @@ impl CorporationsService {
             tracing::debug!("no alliances queued for processing");
             return;
         }
-        
+
         tracing::info!(
             "processing {} alliances ({} remaining)",
             process_limit,
@@ -94,21 +453,49 @@ impl CorporationsService {
 
             let mut old_corporations = Vec::new();
 
-            for (c_id, a_id) in self.corporation_alliance.iter() {
+            for (c_id, a_id) in self.state.corporation_alliance.iter() {
                 if *a_id == alliance_id {
                     old_corporations.push(*c_id);
                 }
             }
 
-            let send_notifications = self.alliance_seen.contains(&alliance_id);
+            let watched = self.corp_watch.is_watched(alliance_id).await;
+
+            let send_notifications = self.warmup == Warmup::Complete
+                && self.state.alliance_seen.contains(&alliance_id)
+                && (watched
+                    || includes_alliance_events(
+                        self.config.event_filter,
+                        self.sov_alliances.as_ref(),
+                        alliance_id,
+                    ));
 
             match self.esi.get_alliance_corporations(alliance_id).await {
                 Ok(new_corporations) => {
-                    self.alliance_seen.insert(alliance_id);
+                    let large = self
+                        .config
+                        .large_alliance_size
+                        .is_some_and(|size| new_corporations.len() >= size);
 
-                    let alliance_ops =
+                    let mut alliance_ops =
                         corporation_alliance_delta(&old_corporations, &new_corporations);
 
+                    // A large alliance gets one chunk per turn and is queued again for the
+                    // rest, it's only seen once the last chunk is applied.
+                    let chunked = large && alliance_ops.len() > self.config.large_alliance_chunk;
+
+                    if chunked {
+                        tracing::info!(
+                            alliance_id,
+                            remaining = alliance_ops.len(),
+                            "processing chunk of large alliance"
+                        );
+
+                        alliance_ops.truncate(self.config.large_alliance_chunk);
+                    } else {
+                        self.state.alliance_seen.insert(alliance_id);
+                    }
+
                     for alliance_op in alliance_ops {
                         match alliance_op {
                             AllianceOp::Add(corporation_id) => {
@@ -117,8 +504,51 @@ impl CorporationsService {
                                     corporation_id,
                                     "corporation joined alliance"
                                 );
-                                self.corporation_alliance
+                                let previous_alliance_id = self
+                                    .state
+                                    .corporation_alliance
                                     .insert(corporation_id, alliance_id);
+                                let pending_departure = self
+                                    .state
+                                    .pending_departures
+                                    .remove(&corporation_id)
+                                    .map(|departure| departure.alliance_id);
+
+                                let (notification, kind) = match corporation_move(
+                                    previous_alliance_id,
+                                    pending_departure,
+                                    alliance_id,
+                                ) {
+                                    Some(from_alliance_id)
+                                        if send_notifications || pending_departure.is_some() =>
+                                    {
+                                        (
+                                            BotNotification::NotifyCorpMovedAlliance(
+                                                corporation_id,
+                                                from_alliance_id,
+                                                alliance_id,
+                                            ),
+                                            "moved",
+                                        )
+                                    }
+                                    None if send_notifications => (
+                                        BotNotification::NotifyCorpJoinAlliance(
+                                            alliance_id,
+                                            corporation_id,
+                                        ),
+                                        "joined",
+                                    ),
+                                    _ => continue,
+                                };
+
+                                if self.notifications.send(notification).is_err() {
+                                    tracing::warn!(
+                                        "aborting service because event channel was closed"
+                                    );
+                                    break 'running;
+                                }
+
+                                metrics::record_corp_event(kind);
                             }
                             AllianceOp::Del(corporation_id) => {
                                 tracing::debug!(
@@ -126,41 +556,160 @@ impl CorporationsService {
                                     corporation_id,
                                     "corporation left alliance"
                                 );
-                                self.corporation_alliance.remove(&corporation_id);
+                                self.state.corporation_alliance.remove(&corporation_id);
+                                self.state.corporation_members.remove(&corporation_id);
 
-                                if send_notifications
-                                    && self
-                                        .notifications
-                                        .send(BotNotification::NotifyCorpLeftAlliance(
+                                // Held back in case the corporation shows up in another alliance
+                                // later in the cycle.
+                                if send_notifications {
+                                    self.state.pending_departures.insert(
+                                        corporation_id,
+                                        PendingDeparture {
                                             alliance_id,
-                                            corporation_id,
-                                        ))
-                                        .is_err()
-                                {
-                                    tracing::warn!(
-                                        "aborting service because event channel was closed"
+                                            left_at: unix_now(),
+                                        },
                                     );
-                                    break 'running;
                                 }
                             }
                         };
                     }
+
+                    if chunked {
+                        self.alliance_queue.push_back(alliance_id);
+                        continue 'running;
+                    }
+
+                    let track_members = watched
+                        || self
+                            .config
+                            .tracked_alliances
+                            .as_ref()
+                            .is_some_and(|tracked| tracked.contains(&alliance_id));
+
+                    if track_members && !self.update_member_counts(&new_corporations).await {
+                        tracing::warn!("aborting service because event channel was closed");
+                        break 'running;
+                    }
                 }
                 Err(_) => {
                     tracing::warn!(alliance_id, "couldn't fetch corporations for alliance");
                 }
             }
         }
+
+        let cycle_complete = self.alliance_queue.is_empty();
+
+        self.flush_pending_departures(cycle_complete, unix_now());
+
+        if cycle_complete {
+            self.complete_warmup();
+        }
+    }
+
+    // Persisted state already describes the alliances, so warmup is only needed when
+    // starting without it.
+    fn begin_warmup(&mut self) {
+        if self.config.warmup && self.state.alliance_seen.is_empty() {
+            tracing::info!("corporation notifications suppressed until the first full scan");
+
+            self.warmup = Warmup::Scanning;
+        }
+    }
+
+    fn complete_warmup(&mut self) {
+        if self.warmup == Warmup::Scanning {
+            tracing::info!(
+                alliances = self.state.alliance_seen.len(),
+                corporations = self.state.corporation_alliance.len(),
+                "corporation warmup complete"
+            );
+
+            self.warmup = Warmup::Complete;
+        }
+    }
+
+    // Returns false when the event channel was closed.
+    async fn update_member_counts(&mut self, corporation_ids: &[EsiID]) -> bool {
+        let Some(threshold_percent) = self.config.member_change_percent else {
+            return true;
+        };
+
+        for corporation_id in corporation_ids {
+            let member_count = match self.esi.get_corporation(*corporation_id).await {
+                Ok(corporation) => corporation.member_count,
+                Err(err) => {
+                    tracing::warn!(corporation_id, ?err, "couldn't fetch corporation");
+                    continue;
+                }
+            };
+
+            let previous_count = self
+                .state
+                .corporation_members
+                .insert(*corporation_id, member_count);
+
+            if let Some((previous_count, member_count)) =
+                member_change(previous_count, member_count, threshold_percent)
+            {
+                let notification = BotNotification::NotifyMemberChange(
+                    *corporation_id,
+                    previous_count,
+                    member_count,
+                );
+
+                if self.notifications.send(notification).is_err() {
+                    return false;
+                }
+            }
+        }
+
+        true
     }
 
-    pub async fn run(&mut self) -> anyhow::Result<()> {
+    // Departures are sent at the end of a cycle, or once they were held for longer than
+    // `DEPARTURE_HOLD`. They stay in the state until sent, so a restart doesn't lose them.
+    fn flush_pending_departures(&mut self, cycle_complete: bool, now: u64) {
+        for corporation_id in due_departures(&self.state.pending_departures, cycle_complete, now) {
+            let alliance_id = self.state.pending_departures[&corporation_id].alliance_id;
+
+            if self
+                .notifications
+                .send(BotNotification::NotifyCorpLeftAlliance(
+                    alliance_id,
+                    corporation_id,
+                ))
+                .is_err()
+            {
+                tracing::warn!("couldn't send departures because event channel was closed");
+                return;
+            }
+
+            self.state.pending_departures.remove(&corporation_id);
+            metrics::record_corp_event("left");
+        }
+    }
+
+    async fn process_alliance_batch(&mut self) {
+        self.process_alliance_queue(Some(self.config.batch_size))
+            .await
+    }
+
+    pub async fn run(&mut self, mut shutdown: ShutdownReceiver) -> anyhow::Result<()> {
+        self.begin_warmup();
         self.update_alliance_queue().await;
         self.process_alliance_queue(None).await;
+        self.save_state().await;
 
         loop {
             match self.last_alliance_queue_update {
                 Some(last_alliance_queue_update)
-                    if last_alliance_queue_update.elapsed() >= Duration::from_secs(3600 * 2) =>
+                    if last_alliance_queue_update.elapsed() >= self.config.full_refresh =>
+                {
+                    self.update_alliance_queue().await
+                }
+                _ if self
+                    .alliance_queue_retry_at
+                    .is_some_and(|retry_at| Instant::now() >= retry_at) =>
                 {
                     self.update_alliance_queue().await
                 }
@@ -169,18 +718,177 @@ impl CorporationsService {
 
             match self.last_alliance_queue_process {
                 Some(last_alliance_queue_process)
-                    if last_alliance_queue_process.elapsed() >= Duration::from_secs(10) =>
+                    if last_alliance_queue_process.elapsed() >= self.config.batch_interval =>
                 {
-                    self.process_alliance_queue(Some(20)).await
+                    self.process_alliance_batch().await
+                }
+                _ => {}
+            };
+
+            match self.last_state_save {
+                Some(last_state_save) if last_state_save.elapsed() >= STATE_SAVE_INTERVAL => {
+                    self.save_state().await
                 }
                 _ => {}
             };
 
-            tokio::time::sleep(Duration::from_millis(100)).await;
+            tokio::select! {
+                _ = shutdown::requested(&mut shutdown) => break,
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+            }
         }
+
+        self.save_state().await;
+
+        tracing::info!("corporation service stopped");
+
+        Ok(())
+    }
+}
+
+fn warn_discarded_queue(queue: &VecDeque<EsiID>) {
+    if !queue.is_empty() {
+        tracing::warn!(
+            "processing queue contains {} items, it will be cleared",
+            queue.len()
+        );
     }
 }
 
+fn alliance_corporation_counts(
+    corporation_alliance: &HashMap<EsiID, EsiID>,
+) -> HashMap<EsiID, usize> {
+    let mut counts = HashMap::new();
+
+    for alliance_id in corporation_alliance.values() {
+        *counts.entry(*alliance_id).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+// Moves alliances last known to have at least `large_alliance_size` corporations to the
+// back of the queue, keeping the order within both groups.
+fn deprioritize_large(
+    queue: &mut VecDeque<EsiID>,
+    corporation_counts: &HashMap<EsiID, usize>,
+    large_alliance_size: usize,
+) {
+    let (small, large): (VecDeque<EsiID>, VecDeque<EsiID>) =
+        queue.drain(..).partition(|alliance_id| {
+            corporation_counts
+                .get(alliance_id)
+                .is_none_or(|count| *count < large_alliance_size)
+        });
+
+    if !large.is_empty() {
+        tracing::info!(alliances = large.len(), "deprioritized large alliances");
+    }
+
+    queue.extend(small);
+    queue.extend(large);
+}
+
+fn prioritize_watched(queue: &mut VecDeque<EsiID>, watched_alliances: &[EsiID]) {
+    queue.retain(|alliance_id| !watched_alliances.contains(alliance_id));
+
+    for alliance_id in watched_alliances.iter().rev() {
+        queue.push_front(*alliance_id);
+    }
+}
+
+// Until the first sovereignty fetch succeeds every alliance is included, so a flaky
+// sovereignty endpoint doesn't silence corporation notifications.
+fn includes_alliance_events(
+    filter: CorpEventFilter,
+    sov_alliances: Option<&HashSet<EsiID>>,
+    alliance_id: EsiID,
+) -> bool {
+    match (filter, sov_alliances) {
+        (CorpEventFilter::All, _) | (_, None) => true,
+        (_, Some(sov_alliances)) => sov_alliances.contains(&alliance_id),
+    }
+}
+
+fn due_departures(
+    pending_departures: &HashMap<EsiID, PendingDeparture>,
+    cycle_complete: bool,
+    now: u64,
+) -> Vec<EsiID> {
+    let mut corporation_ids: Vec<_> = pending_departures
+        .iter()
+        .filter(|(_, departure)| {
+            cycle_complete || now.saturating_sub(departure.left_at) >= DEPARTURE_HOLD.as_secs()
+        })
+        .map(|(corporation_id, _)| *corporation_id)
+        .collect();
+
+    corporation_ids.sort_unstable();
+    corporation_ids
+}
+
+fn corporation_move(
+    previous_alliance_id: Option<EsiID>,
+    pending_departure: Option<EsiID>,
+    alliance_id: EsiID,
+) -> Option<EsiID> {
+    pending_departure
+        .or(previous_alliance_id)
+        .filter(|from_alliance_id| *from_alliance_id != alliance_id)
+}
+
+// Only changes of more than `threshold_percent` of the previously observed count are
+// reported, the first observation of a corporation is never a change.
+fn member_change(
+    previous_count: Option<u64>,
+    member_count: u64,
+    threshold_percent: f64,
+) -> Option<(u64, u64)> {
+    let previous_count = previous_count.filter(|previous_count| *previous_count != member_count)?;
+
+    let change_percent = if previous_count == 0 {
+        f64::INFINITY
+    } else {
+        previous_count.abs_diff(member_count) as f64 / previous_count as f64 * 100.0
+    };
+
+    (change_percent > threshold_percent).then_some((previous_count, member_count))
+}
+
+fn alliance_set_delta(
+    previous_alliances: &HashSet<EsiID>,
+    current_alliances: &HashSet<EsiID>,
+) -> (Vec<EsiID>, Vec<EsiID>) {
+    let mut created: Vec<_> = current_alliances
+        .difference(previous_alliances)
+        .copied()
+        .collect();
+    let mut closed: Vec<_> = previous_alliances
+        .difference(current_alliances)
+        .copied()
+        .collect();
+
+    created.sort_unstable();
+    closed.sort_unstable();
+
+    (created, closed)
+}
+
+fn parse_alliance_ids(alliance_ids: &str) -> Vec<EsiID> {
+    alliance_ids
+        .split(',')
+        .map(str::trim)
+        .filter(|alliance_id| !alliance_id.is_empty())
+        .filter_map(|alliance_id| match alliance_id.parse() {
+            Ok(alliance_id) => Some(alliance_id),
+            Err(err) => {
+                tracing::warn!(alliance_id, ?err, "ignoring invalid tracked alliance id");
+                None
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, PartialEq)]
 enum AllianceOp {
     Add(EsiID),
@@ -224,9 +932,137 @@ fn corporation_alliance_delta(
 
 #[cfg(test)]
 mod tests {
-    use super::{corporation_alliance_delta, AllianceOp};
+    use super::{
+        alliance_corporation_counts, alliance_set_delta, corporation_alliance_delta,
+        corporation_move, deprioritize_large, due_departures, includes_alliance_events,
+        member_change, parse_alliance_ids, prioritize_watched, AllianceOp, CorpEventFilter,
+        CorporationsConfig, CorporationsService, CorporationsState, PendingDeparture, Warmup,
+        DEPARTURE_HOLD,
+    };
+    use crate::{
+        bot::BotNotification,
+        esi::{MockEsi, SovereigntyStructure},
+        health::HealthState,
+        services::corp_watch::CorpWatchList,
+        shutdown,
+    };
+    use std::{
+        collections::{HashMap, HashSet, VecDeque},
+        sync::Arc,
+        time::Duration,
+    };
+    use tokio::sync::mpsc::UnboundedReceiver;
     use tracing_test::traced_test;
 
+    fn sovereignty_structure(alliance_id: u64, solar_system_id: u64) -> SovereigntyStructure {
+        SovereigntyStructure {
+            alliance_id,
+            solar_system_id,
+            structure_id: solar_system_id,
+            structure_type_id: 32226,
+            vulnerability_occupancy_level: None,
+            vulnerable_end_time: None,
+            vulnerable_start_time: None,
+        }
+    }
+
+    async fn service(
+        esi: MockEsi,
+        config: CorporationsConfig,
+    ) -> (CorporationsService, UnboundedReceiver<BotNotification>) {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let service = CorporationsService::new(
+            Arc::new(esi),
+            config,
+            sender,
+            std::env::temp_dir().join("alliance_squawk_missing_corporations.json"),
+            CorpWatchList::load_from(
+                std::env::temp_dir().join("alliance_squawk_missing_watch.json"),
+            )
+            .await
+            .unwrap(),
+            HealthState::shared(),
+        );
+
+        (service, receiver)
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_process_alliance_batch_honors_batch_size() {
+        let esi = MockEsi {
+            alliance_corporations: (1..=5).map(|alliance_id| (alliance_id, vec![])).collect(),
+            ..Default::default()
+        };
+        let config = CorporationsConfig {
+            full_refresh: Duration::from_secs(60),
+            batch_interval: Duration::from_secs(1),
+            batch_size: 2,
+            ..Default::default()
+        };
+
+        let (mut service, _receiver) = service(esi, config).await;
+        service.alliance_queue.extend([1, 2, 3, 4, 5]);
+
+        service.process_alliance_batch().await;
+
+        assert!(service.alliance_queue == [3, 4, 5]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_update_alliance_queue_uses_tracked_alliances() {
+        let esi = MockEsi {
+            alliance_ids: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let config = CorporationsConfig {
+            tracked_alliances: Some(vec![10, 20]),
+            ..Default::default()
+        };
+
+        let (mut service, _receiver) = service(esi, config).await;
+
+        service.update_alliance_queue().await;
+
+        assert!(service.alliance_queue == [10, 20]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_parse_alliance_ids() {
+        assert!(parse_alliance_ids("99010468, 99003581,,abc") == vec![99010468, 99003581]);
+        assert!(parse_alliance_ids("").is_empty());
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_corporations_state_round_trip() {
+        let state = CorporationsState {
+            alliance_seen: [1, 2].into_iter().collect(),
+            corporation_alliance: [(10, 1), (11, 1), (20, 2)].into_iter().collect(),
+            corporation_members: [(10, 25), (20, 3)].into_iter().collect(),
+            pending_departures: [(
+                12,
+                PendingDeparture {
+                    alliance_id: 1,
+                    left_at: 1700000000,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        let path = std::env::temp_dir().join("alliance_squawk_corporations_state_test.json");
+        std::fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+
+        let loaded = CorporationsState::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded == state);
+    }
+
     #[traced_test]
     #[test]
     fn test_corporation_alliance_delta() {
@@ -259,4 +1095,475 @@ mod tests {
 
         assert!(delta[0] == AllianceOp::Del(1));
     }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn run_returns_after_shutdown() {
+        let state_path = std::env::temp_dir().join("alliance_squawk_shutdown_corporations.json");
+
+        // The state is saved on shutdown, so it goes elsewhere than the path others load from.
+        let (mut service, _receiver) =
+            service(MockEsi::default(), CorporationsConfig::default()).await;
+        service.state_path = state_path.clone();
+
+        let (shutdown_sender, shutdown_receiver) = shutdown::channel();
+        shutdown_sender.send(true).unwrap();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), service.run(shutdown_receiver)).await;
+        std::fs::remove_file(&state_path).unwrap();
+
+        assert!(matches!(result, Ok(Ok(()))));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_deprioritize_large_alliances() {
+        let corporation_alliance = HashMap::from([
+            (101, 1),
+            (102, 1),
+            (103, 1),
+            (201, 2),
+            (301, 3),
+            (302, 3),
+            (303, 3),
+            (401, 4),
+            (402, 4),
+        ]);
+        let counts = alliance_corporation_counts(&corporation_alliance);
+
+        assert!(counts.get(&1) == Some(&3));
+        assert!(counts.get(&4) == Some(&2));
+
+        let mut queue = VecDeque::from([1, 2, 3, 4, 5]);
+
+        deprioritize_large(&mut queue, &counts, 3);
+
+        assert!(queue == [2, 4, 5, 1, 3]);
+
+        let mut queue = VecDeque::from([1, 2, 3, 4, 5]);
+
+        deprioritize_large(&mut queue, &counts, 2);
+        prioritize_watched(&mut queue, &[3]);
+
+        assert!(queue == [3, 2, 5, 1, 4]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_prioritize_watched() {
+        let mut queue = VecDeque::from([1, 2, 3, 4]);
+
+        prioritize_watched(&mut queue, &[3, 5]);
+
+        assert!(queue == [3, 5, 1, 2, 4]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_alliance_set_delta() {
+        let previous = HashSet::from([1, 2, 3]);
+        let current = HashSet::from([2, 3, 5, 4]);
+
+        let (created, closed) = alliance_set_delta(&previous, &current);
+
+        assert!(created == vec![4, 5]);
+        assert!(closed == vec![1]);
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_alliance_set_delta_unchanged() {
+        let alliances = HashSet::from([1, 2, 3]);
+
+        let (created, closed) = alliance_set_delta(&alliances, &alliances);
+
+        assert!(created.is_empty());
+        assert!(closed.is_empty());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_update_alliance_queue_notifies_alliance_changes() {
+        let esi = MockEsi {
+            alliance_ids: vec![1, 2],
+            ..Default::default()
+        };
+        let (mut service, mut receiver) = service(esi, CorporationsConfig::default()).await;
+
+        service.update_alliance_queue().await;
+
+        assert!(receiver.try_recv().is_err());
+
+        service.esi = Arc::new(MockEsi {
+            alliance_ids: vec![2, 3],
+            ..Default::default()
+        });
+        service.update_alliance_queue().await;
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyAllianceCreated(3))
+        ));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyAllianceClosed(1))
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_corp_event_filter_parse() {
+        assert!(CorpEventFilter::parse("all") == Some(CorpEventFilter::All));
+        assert!(CorpEventFilter::parse("sov") == Some(CorpEventFilter::SovHolders));
+        assert!(
+            CorpEventFilter::parse("region:10000060") == Some(CorpEventFilter::Region(10000060))
+        );
+        assert!(CorpEventFilter::parse("region:delve").is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_includes_alliance_events() {
+        let sov_alliances = HashSet::from([1, 2]);
+
+        assert!(includes_alliance_events(CorpEventFilter::All, None, 3));
+        assert!(includes_alliance_events(
+            CorpEventFilter::All,
+            Some(&sov_alliances),
+            3
+        ));
+        assert!(includes_alliance_events(
+            CorpEventFilter::SovHolders,
+            Some(&sov_alliances),
+            1
+        ));
+        assert!(!includes_alliance_events(
+            CorpEventFilter::SovHolders,
+            Some(&sov_alliances),
+            3
+        ));
+        assert!(!includes_alliance_events(
+            CorpEventFilter::Region(10000060),
+            Some(&sov_alliances),
+            3
+        ));
+        assert!(includes_alliance_events(
+            CorpEventFilter::SovHolders,
+            None,
+            3
+        ));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_update_alliance_queue_refreshes_sov_alliances() {
+        let esi = MockEsi {
+            alliance_ids: vec![1, 2, 3],
+            sovereignty_structures: vec![
+                sovereignty_structure(1, 30000001),
+                sovereignty_structure(2, 30000002),
+            ],
+            ..Default::default()
+        };
+        let (mut service, _receiver) = service(
+            esi,
+            CorporationsConfig {
+                event_filter: CorpEventFilter::SovHolders,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        service.update_alliance_queue().await;
+
+        assert!(service.sov_alliances == Some(HashSet::from([1, 2])));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_member_change_threshold() {
+        assert!(member_change(None, 100, 10.0).is_none());
+        assert!(member_change(Some(100), 100, 10.0).is_none());
+        assert!(member_change(Some(100), 110, 10.0).is_none());
+        assert!(member_change(Some(100), 111, 10.0) == Some((100, 111)));
+        assert!(member_change(Some(100), 89, 10.0) == Some((100, 89)));
+        assert!(member_change(Some(100), 95, 10.0).is_none());
+        assert!(member_change(Some(0), 5, 10.0) == Some((0, 5)));
+        assert!(member_change(Some(5), 0, 10.0) == Some((5, 0)));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_state_without_member_counts_loads() {
+        let state: CorporationsState =
+            serde_json::from_str(r#"{"alliance_seen":[1],"corporation_alliance":{"10":1}}"#)
+                .unwrap();
+
+        assert!(state.corporation_members.is_empty());
+        assert!(state.corporation_alliance.get(&10) == Some(&1));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_corporation_move() {
+        assert!(corporation_move(None, Some(1), 2) == Some(1));
+        assert!(corporation_move(Some(1), None, 2) == Some(1));
+        assert!(corporation_move(None, None, 2).is_none());
+        assert!(corporation_move(Some(2), None, 2).is_none());
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_due_departures() {
+        let now = 1700000000;
+        let departure = |left_at| PendingDeparture {
+            alliance_id: 1,
+            left_at,
+        };
+        let pending_departures = [
+            (10, departure(now)),
+            (11, departure(now - DEPARTURE_HOLD.as_secs())),
+            (12, departure(now - 60)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert!(due_departures(&pending_departures, false, now) == vec![11]);
+        assert!(due_departures(&pending_departures, true, now) == vec![10, 11, 12]);
+        assert!(due_departures(&HashMap::new(), true, now).is_empty());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_departures_are_held_across_batches() {
+        let esi = MockEsi {
+            alliance_corporations: [(1, vec![]), (2, vec![20]), (3, vec![30, 10])]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let (mut service, mut receiver) = service(esi, CorporationsConfig::default()).await;
+
+        service.state = CorporationsState {
+            alliance_seen: [1, 2, 3].into_iter().collect(),
+            corporation_alliance: [(10, 1), (20, 2), (30, 3)].into_iter().collect(),
+            ..Default::default()
+        };
+        service.alliance_queue.extend([1, 2, 3]);
+
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(receiver.try_recv().is_err());
+        assert!(service.state.pending_departures[&10].alliance_id == 1);
+
+        service.state.pending_departures.insert(
+            40,
+            PendingDeparture {
+                alliance_id: 2,
+                left_at: 0,
+            },
+        );
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyCorpLeftAlliance(2, 40))
+        ));
+        assert!(service.state.pending_departures.contains_key(&10));
+
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyCorpMovedAlliance(10, 1, 3))
+        ));
+        assert!(service.state.pending_departures.is_empty());
+    }
+
+    async fn moving_corporation_notifications(queue: [u64; 2]) -> Vec<BotNotification> {
+        let esi = MockEsi {
+            alliance_corporations: [(1, vec![11]), (2, vec![10, 20])].into_iter().collect(),
+            ..Default::default()
+        };
+        let (mut service, mut receiver) = service(esi, CorporationsConfig::default()).await;
+
+        service.state = CorporationsState {
+            alliance_seen: [1, 2].into_iter().collect(),
+            corporation_alliance: [(10, 1), (11, 1), (12, 1), (20, 2)].into_iter().collect(),
+            ..Default::default()
+        };
+        service.alliance_queue.extend(queue);
+
+        service.process_alliance_queue(None).await;
+        drop(service);
+
+        let mut notifications = vec![];
+        while let Some(notification) = receiver.recv().await {
+            notifications.push(notification);
+        }
+
+        notifications
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_process_alliance_queue_correlates_moves() {
+        for queue in [[1, 2], [2, 1]] {
+            let notifications = moving_corporation_notifications(queue).await;
+
+            assert!(notifications.len() == 2);
+            assert!(notifications.iter().any(|notification| matches!(
+                notification,
+                BotNotification::NotifyCorpMovedAlliance(10, 1, 2)
+            )));
+            assert!(notifications.iter().any(|notification| matches!(
+                notification,
+                BotNotification::NotifyCorpLeftAlliance(1, 12)
+            )));
+        }
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_process_alliance_queue_announces_joins_and_departures() {
+        let esi = MockEsi {
+            alliance_corporations: [(1, vec![10, 13])].into_iter().collect(),
+            ..Default::default()
+        };
+        let (mut service, mut receiver) = service(esi, CorporationsConfig::default()).await;
+
+        service.state = CorporationsState {
+            alliance_seen: [1].into_iter().collect(),
+            corporation_alliance: [(10, 1), (12, 1)].into_iter().collect(),
+            ..Default::default()
+        };
+        service.alliance_queue.push_back(1);
+
+        service.process_alliance_queue(None).await;
+        drop(service);
+
+        let mut notifications = vec![];
+        while let Some(notification) = receiver.recv().await {
+            notifications.push(notification);
+        }
+
+        assert!(notifications.len() == 2);
+        assert!(notifications.iter().any(|notification| matches!(
+            notification,
+            BotNotification::NotifyCorpJoinAlliance(1, 13)
+        )));
+        assert!(notifications.iter().any(|notification| matches!(
+            notification,
+            BotNotification::NotifyCorpLeftAlliance(1, 12)
+        )));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_large_alliance_is_split_across_turns() {
+        let esi = MockEsi {
+            alliance_corporations: [(1, vec![10, 11, 12, 13, 14]), (2, vec![20])]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let config = CorporationsConfig {
+            large_alliance_size: Some(3),
+            large_alliance_chunk: 2,
+            ..Default::default()
+        };
+
+        let (mut service, mut receiver) = service(esi, config).await;
+
+        service.alliance_queue.extend([1, 2]);
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(service.state.corporation_alliance.len() == 2);
+        assert!(!service.state.alliance_seen.contains(&1));
+        assert!(service.alliance_queue == [2, 1]);
+
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(service.state.corporation_alliance.get(&20) == Some(&2));
+        assert!(service.alliance_queue == [1]);
+
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(service.state.corporation_alliance.len() == 5);
+        assert!(service.alliance_queue == [1]);
+
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(service.state.corporation_alliance.len() == 6);
+        assert!(service.state.alliance_seen.contains(&1));
+        assert!(service.alliance_queue.is_empty());
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_warmup_suppresses_events_until_first_full_scan() {
+        let esi = MockEsi {
+            alliance_corporations: [(1, vec![10]), (2, vec![20])].into_iter().collect(),
+            ..Default::default()
+        };
+        let (mut service, mut receiver) = service(esi, CorporationsConfig::default()).await;
+
+        service.begin_warmup();
+
+        assert!(service.warmup == Warmup::Scanning);
+
+        // Alliance 1 is already seen when it comes up again, but the scan isn't complete.
+        service.alliance_queue.extend([1, 1, 2]);
+        service.process_alliance_queue(Some(1)).await;
+        service.state.corporation_alliance.remove(&10);
+        service.process_alliance_queue(Some(1)).await;
+
+        assert!(service.warmup == Warmup::Scanning);
+        assert!(receiver.try_recv().is_err());
+
+        service.process_alliance_queue(None).await;
+
+        assert!(service.warmup == Warmup::Complete);
+        assert!(logs_contain("corporation warmup complete"));
+
+        service.state.corporation_alliance.remove(&20);
+        service.alliance_queue.push_back(2);
+        service.process_alliance_queue(None).await;
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyCorpJoinAlliance(2, 20))
+        ));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_warmup_skipped_with_persisted_state() {
+        let (mut service, _receiver) =
+            service(MockEsi::default(), CorporationsConfig::default()).await;
+
+        service.state.alliance_seen.insert(1);
+        service.begin_warmup();
+
+        assert!(service.warmup == Warmup::Complete);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn test_update_alliance_queue_keeps_queue_on_empty_response() {
+        let (mut service, mut receiver) =
+            service(MockEsi::default(), CorporationsConfig::default()).await;
+
+        service.alliance_queue.extend([1, 2]);
+        service.known_alliances = Some(HashSet::from([1, 2, 3]));
+
+        service.update_alliance_queue().await;
+
+        assert!(service.alliance_queue == [1, 2]);
+        assert!(service.known_alliances == Some(HashSet::from([1, 2, 3])));
+        assert!(service.alliance_queue_retry_at.is_some());
+        assert!(receiver.try_recv().is_err());
+    }
 }
@@ -0,0 +1,114 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::bot::BotNotification;
+
+#[derive(Debug, Clone)]
+pub struct EventLogEntry {
+    pub timestamp: u64,
+    pub notification: BotNotification,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    capacity: usize,
+    entries: Arc<RwLock<VecDeque<EventLogEntry>>>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> EventLog {
+        let capacity = capacity.max(1);
+
+        EventLog {
+            capacity,
+            entries: Arc::new(RwLock::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    pub async fn record(&self, timestamp: u64, notification: BotNotification) {
+        let mut entries = self.entries.write().await;
+
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+
+        entries.push_back(EventLogEntry {
+            timestamp,
+            notification,
+        });
+    }
+
+    /// Returns up to `count` entries, newest first.
+    pub async fn recent(&self, count: usize) -> Vec<EventLogEntry> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .rev()
+            .take(count)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::bot::BotNotification;
+
+    use super::EventLog;
+
+    fn timestamps(entries: &[super::EventLogEntry]) -> Vec<u64> {
+        entries.iter().map(|entry| entry.timestamp).collect()
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn recent_returns_newest_first() {
+        let event_log = EventLog::new(10);
+
+        for timestamp in 1..=4 {
+            event_log
+                .record(timestamp, BotNotification::NotifySovLost(timestamp))
+                .await;
+        }
+
+        assert!(timestamps(&event_log.recent(10).await) == vec![4, 3, 2, 1]);
+        assert!(timestamps(&event_log.recent(2).await) == vec![4, 3]);
+        assert!(matches!(
+            event_log.recent(1).await[0].notification,
+            BotNotification::NotifySovLost(4)
+        ));
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn record_evicts_oldest_past_capacity() {
+        let event_log = EventLog::new(3);
+
+        for timestamp in 1..=5 {
+            event_log
+                .record(timestamp, BotNotification::NotifyAllianceCreated(timestamp))
+                .await;
+        }
+
+        assert!(timestamps(&event_log.recent(10).await) == vec![5, 4, 3]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn zero_capacity_keeps_latest() {
+        let event_log = EventLog::new(0);
+
+        event_log
+            .record(1, BotNotification::NotifyAllianceClosed(1))
+            .await;
+        event_log
+            .record(2, BotNotification::NotifyAllianceClosed(2))
+            .await;
+
+        assert!(timestamps(&event_log.recent(10).await) == vec![2]);
+    }
+}
@@ -1,66 +1,830 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use tokio::sync::RwLock;
+use futures::future::join_all;
+use tokio::sync::{Mutex, RwLock};
 
-use crate::esi::{Alliance, Corporation, Esi, EsiID, System};
+use crate::esi::{
+    Alliance, ApiResult, Constellation, Corporation, EntityKind, EntityMatch, EsiClient, EsiError,
+    EsiID, Region, System,
+};
 
+const NOT_FOUND_TTL: Duration = Duration::from_secs(600);
+
+const TICKER_INDEX_REFRESH: Duration = Duration::from_secs(3600);
+const TICKER_INDEX_CONCURRENCY: usize = 16;
+const MAX_TICKER_LENGTH: usize = 5;
+
+#[derive(Debug)]
+pub enum Lookup<T> {
+    Found(EsiID, T),
+    Ambiguous(Vec<EntityMatch>),
+    NotFound,
+}
+
+#[derive(Debug, Clone)]
+struct TickerEntry {
+    name: String,
+    ticker: String,
+}
+
+// Responses are cached by `Esi` for as long as ESI says they're valid, only what can't be
+// looked up again cheaply is kept here.
 #[derive(Debug, Clone)]
 pub struct InformationService {
-    esi: Esi,
-    alliances: Arc<RwLock<HashMap<EsiID, Alliance>>>,
-    corporations: Arc<RwLock<HashMap<EsiID, Corporation>>>,
-    systems: Arc<RwLock<HashMap<EsiID, System>>>,
+    esi: Arc<dyn EsiClient>,
+    // System names never change, so the ones resolved in bulk are kept for good.
+    system_names: Arc<RwLock<HashMap<EsiID, String>>>,
+    // ESI has no ticker search, so the tickers of every alliance and of the corporations in
+    // the response cache are indexed.
+    tickers: Arc<RwLock<HashMap<(EntityKind, EsiID), TickerEntry>>>,
+    alliances_indexed_at: Arc<Mutex<Option<Instant>>>,
+    // Keyed by kind as well, ids are only unique per kind and a missing alliance says nothing
+    // about a corporation with the same id.
+    not_found: Arc<RwLock<HashMap<(&'static str, EsiID), Instant>>>,
 }
 
 impl InformationService {
-    pub fn new(esi: Esi) -> Self {
+    pub fn new(esi: Arc<dyn EsiClient>) -> Self {
         InformationService {
             esi,
-            alliances: Default::default(),
-            corporations: Default::default(),
-            systems: Default::default(),
+            system_names: Default::default(),
+            tickers: Default::default(),
+            alliances_indexed_at: Default::default(),
+            not_found: Default::default(),
         }
     }
 
-    pub async fn get_alliance(&self, id: EsiID) -> anyhow::Result<Alliance> {
-        let mut alliances = self.alliances.write().await;
+    #[cfg(test)]
+    pub async fn clear_negative_cache(&self) {
+        self.not_found.write().await.clear();
+    }
 
-        if let Some(alliance) = alliances.get(&id) {
-            Ok(alliance.clone())
-        } else {
-            let alliance = self.esi.get_alliance(id).await?;
+    async fn check_not_found(&self, kind: &'static str, id: EsiID) -> anyhow::Result<()> {
+        match self.not_found.read().await.get(&(kind, id)) {
+            Some(not_found_at) if not_found_at.elapsed() < NOT_FOUND_TTL => {
+                tracing::debug!(kind, id, "skipping lookup of recently missing id");
 
-            alliances.insert(id, alliance.clone());
+                Err(EsiError::NotFound.into())
+            }
+            _ => Ok(()),
+        }
+    }
 
-            Ok(alliance)
+    async fn record_not_found<T>(
+        &self,
+        kind: &'static str,
+        id: EsiID,
+        result: ApiResult<T>,
+    ) -> anyhow::Result<T> {
+        if let Err(EsiError::NotFound) = result {
+            self.not_found
+                .write()
+                .await
+                .insert((kind, id), Instant::now());
         }
+
+        Ok(result?)
+    }
+
+    async fn lookup<T, F>(&self, kind: &'static str, id: EsiID, fetch: F) -> anyhow::Result<T>
+    where
+        F: Future<Output = ApiResult<T>>,
+    {
+        self.check_not_found(kind, id).await?;
+
+        self.record_not_found(kind, id, fetch.await).await
+    }
+
+    async fn record_ticker(&self, kind: EntityKind, id: EsiID, name: &str, ticker: &str) {
+        self.tickers.write().await.insert(
+            (kind, id),
+            TickerEntry {
+                name: name.to_string(),
+                ticker: ticker.to_string(),
+            },
+        );
+    }
+
+    pub async fn get_alliance(&self, id: EsiID) -> anyhow::Result<Alliance> {
+        let alliance = self
+            .lookup("alliance", id, self.esi.get_alliance(id))
+            .await?;
+
+        self.record_ticker(EntityKind::Alliance, id, &alliance.name, &alliance.ticker)
+            .await;
+
+        Ok(alliance)
     }
 
     pub async fn get_corporation(&self, id: EsiID) -> anyhow::Result<Corporation> {
-        let mut corporations = self.corporations.write().await;
+        self.lookup("corporation", id, self.esi.get_corporation(id))
+            .await
+    }
+
+    pub async fn get_system(&self, id: EsiID) -> anyhow::Result<System> {
+        let system = self.lookup("system", id, self.esi.get_system(id)).await?;
 
-        if let Some(corporation) = corporations.get(&id) {
-            Ok(corporation.clone())
-        } else {
-            let corporation = self.esi.get_corporation(id).await?;
+        self.system_names
+            .write()
+            .await
+            .insert(id, system.name.clone());
 
-            corporations.insert(id, corporation.clone());
+        Ok(system)
+    }
+
+    pub async fn get_constellation(&self, id: EsiID) -> anyhow::Result<Constellation> {
+        self.lookup("constellation", id, self.esi.get_constellation(id))
+            .await
+    }
 
-            Ok(corporation)
+    pub async fn get_region(&self, id: EsiID) -> anyhow::Result<Region> {
+        self.lookup("region", id, self.esi.get_region(id)).await
+    }
+
+    pub async fn get_system_region(&self, system_id: EsiID) -> anyhow::Result<Region> {
+        let system = self.get_system(system_id).await?;
+        let constellation = self.get_constellation(system.constellation_id).await?;
+
+        self.get_region(constellation.region_id).await
+    }
+
+    pub async fn search_system(&self, name: &str) -> anyhow::Result<Option<System>> {
+        match self.esi.search_system_id(name).await? {
+            Some(system_id) => Ok(Some(self.get_system(system_id).await?)),
+            None => Ok(None),
         }
     }
 
-    pub async fn get_system(&self, id: EsiID) -> anyhow::Result<System> {
-        let mut systems = self.systems.write().await;
+    /// Tickers resolve for every alliance, but only for corporations that were fetched before,
+    /// ESI has no public ticker search.
+    pub async fn resolve_entities(&self, query: &str) -> anyhow::Result<Vec<EntityMatch>> {
+        let query = query.trim();
+
+        if let Ok(id) = query.parse::<EsiID>() {
+            return self.resolve_entity_id(id).await;
+        }
+
+        let mut matches = self.esi.search_entities(query).await?;
+
+        for ticker_match in self.ticker_matches(query).await {
+            if !matches.iter().any(|entity_match| {
+                entity_match.kind == ticker_match.kind && entity_match.id == ticker_match.id
+            }) {
+                matches.push(ticker_match);
+            }
+        }
+
+        Ok(matches)
+    }
+
+    async fn resolve_entity_id(&self, id: EsiID) -> anyhow::Result<Vec<EntityMatch>> {
+        match self.get_alliance(id).await {
+            Ok(alliance) => {
+                return Ok(vec![EntityMatch {
+                    kind: EntityKind::Alliance,
+                    id,
+                    name: alliance.name,
+                }])
+            }
+            Err(err) if !is_not_found(&err) => return Err(err),
+            Err(_) => {}
+        }
+
+        match self.get_corporation(id).await {
+            Ok(corporation) => Ok(vec![EntityMatch {
+                kind: EntityKind::Corporation,
+                id,
+                name: corporation.name,
+            }]),
+            Err(err) if is_not_found(&err) => Ok(vec![]),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Built on first use and refreshed hourly. Alliances are cached by `Esi`, so a refresh
+    // mostly revalidates ETags. A failed build is retried on the next lookup.
+    async fn index_alliance_tickers(&self) {
+        let mut indexed_at = self.alliances_indexed_at.lock().await;
+
+        if indexed_at.is_some_and(|indexed_at| indexed_at.elapsed() < TICKER_INDEX_REFRESH) {
+            return;
+        }
+
+        let alliance_ids = match self.esi.get_alliance_ids().await {
+            Ok(alliance_ids) => alliance_ids,
+            Err(err) => {
+                tracing::warn!(?err, "couldn't index alliance tickers");
+                return;
+            }
+        };
+
+        tracing::info!(alliances = alliance_ids.len(), "index alliance tickers");
+
+        let mut failed = 0;
+
+        for chunk in alliance_ids.chunks(TICKER_INDEX_CONCURRENCY) {
+            let alliances = join_all(chunk.iter().map(|id| self.get_alliance(*id))).await;
+
+            failed += alliances.iter().filter(|result| result.is_err()).count();
+        }
+
+        if failed > 0 {
+            tracing::warn!(failed, "couldn't index some alliance tickers");
+        }
+
+        let alliance_ids: HashSet<_> = alliance_ids.into_iter().collect();
+
+        self.tickers
+            .write()
+            .await
+            .retain(|(kind, id), _| *kind != EntityKind::Alliance || alliance_ids.contains(id));
+
+        *indexed_at = Some(Instant::now());
+    }
+
+    async fn index_corporation_tickers(&self) {
+        let corporations = self.esi.cached_corporations().await;
+        let mut tickers = self.tickers.write().await;
+
+        for (corporation_id, corporation) in corporations {
+            tickers.insert(
+                (EntityKind::Corporation, corporation_id),
+                TickerEntry {
+                    name: corporation.name,
+                    ticker: corporation.ticker,
+                },
+            );
+        }
+    }
+
+    async fn ticker_matches(&self, ticker: &str) -> Vec<EntityMatch> {
+        if ticker.chars().count() > MAX_TICKER_LENGTH {
+            return vec![];
+        }
+
+        self.index_alliance_tickers().await;
+        self.index_corporation_tickers().await;
+
+        let mut matches: Vec<_> = self
+            .tickers
+            .read()
+            .await
+            .iter()
+            .filter(|(_, entry)| entry.ticker.eq_ignore_ascii_case(ticker))
+            .map(|((kind, id), entry)| EntityMatch {
+                kind: *kind,
+                id: *id,
+                name: entry.name.clone(),
+            })
+            .collect();
+
+        matches.sort_by_key(|entity_match| (entity_match.kind, entity_match.id));
+        matches
+    }
+
+    pub async fn find_alliance(&self, query: &str) -> anyhow::Result<Lookup<Alliance>> {
+        let matches = self.resolve_entities(query).await?;
+
+        match single_match(matches, EntityKind::Alliance) {
+            Ok(alliance_id) => Ok(Lookup::Found(
+                alliance_id,
+                self.get_alliance(alliance_id).await?,
+            )),
+            Err(matches) if matches.is_empty() => Ok(Lookup::NotFound),
+            Err(matches) => Ok(Lookup::Ambiguous(matches)),
+        }
+    }
+
+    pub async fn get_alliance_corporation_ids(
+        &self,
+        alliance_id: EsiID,
+    ) -> anyhow::Result<Vec<EsiID>> {
+        Ok(self.esi.get_alliance_corporations(alliance_id).await?)
+    }
+
+    pub async fn get_name(&self, id: EsiID) -> anyhow::Result<Option<String>> {
+        Ok(self.esi.resolve_names(&[id]).await?.remove(&id))
+    }
+
+    pub async fn get_names(&self, ids: &[EsiID]) -> anyhow::Result<HashMap<EsiID, String>> {
+        Ok(self.esi.resolve_names(ids).await?)
+    }
+
+    pub async fn get_alliance_names(
+        &self,
+        alliance_ids: &[EsiID],
+    ) -> anyhow::Result<HashMap<EsiID, String>> {
+        Ok(self.esi.get_alliance_names(alliance_ids).await?)
+    }
+
+    // Warms the response cache so the corporations can be used right after without waiting
+    // for them one by one.
+    pub async fn prefetch(&self, corporation_ids: &[EsiID]) {
+        let mut corporation_ids = corporation_ids.to_vec();
+        corporation_ids.sort_unstable();
+        corporation_ids.dedup();
+
+        if corporation_ids.is_empty() {
+            return;
+        }
+
+        tracing::debug!(corporations = corporation_ids.len(), "prefetch esi data");
+
+        let corporations =
+            join_all(corporation_ids.iter().map(|id| self.get_corporation(*id))).await;
+
+        let failed = corporations.iter().filter(|result| result.is_err()).count();
+
+        if failed > 0 {
+            tracing::warn!(failed, "couldn't prefetch some esi data");
+        }
+    }
+
+    pub async fn get_system_names(&self, ids: &[EsiID]) -> anyhow::Result<HashMap<EsiID, String>> {
+        let mut names = HashMap::new();
+        let mut missing = vec![];
+
+        {
+            let system_names = self.system_names.read().await;
+
+            for id in ids {
+                match system_names.get(id) {
+                    Some(system_name) => {
+                        names.insert(*id, system_name.clone());
+                    }
+                    None => missing.push(*id),
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let resolved = self.esi.resolve_names(&missing).await?;
+
+            self.system_names.write().await.extend(resolved.clone());
+            names.extend(resolved);
+        }
+
+        Ok(names)
+    }
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<EsiError>(), Some(EsiError::NotFound))
+}
+
+fn single_match(matches: Vec<EntityMatch>, kind: EntityKind) -> Result<EsiID, Vec<EntityMatch>> {
+    let matches: Vec<_> = matches
+        .into_iter()
+        .filter(|entity_match| entity_match.kind == kind)
+        .collect();
+
+    match matches.as_slice() {
+        [entity_match] => Ok(entity_match.id),
+        _ => Err(matches),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use reqwest::Url;
+    use tracing_test::traced_test;
+    use wiremock::{
+        matchers::{body_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::esi::{EntityKind, EntityMatch, Esi, EsiClient, EsiError};
+
+    use super::{single_match, InformationService, Lookup};
+
+    fn information_service(server: &MockServer) -> InformationService {
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+
+        InformationService::new(Arc::new(Esi::new().with_base_url(base_url)))
+    }
+
+    async fn mount_system(server: &MockServer, expected_requests: u64) {
+        Mock::given(method("GET"))
+            .and(path("/universe/systems/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "system_id": 1,
+                "constellation_id": 2,
+                "name": "Jita",
+                "security_status": 0.9
+            })))
+            .expect(expected_requests)
+            .mount(server)
+            .await;
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_corporation_caches_not_found() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/corporations/1/"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let information = information_service(&server);
+
+        assert!(information.get_corporation(1).await.is_err());
+
+        let error = information.get_corporation(1).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<EsiError>(),
+            Some(EsiError::NotFound)
+        ));
+
+        information.clear_negative_cache().await;
+
+        assert!(information.get_corporation(1).await.is_err());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn not_found_alliance_doesnt_hide_corporation() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/98000001/"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        mount_corporation(&server, 98000001, "B0RT").await;
+
+        let information = information_service(&server);
+
+        assert!(information.get_alliance(98000001).await.is_err());
+        assert!(information.get_alliance(98000001).await.is_err());
+        assert!(information.get_corporation(98000001).await.unwrap().ticker == "B0RT");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_system_names_resolves_in_bulk() {
+        let server = MockServer::start().await;
+        mount_system(&server, 1).await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/names/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "category": "solar_system", "id": 2, "name": "Amarr" },
+                { "category": "solar_system", "id": 3, "name": "Dodixie" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let information = information_service(&server);
+
+        information.get_system(1).await.unwrap();
+
+        let names = information.get_system_names(&[1, 2, 3]).await.unwrap();
+        let cached_names = information.get_system_names(&[2, 3]).await.unwrap();
+
+        assert!(names.len() == 3);
+        assert!(names[&1] == "Jita");
+        assert!(names[&2] == "Amarr");
+        assert!(cached_names[&3] == "Dodixie");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_system_uses_cached_response() {
+        let server = MockServer::start().await;
+        mount_system(&server, 1).await;
+
+        let information = information_service(&server);
+
+        information.get_system(1).await.unwrap();
+        let system = information.get_system(1).await.unwrap();
+
+        assert!(system.name == "Jita");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_system_refetches_expired_response() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/universe/systems/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "system_id": 1,
+                        "constellation_id": 2,
+                        "name": "Jita",
+                        "security_status": 0.9
+                    }))
+                    .insert_header("Cache-Control", "max-age=0"),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let information = information_service(&server);
+
+        information.get_system(1).await.unwrap();
+        information.get_system(1).await.unwrap();
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_system_region_resolves_through_constellation() {
+        let server = MockServer::start().await;
+        mount_system(&server, 1).await;
+
+        Mock::given(method("GET"))
+            .and(path("/universe/constellations/2/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "constellation_id": 2,
+                "name": "Kimotoro",
+                "region_id": 3
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
 
-        if let Some(system) = systems.get(&id) {
-            Ok(system.clone())
-        } else {
-            let system = self.esi.get_system(id).await?;
+        Mock::given(method("GET"))
+            .and(path("/universe/regions/3/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "region_id": 3,
+                "name": "The Forge"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
 
-            systems.insert(id, system.clone());
+        let information = information_service(&server);
 
-            Ok(system)
+        let region = information.get_system_region(1).await.unwrap();
+        let cached_region = information.get_system_region(1).await.unwrap();
+
+        assert!(region.name == "The Forge");
+        assert!(cached_region.region_id == 3);
+    }
+
+    async fn mount_corporation(server: &MockServer, corporation_id: u64, ticker: &str) {
+        Mock::given(method("GET"))
+            .and(path(format!("/corporations/{}/", corporation_id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "ceo_id": 2112000001_u64,
+                "creator_id": 2112000001_u64,
+                "member_count": 10,
+                "name": format!("Corporation {}", corporation_id),
+                "tax_rate": 0.1,
+                "ticker": ticker
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn get_names_resolves_in_one_request() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/universe/names/"))
+            .and(body_json(vec![98000001, 99000001]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "category": "corporation", "id": 98000001, "name": "Corporation 1" },
+                { "category": "alliance", "id": 99000001, "name": "Alliance 1" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let information = information_service(&server);
+
+        let names = information
+            .get_names(&[99000001, 98000001, 99000001])
+            .await
+            .unwrap();
+
+        assert!(names[&98000001] == "Corporation 1");
+        assert!(names[&99000001] == "Alliance 1");
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn prefetch_fetches_each_id_once_concurrently() {
+        let server = MockServer::start().await;
+        let delay = Duration::from_millis(300);
+
+        for corporation_id in [98000001, 98000002, 98000003] {
+            Mock::given(method("GET"))
+                .and(path(format!("/corporations/{}/", corporation_id)))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({
+                            "ceo_id": 2112000001_u64,
+                            "creator_id": 2112000001_u64,
+                            "member_count": 10,
+                            "name": format!("Corporation {}", corporation_id),
+                            "tax_rate": 0.1,
+                            "ticker": "CORP"
+                        }))
+                        .set_delay(delay),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let information = information_service(&server);
+        let ids = [98000001, 98000002, 98000003, 98000001];
+
+        let started = std::time::Instant::now();
+        information.prefetch(&ids).await;
+        let elapsed = started.elapsed();
+
+        information.prefetch(&ids).await;
+        information.get_corporation(98000002).await.unwrap();
+
+        assert!(server.received_requests().await.unwrap().len() == 3);
+        assert!(elapsed < delay * 2);
+    }
+
+    async fn mount_universe_ids(server: &MockServer, body: serde_json::Value) {
+        Mock::given(method("POST"))
+            .and(path("/universe/ids/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn resolve_entities_by_name() {
+        let server = MockServer::start().await;
+
+        mount_universe_ids(
+            &server,
+            serde_json::json!({
+                "alliances": [{ "id": 1354830081, "name": "Goonswarm Federation" }],
+                "corporations": [{ "id": 98000001, "name": "Goonswarm Federation" }]
+            }),
+        )
+        .await;
+
+        let information = information_service(&server);
+        let matches = information
+            .resolve_entities("Goonswarm Federation")
+            .await
+            .unwrap();
+
+        assert!(
+            matches
+                == vec![
+                    EntityMatch {
+                        kind: EntityKind::Alliance,
+                        id: 1354830081,
+                        name: "Goonswarm Federation".to_string(),
+                    },
+                    EntityMatch {
+                        kind: EntityKind::Corporation,
+                        id: 98000001,
+                        name: "Goonswarm Federation".to_string(),
+                    },
+                ]
+        );
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn resolve_entities_by_corporation_id() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/98000001/"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        mount_corporation(&server, 98000001, "B0RT").await;
+
+        let information = information_service(&server);
+        let expected = vec![EntityMatch {
+            kind: EntityKind::Corporation,
+            id: 98000001,
+            name: "Corporation 98000001".to_string(),
+        }];
+
+        assert!(information.resolve_entities("98000001").await.unwrap() == expected);
+        assert!(information.resolve_entities("98000001").await.unwrap() == expected);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn resolve_entities_by_alliance_ticker() {
+        let server = MockServer::start().await;
+
+        mount_universe_ids(&server, serde_json::json!({})).await;
+
+        Mock::given(method("GET"))
+            .and(path("/alliances/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![99000001, 99000002]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        for (alliance_id, ticker) in [(99000001, "GUNS"), (99000002, "B0RT")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/alliances/{}/", alliance_id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "creator_corporation_id": 98000001,
+                    "creator_id": 2112000001_u64,
+                    "date_founded": "2010-11-04T13:11:00Z",
+                    "name": format!("Alliance {}", alliance_id),
+                    "ticker": ticker
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
         }
+
+        let information = information_service(&server);
+        let expected = vec![EntityMatch {
+            kind: EntityKind::Alliance,
+            id: 99000001,
+            name: "Alliance 99000001".to_string(),
+        }];
+
+        assert!(information.resolve_entities("guns").await.unwrap() == expected);
+        assert!(information.resolve_entities("GUNS").await.unwrap() == expected);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn resolve_entities_by_cached_corporation_ticker() {
+        let server = MockServer::start().await;
+
+        mount_universe_ids(&server, serde_json::json!({})).await;
+        mount_corporation(&server, 98000001, "B0RT").await;
+
+        let base_url = Url::parse(&format!("{}/", server.uri())).unwrap();
+        let esi = Arc::new(Esi::new().with_base_url(base_url));
+        let information = InformationService::new(esi.clone());
+
+        assert!(information
+            .resolve_entities("b0rt")
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Fetched by someone else sharing the response cache, like the corporations service.
+        esi.get_corporation(98000001).await.unwrap();
+
+        assert!(
+            information.resolve_entities("b0rt").await.unwrap()
+                == vec![EntityMatch {
+                    kind: EntityKind::Corporation,
+                    id: 98000001,
+                    name: "Corporation 98000001".to_string(),
+                }]
+        );
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn find_alliance_ambiguous_and_missing() {
+        let server = MockServer::start().await;
+
+        mount_universe_ids(&server, serde_json::json!({})).await;
+        mount_corporation(&server, 98000001, "DUPE").await;
+        mount_corporation(&server, 98000002, "DUPE").await;
+
+        let information = information_service(&server);
+
+        information.get_corporation(98000001).await.unwrap();
+        information.get_corporation(98000002).await.unwrap();
+
+        assert!(information.resolve_entities("DUPE").await.unwrap().len() == 2);
+        assert!(matches!(
+            information.find_alliance("DUPE").await.unwrap(),
+            Lookup::NotFound
+        ));
+        assert!(matches!(
+            single_match(
+                information.resolve_entities("DUPE").await.unwrap(),
+                EntityKind::Corporation
+            ),
+            Err(matches) if matches.len() == 2
+        ));
     }
 }
@@ -1,5 +1,13 @@
+pub mod adm_acks;
 pub mod adm_configuration;
+#[cfg(feature = "adm-history")]
+pub mod adm_history;
 pub mod adm_notification_service;
 pub mod adm_service;
+pub mod channel_config;
+pub mod corp_watch;
 pub mod corporations_service;
+pub mod event_log;
 pub mod information_service;
+pub mod standings;
+pub mod vulnerability_service;
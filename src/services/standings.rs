@@ -0,0 +1,95 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::esi::EsiID;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Standing {
+    Friendly,
+    Neutral,
+    Hostile,
+}
+
+impl std::fmt::Display for Standing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Standing::Friendly => write!(f, "Friendly"),
+            Standing::Neutral => write!(f, "Neutral"),
+            Standing::Hostile => write!(f, "Hostile"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Standings {
+    alliances: Arc<HashMap<EsiID, Standing>>,
+}
+
+impl Standings {
+    pub async fn load_from(path: impl AsRef<Path>) -> anyhow::Result<Standings> {
+        let alliances = match fs::read_to_string(path).await {
+            Ok(json_data) => serde_json::from_str(&json_data)?,
+            Err(_) => Default::default(),
+        };
+
+        Ok(Standings {
+            alliances: Arc::new(alliances),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.alliances.len()
+    }
+
+    pub fn get(&self, alliance_id: EsiID) -> Option<Standing> {
+        self.alliances.get(&alliance_id).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::{Standing, Standings};
+
+    #[traced_test]
+    #[tokio::test]
+    async fn load_standings() {
+        let path = std::env::temp_dir().join("alliance_squawk_standings_test.json");
+        std::fs::write(&path, r#"{"99000001":"Friendly","99000002":"Hostile"}"#).unwrap();
+
+        let standings = Standings::load_from(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(standings.len() == 2);
+        assert!(standings.get(99000001) == Some(Standing::Friendly));
+        assert!(standings.get(99000002) == Some(Standing::Hostile));
+        assert!(standings.get(99000003).is_none());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn load_missing_standings() {
+        let standings = Standings::load_from(
+            std::env::temp_dir().join("alliance_squawk_missing_standings.json"),
+        )
+        .await
+        .unwrap();
+
+        assert!(standings.len() == 0);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn load_invalid_standings() {
+        let path = std::env::temp_dir().join("alliance_squawk_invalid_standings_test.json");
+        std::fs::write(&path, r#"{"99000001":"Blue"}"#).unwrap();
+
+        let result = Standings::load_from(&path).await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,256 @@
+use std::{collections::HashSet, env, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    bot::BotNotification,
+    esi::{EsiID, SovereigntyStructure},
+    shutdown::{self, ShutdownReceiver},
+};
+
+use super::adm_service::AdmService;
+
+const DEFAULT_LEAD_MINUTES: i64 = 30;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VulnerabilityWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl VulnerabilityWindow {
+    fn from_structure(structure: &SovereigntyStructure) -> Option<VulnerabilityWindow> {
+        Some(VulnerabilityWindow {
+            start: structure.vulnerable_start_time?,
+            end: structure.vulnerable_end_time?,
+        })
+    }
+
+    pub fn should_alert(&self, now: DateTime<Utc>, lead_time: chrono::Duration) -> bool {
+        now >= self.start - lead_time && now < self.end
+    }
+}
+
+pub struct VulnerabilityService {
+    adm: AdmService,
+    notifications: UnboundedSender<BotNotification>,
+    lead_time: chrono::Duration,
+    alerted: HashSet<(EsiID, DateTime<Utc>)>,
+}
+
+impl VulnerabilityService {
+    pub fn new(adm: AdmService, notifications: UnboundedSender<BotNotification>) -> Self {
+        let lead_time = chrono::Duration::minutes(lead_minutes(
+            env::var("VULNERABILITY_LEAD_MINUTES").ok().as_deref(),
+        ));
+
+        tracing::info!(
+            lead_minutes = lead_time.num_minutes(),
+            "vulnerability alert lead time"
+        );
+
+        VulnerabilityService {
+            adm,
+            notifications,
+            lead_time,
+            alerted: Default::default(),
+        }
+    }
+
+    async fn send_vulnerability_alerts(&mut self, now: DateTime<Utc>) -> anyhow::Result<()> {
+        let structures = self.adm.alliance_structures().await?;
+
+        self.alerted.retain(|(_, start)| {
+            structures
+                .iter()
+                .filter_map(VulnerabilityWindow::from_structure)
+                .any(|window| window.start == *start && now < window.end)
+        });
+
+        for structure in structures {
+            let Some(window) = VulnerabilityWindow::from_structure(&structure) else {
+                continue;
+            };
+
+            if !window.should_alert(now, self.lead_time)
+                || !self.alerted.insert((structure.structure_id, window.start))
+            {
+                continue;
+            }
+
+            tracing::info!(
+                system_id = structure.solar_system_id,
+                structure_id = structure.structure_id,
+                ?window,
+                "structure becoming vulnerable"
+            );
+
+            if self
+                .notifications
+                .send(BotNotification::NotifyVulnerable(
+                    structure.solar_system_id,
+                    window,
+                ))
+                .is_err()
+            {
+                return Err(anyhow::Error::msg("couldn't send notification to bot")
+                    .context("bot not running"));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn run(&mut self, mut shutdown: ShutdownReceiver) -> anyhow::Result<()> {
+        loop {
+            if let Err(err) = self.send_vulnerability_alerts(Utc::now()).await {
+                tracing::error!(?err, "couldn't check vulnerability windows");
+            }
+
+            tokio::select! {
+                _ = shutdown::requested(&mut shutdown) => break,
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+            }
+        }
+
+        tracing::info!("vulnerability service stopped");
+
+        Ok(())
+    }
+}
+
+fn lead_minutes(lead_minutes: Option<&str>) -> i64 {
+    match lead_minutes.map(str::parse::<i64>) {
+        Some(Ok(lead_minutes)) if lead_minutes >= 0 => lead_minutes,
+        Some(_) => {
+            tracing::warn!("invalid vulnerability lead time, using default");
+            DEFAULT_LEAD_MINUTES
+        }
+        None => DEFAULT_LEAD_MINUTES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use chrono::{Duration, TimeZone, Utc};
+    use tracing_test::traced_test;
+
+    use crate::{
+        bot::BotNotification,
+        esi::{MockEsi, SovereigntyStructure},
+        services::{
+            adm_configuration::AdmConfiguration,
+            adm_service::{AdmService, SovStructureType},
+            information_service::InformationService,
+        },
+    };
+
+    use super::{lead_minutes, VulnerabilityService, VulnerabilityWindow};
+
+    const ALLIANCE_ID: u64 = 99000001;
+
+    fn window() -> VulnerabilityWindow {
+        VulnerabilityWindow {
+            start: Utc.with_ymd_and_hms(2024, 1, 15, 22, 0, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2024, 1, 16, 2, 0, 0).unwrap(),
+        }
+    }
+
+    #[traced_test]
+    #[test]
+    fn should_alert_before_window() {
+        let window = window();
+
+        assert!(!window.should_alert(window.start - Duration::minutes(31), Duration::minutes(30)));
+        assert!(window.should_alert(window.start - Duration::minutes(30), Duration::minutes(30)));
+        assert!(window.should_alert(window.start - Duration::minutes(1), Duration::minutes(30)));
+    }
+
+    #[traced_test]
+    #[test]
+    fn should_alert_within_window() {
+        let window = window();
+
+        assert!(window.should_alert(window.start, Duration::minutes(30)));
+        assert!(window.should_alert(window.end - Duration::minutes(1), Duration::zero()));
+    }
+
+    #[traced_test]
+    #[test]
+    fn should_not_alert_after_window() {
+        let window = window();
+
+        assert!(!window.should_alert(window.end, Duration::minutes(30)));
+        assert!(!window.should_alert(window.end + Duration::hours(1), Duration::minutes(30)));
+    }
+
+    #[traced_test]
+    #[test]
+    fn lead_minutes_defaults() {
+        assert!(lead_minutes(None) == 30);
+        assert!(lead_minutes(Some("45")) == 45);
+        assert!(lead_minutes(Some("-5")) == 30);
+        assert!(lead_minutes(Some("soon")) == 30);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn alerts_once_per_window() {
+        let window = window();
+        let esi = Arc::new(MockEsi {
+            sovereignty_structures: vec![SovereigntyStructure {
+                alliance_id: ALLIANCE_ID,
+                solar_system_id: 30000142,
+                structure_id: 1,
+                structure_type_id: 32458,
+                vulnerability_occupancy_level: Some(4.0),
+                vulnerable_end_time: Some(window.end),
+                vulnerable_start_time: Some(window.start),
+            }],
+            ..Default::default()
+        });
+        let information = InformationService::new(esi.clone());
+        let configuration = AdmConfiguration::load_configuration_from(
+            std::env::temp_dir().join("alliance_squawk_missing_adm.toml"),
+        )
+        .await
+        .unwrap();
+        let adm = AdmService::new(
+            esi,
+            ALLIANCE_ID,
+            HashSet::from([SovStructureType::IHub]),
+            information,
+            configuration,
+        );
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut service = VulnerabilityService::new(adm, sender);
+
+        service
+            .send_vulnerability_alerts(window.start - Duration::hours(2))
+            .await
+            .unwrap();
+
+        assert!(receiver.try_recv().is_err());
+
+        service
+            .send_vulnerability_alerts(window.start - Duration::minutes(10))
+            .await
+            .unwrap();
+        service
+            .send_vulnerability_alerts(window.start - Duration::minutes(9))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(BotNotification::NotifyVulnerable(30000142, alerted)) if alerted == window
+        ));
+        assert!(receiver.try_recv().is_err());
+    }
+}
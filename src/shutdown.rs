@@ -0,0 +1,35 @@
+use tokio::sync::watch;
+
+pub type ShutdownSender = watch::Sender<bool>;
+pub type ShutdownReceiver = watch::Receiver<bool>;
+
+pub fn channel() -> (ShutdownSender, ShutdownReceiver) {
+    watch::channel(false)
+}
+
+pub async fn requested(shutdown: &mut ShutdownReceiver) {
+    // A dropped sender means nobody can signal anymore, treat it as a shutdown too.
+    let _ = shutdown.wait_for(|shutdown| *shutdown).await;
+}
+
+#[cfg(unix)]
+async fn terminate() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    signal(SignalKind::terminate())
+        .expect("install SIGTERM handler")
+        .recv()
+        .await;
+}
+
+#[cfg(not(unix))]
+async fn terminate() {
+    std::future::pending::<()>().await
+}
+
+pub async fn wait_for_signal() {
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => tracing::info!("received SIGINT"),
+        _ = terminate() => tracing::info!("received SIGTERM"),
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+
+use crate::{bot::BotNotification, health::unix_now};
+
+#[async_trait]
+pub trait NotificationSink: std::fmt::Debug + Send + Sync {
+    async fn send(&self, notification: &BotNotification) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogSink;
+
+#[async_trait]
+impl NotificationSink for LogSink {
+    async fn send(&self, notification: &BotNotification) -> anyhow::Result<()> {
+        tracing::info!(?notification, "notification");
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileRecord<'a> {
+    timestamp: u64,
+    notification: &'a BotNotification,
+}
+
+// Appends one JSON object per notification, the lock keeps concurrent writes from
+// interleaving lines.
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSink {
+    pub fn new(path: impl AsRef<Path>) -> FileSink {
+        FileSink {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for FileSink {
+    async fn send(&self, notification: &BotNotification) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(&FileRecord {
+            timestamp: unix_now(),
+            notification,
+        })?;
+        line.push('\n');
+
+        let _lock = self.lock.lock().await;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::{
+        bot::BotNotification,
+        services::adm_service::{Status, SystemAdm},
+    };
+
+    use super::{FileSink, LogSink, NotificationSink};
+
+    #[traced_test]
+    #[tokio::test]
+    async fn file_sink_writes_json_lines() {
+        let path = std::env::temp_dir().join("alliance_squawk_sink_test.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let sink = FileSink::new(&path);
+
+        sink.send(&BotNotification::NotifyCorpJoinAlliance(99000001, 98000001))
+            .await
+            .unwrap();
+        sink.send(&BotNotification::NotifyAdm(SystemAdm {
+            previous_adm: Some(1.2),
+            ..SystemAdm::for_test(30000142, Status::Critical(0.8))
+        }))
+        .await
+        .unwrap();
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<serde_json::Value> = data
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert!(lines.len() == 2);
+        assert!(lines.iter().all(|line| line["timestamp"].is_u64()));
+        assert!(
+            lines[0]["notification"]
+                == serde_json::json!({ "NotifyCorpJoinAlliance": [99000001, 98000001] })
+        );
+        assert!(lines[1]["notification"]["NotifyAdm"]["system_id"] == 30000142);
+        assert!(lines[1]["notification"]["NotifyAdm"]["status"]["Critical"].is_number());
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn log_sink_logs_notification() {
+        LogSink
+            .send(&BotNotification::NotifySovLost(30000142))
+            .await
+            .unwrap();
+
+        assert!(logs_contain("NotifySovLost(30000142)"));
+    }
+}
@@ -0,0 +1,161 @@
+use std::env;
+
+use anyhow::Context;
+use serenity::builder::{CreateEmbed, CreateEmbedAuthor};
+
+use crate::services::standings::Standing;
+
+pub type Color = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub warning: Color,
+    pub critical: Color,
+    pub recovered: Color,
+    pub corp_join: Color,
+    pub corp_leave: Color,
+    pub friendly: Color,
+    pub hostile: Color,
+    pub sov_lost: Color,
+    pub vulnerable: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            warning: (238, 210, 2),
+            critical: (255, 103, 0),
+            recovered: (87, 242, 135),
+            corp_join: (188, 69, 255),
+            corp_leave: (188, 69, 255),
+            friendly: (52, 152, 219),
+            hostile: (237, 66, 69),
+            sov_lost: (237, 66, 69),
+            vulnerable: (255, 165, 0),
+        }
+    }
+}
+
+impl Theme {
+    pub fn from_env() -> anyhow::Result<Theme> {
+        let defaults = Theme::default();
+
+        Ok(Theme {
+            warning: color_from_env("THEME_WARNING_COLOR", defaults.warning)?,
+            critical: color_from_env("THEME_CRITICAL_COLOR", defaults.critical)?,
+            recovered: color_from_env("THEME_RECOVERED_COLOR", defaults.recovered)?,
+            corp_join: color_from_env("THEME_CORP_JOIN_COLOR", defaults.corp_join)?,
+            corp_leave: color_from_env("THEME_CORP_LEAVE_COLOR", defaults.corp_leave)?,
+            friendly: color_from_env("THEME_FRIENDLY_COLOR", defaults.friendly)?,
+            hostile: color_from_env("THEME_HOSTILE_COLOR", defaults.hostile)?,
+            sov_lost: color_from_env("THEME_SOV_LOST_COLOR", defaults.sov_lost)?,
+            vulnerable: color_from_env("THEME_VULNERABLE_COLOR", defaults.vulnerable)?,
+        })
+    }
+
+    pub fn standing_color(&self, standing: Option<Standing>, default: Color) -> Color {
+        match standing {
+            Some(Standing::Friendly) => self.friendly,
+            Some(Standing::Hostile) => self.hostile,
+            Some(Standing::Neutral) | None => default,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Branding {
+    pub ticker: Option<String>,
+    pub name: Option<String>,
+}
+
+impl Branding {
+    pub fn title(&self, title: impl std::fmt::Display) -> String {
+        match &self.ticker {
+            Some(ticker) => format!("[{}] {}", ticker, title),
+            None => title.to_string(),
+        }
+    }
+
+    pub fn embed(&self, title: impl std::fmt::Display) -> CreateEmbed {
+        let embed = CreateEmbed::new().title(self.title(title));
+
+        match &self.name {
+            Some(name) => embed.author(CreateEmbedAuthor::new(name)),
+            None => embed,
+        }
+    }
+}
+
+fn color_from_env(name: &str, default: Color) -> anyhow::Result<Color> {
+    match env::var(name) {
+        Ok(value) => parse_hex_color(&value).with_context(|| format!("`{}` is invalid", name)),
+        Err(_) => Ok(default),
+    }
+}
+
+pub fn parse_hex_color(value: &str) -> anyhow::Result<Color> {
+    let hex = value.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!(
+            "`{}` is not a hex color like `#eed202`",
+            value
+        ));
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16);
+
+    Ok((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::services::standings::Standing;
+
+    use super::{parse_hex_color, Branding, Theme};
+
+    #[traced_test]
+    #[test]
+    fn parse_hex_color_valid() {
+        assert!(parse_hex_color("#eed202").unwrap() == (238, 210, 2));
+        assert!(parse_hex_color("FF6700").unwrap() == (255, 103, 0));
+        assert!(parse_hex_color(" #57f287 ").unwrap() == (87, 242, 135));
+    }
+
+    #[traced_test]
+    #[test]
+    fn parse_hex_color_invalid() {
+        assert!(parse_hex_color("").is_err());
+        assert!(parse_hex_color("#fff").is_err());
+        assert!(parse_hex_color("#eed2020").is_err());
+        assert!(parse_hex_color("#gggggg").is_err());
+        assert!(parse_hex_color("#+eed20").is_err());
+        assert!(parse_hex_color("rgb(1,2,3)").is_err());
+    }
+
+    #[traced_test]
+    #[test]
+    fn branding_prefixes_title_with_ticker() {
+        let branding = Branding {
+            ticker: Some("TEST".to_string()),
+            name: Some("Test Alliance Please Ignore".to_string()),
+        };
+
+        assert!(branding.title("ADM Status Report") == "[TEST] ADM Status Report");
+        assert!(Branding::default().title("ADM Status Report") == "ADM Status Report");
+    }
+
+    #[traced_test]
+    #[test]
+    fn standing_color_overrides_event_color() {
+        let theme = Theme::default();
+
+        assert!(theme.standing_color(Some(Standing::Friendly), theme.corp_join) == theme.friendly);
+        assert!(theme.standing_color(Some(Standing::Hostile), theme.corp_join) == theme.hostile);
+        assert!(theme.standing_color(Some(Standing::Neutral), theme.corp_join) == theme.corp_join);
+        assert!(theme.standing_color(None, theme.corp_leave) == theme.corp_leave);
+    }
+}